@@ -0,0 +1,183 @@
+//! Iso-contours of sampled distances with marching squares.
+
+use crate::{
+    grid::Grid,
+    sdf::{Sdf, SignedDistance},
+};
+use glam::{vec2, UVec2, Vec2};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A polyline along an iso-contour. The inside, where the values are below
+/// the level, is on the left: closed contours go counterclockwise around
+/// shapes and clockwise around holes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Contour {
+    pub points: Vec<Vec2>,
+    /// Whether the last point connects back to the first one. Contours are
+    /// open where they leave the sampled region.
+    pub closed: bool,
+}
+
+impl Contour {
+    /// Positive for counterclockwise contours, as if they were closed.
+    pub fn signed_area(&self) -> f32 {
+        let n = self.points.len();
+        0.5 * (0..n)
+            .map(|i| self.points[i].perp_dot(self.points[(i + 1) % n]))
+            .sum::<f32>()
+    }
+}
+
+impl Grid<f32> {
+    /// The contours at `level` between the centers of the cells.
+    pub fn contours(&self, level: f32) -> Vec<Contour> {
+        if self.w == 0 || self.h == 0 {
+            return Vec::new();
+        }
+        // Rows of the lattice go up, unlike the rows of the grid
+        let lattice = Lattice {
+            nx: self.w,
+            ny: self.h,
+            values: (0..self.h)
+                .rev()
+                .flat_map(|y| (0..self.w).map(move |x| (x, y)))
+                .map(|(x, y)| self.get(x, y))
+                .collect(),
+            origin: self.position(0, self.h - 1),
+            step: Vec2::splat(1.0 / self.h as f32),
+        };
+        lattice.contours(level, |i, j| {
+            let corners = [(i, j), (i + 1, j), (i, j + 1), (i + 1, j + 1)];
+            0.25 * corners.map(|(i, j)| lattice.get(i, j)).iter().sum::<f32>()
+        })
+    }
+}
+
+/// The contours at `level` of `sdf` sampled over the rectangle from `min` to
+/// `max`, split into `cells` cells. Ambiguous cells are resolved with the
+/// value at their center.
+pub fn contours<S: Sdf>(sdf: &S, min: Vec2, max: Vec2, cells: UVec2, level: f32) -> Vec<Contour> {
+    let step = (max - min) / cells.as_vec2();
+    let (nx, ny) = (cells.x as usize + 1, cells.y as usize + 1);
+    let lattice = Lattice {
+        nx,
+        ny,
+        values: (0..ny)
+            .flat_map(|j| (0..nx).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                sdf.signed_distance(min + step * vec2(i as f32, j as f32))
+                    .value()
+            })
+            .collect(),
+        origin: min,
+        step,
+    };
+    lattice.contours(level, |i, j| {
+        let center = min + step * vec2(i as f32 + 0.5, j as f32 + 0.5);
+        sdf.signed_distance(center).value()
+    })
+}
+
+/// Values at the points `origin + step * (i, j)`, in rows of increasing `j`.
+struct Lattice {
+    nx: usize,
+    ny: usize,
+    values: Vec<f32>,
+    origin: Vec2,
+    step: Vec2,
+}
+
+/// A horizontal edge from `(i, j)` to `(i + 1, j)` or a vertical one from
+/// `(i, j)` to `(i, j + 1)`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Edge {
+    Horizontal(usize, usize),
+    Vertical(usize, usize),
+}
+
+impl Lattice {
+    fn get(&self, i: usize, j: usize) -> f32 {
+        self.values[j * self.nx + i]
+    }
+
+    fn point(&self, i: usize, j: usize) -> Vec2 {
+        self.origin + self.step * vec2(i as f32, j as f32)
+    }
+
+    /// Where the edge crosses `level`, by linear interpolation.
+    fn crossing(&self, edge: Edge, level: f32) -> Vec2 {
+        let ((i0, j0), (i1, j1)) = match edge {
+            Edge::Horizontal(i, j) => ((i, j), (i + 1, j)),
+            Edge::Vertical(i, j) => ((i, j), (i, j + 1)),
+        };
+        let (a, b) = (self.get(i0, j0), self.get(i1, j1));
+        let t = ((level - a) / (b - a)).clamp(0.0, 1.0);
+        self.point(i0, j0).lerp(self.point(i1, j1), t)
+    }
+
+    fn contours(&self, level: f32, center: impl Fn(usize, usize) -> f32) -> Vec<Contour> {
+        if self.nx < 2 || self.ny < 2 {
+            return Vec::new();
+        }
+        // Segments from edge to edge with the inside on their left, by start
+        let mut segments = BTreeMap::new();
+        for j in 0..self.ny - 1 {
+            for i in 0..self.nx - 1 {
+                // The corners and edges counterclockwise from the bottom left,
+                // each edge going from its corner to the next one
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let edges = [
+                    Edge::Horizontal(i, j),
+                    Edge::Vertical(i + 1, j),
+                    Edge::Horizontal(i, j + 1),
+                    Edge::Vertical(i, j),
+                ];
+                let inside = corners.map(|(i, j)| self.get(i, j) < level);
+                // Exits go from an inside to an outside corner, entries
+                // the other way around, and they alternate
+                let crossings = (0..4)
+                    .filter(|&k| inside[k] != inside[(k + 1) % 4])
+                    .collect::<Vec<_>>();
+                let n = crossings.len();
+                // A saddle connects the inside corners if its center is inside
+                let next = n == 2 || center(i, j) < level;
+                for (c, &k) in crossings.iter().enumerate() {
+                    if inside[k] {
+                        let entry = if next { c + 1 } else { c + n - 1 };
+                        segments.insert(edges[k], edges[crossings[entry % n]]);
+                    }
+                }
+            }
+        }
+        self.link(segments, level)
+    }
+
+    /// Joins the segments into polylines, the open ones first.
+    fn link(&self, mut segments: BTreeMap<Edge, Edge>, level: f32) -> Vec<Contour> {
+        let mut starts = segments.keys().copied().collect::<Vec<_>>();
+        let ends = segments.values().copied().collect::<BTreeSet<_>>();
+        // Open contours start on the boundary, which no segment ends at
+        starts.sort_by_key(|edge| ends.contains(edge));
+        let mut contours = Vec::new();
+        for start in starts {
+            if !segments.contains_key(&start) {
+                continue;
+            }
+            let mut points = vec![self.crossing(start, level)];
+            let mut edge = start;
+            while let Some(next) = segments.remove(&edge) {
+                let point = self.crossing(next, level);
+                if points.last() != Some(&point) {
+                    points.push(point);
+                }
+                edge = next;
+            }
+            let closed = edge == start;
+            if closed && points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            contours.push(Contour { points, closed });
+        }
+        contours
+    }
+}
@@ -63,15 +63,18 @@ where
         iter.enumerate().for_each(|(i, value)| {
             let y = i / self.w;
             let x = i - y * self.w;
-            let p = vec2(
-                (x as f32 / self.w as f32 - 0.5) * ar,
-                0.5 - y as f32 / self.h as f32,
-            ) + 0.5 / self.w as f32;
+            let p = position(self.w, self.h, ar, x, y);
             debug_assert!(p.x.abs() < 0.5 * ar && p.y.abs() < 0.5);
             *value = sdf.signed_distance(p);
         });
     }
 
+    /// The center of the cell in column `x` and row `y`, the rows going down
+    /// from the top of the grid at `0.5`.
+    pub fn position(&self, x: usize, y: usize) -> Vec2 {
+        position(self.w, self.h, self.aspect_ratio(), x, y)
+    }
+
     pub fn resize(&mut self, w: usize, h: usize) {
         self.w = w;
         self.h = h;
@@ -97,3 +100,10 @@ where
         self.as_ref().signed_distance(p)
     }
 }
+
+fn position(w: usize, h: usize, ar: f32, x: usize, y: usize) -> Vec2 {
+    vec2(
+        ((x as f32 + 0.5) / w as f32 - 0.5) * ar,
+        0.5 - (y as f32 + 0.5) / h as f32,
+    )
+}
@@ -0,0 +1,107 @@
+use crate::sdf::Sdf3;
+use glam::{vec3, Vec3};
+
+/// A voxel grid over the cube of side 1 centered at the origin, stored in
+/// `x`, then `y`, then `z` order.
+pub struct Grid3<T> {
+    pub w: usize,
+    pub h: usize,
+    pub d: usize,
+    pub buffer: Vec<T>,
+}
+
+impl<#[cfg(feature = "rayon")] T: Send, #[cfg(not(feature = "rayon"))] T> Grid3<T>
+where
+    T: Default + Clone + Copy,
+{
+    pub fn new(w: usize, h: usize, d: usize) -> Self {
+        Self {
+            w,
+            h,
+            d,
+            buffer: vec![Default::default(); w * h * d],
+        }
+    }
+
+    pub fn from_sdf<
+        #[cfg(feature = "rayon")] S: Sdf3<T = T> + Sync,
+        #[cfg(not(feature = "rayon"))] S: Sdf3<T = T>,
+    >(
+        w: usize,
+        h: usize,
+        d: usize,
+        sdf: &S,
+    ) -> Self {
+        let mut result = Self::new(w, h, d);
+        result.update(sdf);
+        result
+    }
+
+    pub fn update<
+        #[cfg(feature = "rayon")] S: Sdf3<T = T> + Sync,
+        #[cfg(not(feature = "rayon"))] S: Sdf3<T = T>,
+    >(
+        &mut self,
+        sdf: &S,
+    ) {
+        #[cfg(feature = "rayon")]
+        use rayon::prelude::*;
+
+        let (w, h) = (self.w, self.h);
+        let size = self.size();
+
+        #[cfg(feature = "rayon")]
+        let iter = self.buffer.par_iter_mut();
+        #[cfg(not(feature = "rayon"))]
+        let iter = self.buffer.iter_mut();
+
+        iter.enumerate().for_each(|(i, value)| {
+            let x = i % w;
+            let y = i / w % h;
+            let z = i / (w * h);
+            let p = (vec3(x as f32, y as f32, z as f32) + 0.5) / size - 0.5;
+            *value = sdf.signed_distance(p);
+        });
+    }
+
+    fn size(&self) -> Vec3 {
+        vec3(self.w as f32, self.h as f32, self.d as f32)
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> T {
+        self.buffer[(z * self.h + y) * self.w + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: T) {
+        self.buffer[(z * self.h + y) * self.w + x] = value;
+    }
+}
+
+impl Grid3<f32> {
+    /// Trilinear interpolation between the centers of the voxels, clamped
+    /// to the outermost ones.
+    pub fn sample(&self, p: Vec3) -> f32 {
+        let size = self.size();
+        let q = ((p + 0.5) * size - 0.5).clamp(Vec3::ZERO, size - 1.0);
+        let i = q.floor().as_uvec3();
+        let t = q - i.as_vec3();
+        let j = (i + 1).min(size.as_uvec3() - 1);
+        let get = |x: u32, y: u32, z: u32| self.get(x as usize, y as usize, z as usize);
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let plane = |z: u32| {
+            lerp(
+                lerp(get(i.x, i.y, z), get(j.x, i.y, z), t.x),
+                lerp(get(i.x, j.y, z), get(j.x, j.y, z), t.x),
+                t.y,
+            )
+        };
+        lerp(plane(i.z), plane(j.z), t.z)
+    }
+}
+
+impl Sdf3 for Grid3<f32> {
+    type T = f32;
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        self.sample(p)
+    }
+}
@@ -1,9 +1,15 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "std")]
+pub mod contour;
 #[cfg(feature = "std")]
 pub mod grid;
+#[cfg(feature = "std")]
+pub mod grid3;
 pub mod gridref;
 pub mod primitives;
+pub mod primitives3;
+pub mod primitives3_enum;
 pub mod primitives_enum;
 pub mod sdf;
 
@@ -24,6 +30,99 @@ mod tests {
         let torus = Torus::new(0.2, 0.1);
         let d = torus.signed_distance(vec2(0.4, 0.0));
         assert_approx_eq!(d, 0.1);
+        let capsule = Capsule::new(vec2(-0.2, 0.0), vec2(0.2, 0.0), 0.1);
+        let d = capsule.signed_distance(vec2(0.5, 0.0));
+        assert_approx_eq!(d, 0.2);
+        let hexagon = Hexagon::new(0.2);
+        let d = hexagon.signed_distance(vec2(0.0, 0.5));
+        assert_approx_eq!(d, 0.3);
+
+        let (a, b, c) = (vec2(-0.2, -0.1), vec2(0.3, 0.2), vec2(0.1, -0.3));
+        let triangle = Triangle::new(a, b, c);
+        let polygon = Polygon::new([a, b, c]);
+        for p in [
+            vec2(0.0, 0.0),
+            vec2(0.5, 0.1),
+            vec2(-0.3, 0.4),
+            vec2(0.1, -0.2),
+        ] {
+            assert_approx_eq!(triangle.signed_distance(p), polygon.signed_distance(p));
+        }
+        assert!(triangle.signed_distance(vec2(0.05, -0.05)) < 0.0);
+
+        // The vertices of regular shapes are on their circle
+        let regular_polygon = RegularPolygon::new(0.3, 6);
+        let d = regular_polygon.signed_distance(vec2(0.0, 0.3));
+        assert_approx_eq!(d, 0.0);
+        let star = RegularStar::new(0.3, 5, 0.5);
+        let d = star.signed_distance(vec2(0.0, 0.3));
+        assert_approx_eq!(d, 0.0);
+    }
+
+    #[test]
+    fn blends() {
+        use sdf::{Blend, SignedDistance};
+        use strum::IntoEnumIterator;
+
+        let r = 0.1;
+        assert_approx_eq!(Blend::Smooth.union(0.0, 0.0, r), -0.25 * r);
+        assert_approx_eq!(Blend::Exponential.union(0.2, 0.2, r), 0.2 - r);
+        assert_approx_eq!(
+            Blend::Chamfer.union(0.0, 0.0, r),
+            -r * core::f32::consts::FRAC_1_SQRT_2
+        );
+        assert_approx_eq!(Blend::Round.union(0.0, 0.0, r), r - r * 2f32.sqrt());
+        for blend in Blend::iter() {
+            for (a, b) in [(0.3, -0.2), (-0.05, 0.02), (0.0, 0.0), (0.4, 0.45)] {
+                // A blend only removes material from the intersection and
+                // adds it to the union, and none without a radius
+                assert!(blend.union(a, b, r) <= a.min(b) + 1e-6);
+                assert!(blend.intersect(a, b, r) >= a.max(b) - 1e-6);
+                assert_eq!(blend.union(a, b, 0.0), a.union(&b));
+                assert_eq!(blend.intersect(a, b, 0.0), a.intersect(&b));
+                assert_eq!(blend.subtract(a, b, 0.0), a.subtract(&b));
+            }
+            if blend != Blend::Exponential {
+                // Far from the edges the blends are exact
+                assert_approx_eq!(blend.union(0.5, -0.1, r), -0.1);
+                assert_approx_eq!(blend.intersect(0.5, -0.1, r), 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn primitives3() {
+        use glam::{vec3, Vec3};
+        use primitives3::*;
+        use primitives3_enum::Shape3;
+        use sdf::Sdf3;
+        use strum::IntoEnumIterator;
+
+        let d = Sphere::new(0.2).signed_distance(vec3(0.0, 0.5, 0.0));
+        assert_approx_eq!(d, 0.3);
+        let cuboid = Cuboid::new(vec3(0.4, 0.2, 0.6));
+        assert_approx_eq!(cuboid.signed_distance(vec3(0.5, 0.0, 0.0)), 0.3);
+        assert_approx_eq!(cuboid.signed_distance(Vec3::ZERO), -0.1);
+        let torus = Torus::new(0.3, 0.1);
+        assert_approx_eq!(torus.signed_distance(vec3(0.0, 0.0, -0.3)), -0.1);
+        let capsule = Capsule::new(vec3(-0.2, 0.0, 0.0), vec3(0.2, 0.0, 0.0), 0.1);
+        assert_approx_eq!(capsule.signed_distance(vec3(0.1, 0.3, 0.0)), 0.2);
+        let cylinder = Cylinder::new(vec3(0.0, -0.2, 0.0), vec3(0.0, 0.2, 0.0), 0.1);
+        assert_approx_eq!(cylinder.signed_distance(vec3(0.3, 0.1, 0.0)), 0.2);
+        assert_approx_eq!(cylinder.signed_distance(vec3(0.0, 0.5, 0.05)), 0.3);
+
+        // The closed form gradients match the finite differences
+        for shape in Shape3::iter() {
+            for p in [
+                vec3(0.31, 0.17, -0.23),
+                vec3(-0.4, 0.05, 0.12),
+                vec3(0.02, -0.37, 0.44),
+                vec3(0.06, 0.04, -0.03),
+            ] {
+                let error = shape.gradient(p) - shape.derivative(p, 1e-3);
+                assert!(error.length() < 1e-2, "{shape:?} at {p}");
+            }
+        }
     }
 
     #[test]
@@ -46,4 +145,108 @@ mod tests {
         let d = grid.as_ref().signed_distance(vec2(0.4, 0.0));
         assert_approx_eq!(d, 0.1, E);
     }
+
+    #[test]
+    fn grid3() {
+        use glam::vec3;
+        use grid3::Grid3;
+        use primitives3::Sphere;
+        use sdf::Sdf3;
+
+        const N: usize = 32;
+        const E: f32 = 1.0 / (N as f32);
+
+        let sphere = Sphere::new(0.25);
+        let grid = Grid3::from_sdf(N, N, N, &sphere);
+        for p in [
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.1, -0.3, 0.2),
+            vec3(-0.4, 0.25, -0.05),
+        ] {
+            assert_approx_eq!(grid.sample(p), sphere.signed_distance(p), E);
+        }
+        // Between the centers of two voxels
+        let (a, b) = (
+            vec3(0.5, 2.5, 7.5) / N as f32 - 0.5,
+            vec3(1.5, 2.5, 7.5) / N as f32 - 0.5,
+        );
+        let mid = grid.sample(0.5 * (a + b));
+        assert_approx_eq!(mid, 0.5 * (grid.sample(a) + grid.sample(b)));
+        assert_approx_eq!(grid.sample(a), grid.get(0, 2, 7));
+    }
+
+    #[test]
+    fn contours() {
+        use core::f32::consts::PI;
+        use glam::{uvec2, Vec2};
+
+        let disk = Disk::new(0.3);
+        let contours = contour::contours(
+            &disk,
+            Vec2::splat(-0.5),
+            Vec2::splat(0.5),
+            uvec2(40, 40),
+            0.0,
+        );
+        assert_eq!(contours.len(), 1);
+        assert!(contours[0].closed);
+        assert_approx_eq!(contours[0].signed_area(), PI * 0.09, 0.005);
+        for p in &contours[0].points {
+            assert_approx_eq!(p.length(), 0.3, 1e-3);
+        }
+
+        // Holes go clockwise
+        let torus = Torus::new(0.25, 0.1);
+        let mut contours = contour::contours(
+            &torus,
+            Vec2::splat(-0.5),
+            Vec2::splat(0.5),
+            uvec2(50, 50),
+            0.0,
+        );
+        contours.sort_by(|a, b| b.signed_area().total_cmp(&a.signed_area()));
+        assert_eq!(contours.len(), 2);
+        assert_approx_eq!(contours[0].signed_area(), PI * 0.35 * 0.35, 0.01);
+        assert_approx_eq!(contours[1].signed_area(), -PI * 0.15 * 0.15, 0.01);
+
+        // Cut by the region
+        let contours = contour::contours(&disk, Vec2::ZERO, Vec2::splat(0.5), uvec2(20, 20), 0.0);
+        assert_eq!(contours.len(), 1);
+        assert!(!contours[0].closed);
+        assert_approx_eq!(contours[0].points[0].length(), 0.3, 1e-3);
+
+        let grid = Grid::from_sdf(64, 64, &disk);
+        let contours = grid.contours(0.0);
+        assert_eq!(contours.len(), 1);
+        for p in &contours[0].points {
+            assert_approx_eq!(p.length(), 0.3, 1.0 / 64.0);
+        }
+        assert!(Grid::<f32>::new(0, 0).contours(0.0).is_empty());
+        assert!(Grid::<f32>::new(8, 0).contours(0.0).is_empty());
+    }
+
+    #[test]
+    fn contour_saddles() {
+        use glam::{uvec2, Vec2};
+
+        /// Disks at two opposite corners of the square from -1 to 1
+        struct Disks(f32);
+        impl Sdf for Disks {
+            type T = f32;
+            fn signed_distance(&self, p: Vec2) -> f32 {
+                p.distance(Vec2::ONE).min(p.distance(-Vec2::ONE)) - self.0
+            }
+        }
+
+        // Whether the contours cut off the corners of the disks or the others
+        for (radius, connected) in [(1.2, false), (1.5, true)] {
+            let contours =
+                contour::contours(&Disks(radius), -Vec2::ONE, Vec2::ONE, uvec2(1, 1), 0.0);
+            assert_eq!(contours.len(), 2);
+            for contour in contours {
+                let mid = contour.points.iter().sum::<Vec2>() / contour.points.len() as f32;
+                assert_eq!(mid.x * mid.y < 0.0, connected);
+            }
+        }
+    }
 }
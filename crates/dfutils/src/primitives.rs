@@ -1,15 +1,46 @@
+mod capsule;
 mod cross;
 mod disk;
+mod equilateral_triangle;
+mod hexagon;
+mod isosceles_triangle;
+mod koch_snowflake;
 mod line_segment;
+mod pentagon;
 mod plane;
+mod polygon;
 mod ray;
 mod rectangle;
+mod regular_polygon;
+mod regular_star;
+mod sierpinski_triangle;
 mod torus;
+mod triangle;
 
+pub use capsule::*;
 pub use cross::*;
 pub use disk::*;
+pub use equilateral_triangle::*;
+pub use hexagon::*;
+pub use isosceles_triangle::*;
+pub use koch_snowflake::*;
 pub use line_segment::*;
+pub use pentagon::*;
 pub use plane::*;
+pub use polygon::*;
 pub use ray::*;
 pub use rectangle::*;
+pub use regular_polygon::*;
+pub use regular_star::*;
+pub use sierpinski_triangle::*;
 pub use torus::*;
+pub use triangle::*;
+
+use glam::Vec2;
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// The vector from the closest point on the segment from the origin to `e` to `p`.
+fn reject_from_segment(p: Vec2, e: Vec2) -> Vec2 {
+    p - e * (p.dot(e) / e.length_squared()).clamp(0.0, 1.0)
+}
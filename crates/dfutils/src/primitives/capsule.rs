@@ -0,0 +1,34 @@
+use super::LineSegment;
+use crate::sdf::Sdf;
+use glam::{vec2, Vec2};
+
+/// The points within `radius` of the segment from `a` to `b`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Capsule {
+    pub a: Vec2,
+    pub b: Vec2,
+    pub radius: f32,
+}
+
+impl Capsule {
+    pub const fn new(a: Vec2, b: Vec2, radius: f32) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl Default for Capsule {
+    fn default() -> Self {
+        Self {
+            a: vec2(-0.15, 0.0),
+            b: vec2(0.15, 0.0),
+            radius: 0.1,
+        }
+    }
+}
+
+impl Sdf for Capsule {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        LineSegment::new(self.a, self.b).signed_distance(p) - self.radius
+    }
+}
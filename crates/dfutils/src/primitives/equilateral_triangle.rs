@@ -0,0 +1,34 @@
+use crate::sdf::Sdf;
+use glam::{vec2, Vec2};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EquilateralTriangle {
+    pub radius: f32,
+}
+
+impl EquilateralTriangle {
+    pub const fn new(radius: f32) -> Self {
+        Self { radius }
+    }
+}
+
+impl Default for EquilateralTriangle {
+    fn default() -> Self {
+        Self { radius: 0.3 }
+    }
+}
+
+impl Sdf for EquilateralTriangle {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        const COS_FRAC_PI_6: f32 = 0.866_025_4;
+        const K: f32 = super::SQRT_3;
+        let r = self.radius * COS_FRAC_PI_6;
+        let mut p = vec2(p.x.abs() - r, p.y + r / K);
+        if p.x + K * p.y > 0.0 {
+            p = vec2(p.x - K * p.y, -K * p.x - p.y) * 0.5;
+        }
+        p.x -= p.x.clamp(-2.0 * r, 0.0);
+        -p.length() * p.y.signum()
+    }
+}
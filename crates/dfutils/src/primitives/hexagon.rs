@@ -0,0 +1,34 @@
+use crate::sdf::Sdf;
+use glam::{vec2, Vec2};
+
+/// A regular hexagon with flat top and bottom, `radius` is its inradius.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hexagon {
+    pub radius: f32,
+}
+
+impl Hexagon {
+    pub const fn new(radius: f32) -> Self {
+        Self { radius }
+    }
+}
+
+impl Default for Hexagon {
+    fn default() -> Self {
+        Self { radius: 0.25 }
+    }
+}
+
+impl Sdf for Hexagon {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        const COS_FRAC_PI_6: f32 = 0.866_025_4;
+        const TAN_FRAC_PI_6: f32 = 0.577_350_26;
+        const K: Vec2 = vec2(-COS_FRAC_PI_6, 0.5);
+        let r = self.radius;
+        let mut p = p.abs();
+        p -= 2.0 * K * p.dot(K).min(0.0);
+        p -= vec2(p.x.clamp(-TAN_FRAC_PI_6 * r, TAN_FRAC_PI_6 * r), r);
+        p.length() * p.y.signum()
+    }
+}
@@ -0,0 +1,38 @@
+use super::reject_from_segment;
+use crate::sdf::Sdf;
+use glam::{vec2, Vec2};
+
+/// A triangle with its base centered below the apex.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct IsoscelesTriangle {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl IsoscelesTriangle {
+    pub const fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+}
+
+impl Default for IsoscelesTriangle {
+    fn default() -> Self {
+        Self {
+            width: 0.4,
+            height: 0.4,
+        }
+    }
+}
+
+impl Sdf for IsoscelesTriangle {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        let q = vec2(0.5 * self.width, self.height);
+        // Apex at the origin, the base below it, centered vertically
+        let p = vec2(p.x.abs(), 0.5 * self.height - p.y);
+        let a = reject_from_segment(p, q);
+        let b = p - vec2(p.x.min(q.x), q.y);
+        let s = q.y.signum();
+        a.length().min(b.length()) * (s * p.perp_dot(q)).max(s * (p.y - q.y)).signum()
+    }
+}
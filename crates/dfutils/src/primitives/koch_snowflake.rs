@@ -0,0 +1,43 @@
+use super::SQRT_3;
+use crate::sdf::Sdf;
+use core::f32::consts::PI;
+use glam::{vec2, Mat2, Vec2};
+
+/// A Koch snowflake after `iterations` subdivisions of the sides of a hexagram.
+// Based on https://www.shadertoy.com/view/NljfRG (sylvain69780 12/05/2022)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct KochSnowflake {
+    pub radius: f32,
+    pub iterations: u32,
+}
+
+impl KochSnowflake {
+    pub const fn new(radius: f32, iterations: u32) -> Self {
+        Self { radius, iterations }
+    }
+}
+
+impl Default for KochSnowflake {
+    fn default() -> Self {
+        Self {
+            radius: 0.3,
+            iterations: 2,
+        }
+    }
+}
+
+impl Sdf for KochSnowflake {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        let r = self.radius;
+        let mut p = Mat2::from_angle(PI / 3.0) * p.abs() - r * vec2(0.0, 0.5);
+        let rot = Mat2::from_angle(-PI / 6.0);
+        let mut w = rot.x_axis.x * r;
+        for _ in 0..self.iterations * 2 + 2 {
+            p = rot * vec2(p.x.abs() - w, -p.y);
+            w /= SQRT_3;
+            p.x += w;
+        }
+        p.y.signum() * (vec2(p.x - p.x.clamp(-w, w), p.y)).length()
+    }
+}
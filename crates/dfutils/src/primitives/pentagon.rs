@@ -0,0 +1,37 @@
+use crate::sdf::Sdf;
+use glam::{vec2, Vec2};
+
+/// A regular pentagon pointing up, `radius` is its inradius.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pentagon {
+    pub radius: f32,
+}
+
+impl Pentagon {
+    pub const fn new(radius: f32) -> Self {
+        Self { radius }
+    }
+}
+
+impl Default for Pentagon {
+    fn default() -> Self {
+        Self { radius: 0.25 }
+    }
+}
+
+impl Sdf for Pentagon {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        const COS_FRAC_PI_5: f32 = 0.809_017;
+        const SIN_FRAC_PI_5: f32 = 0.587_785_24;
+        const TAN_FRAC_PI_5: f32 = 0.726_542_5;
+        const K1: Vec2 = vec2(-COS_FRAC_PI_5, SIN_FRAC_PI_5);
+        const K2: Vec2 = vec2(COS_FRAC_PI_5, SIN_FRAC_PI_5);
+        let r = self.radius;
+        let mut p = vec2(p.x.abs(), -p.y);
+        p -= 2.0 * K1 * p.dot(K1).min(0.0);
+        p -= 2.0 * K2 * p.dot(K2).min(0.0);
+        p -= vec2(p.x.clamp(-TAN_FRAC_PI_5 * r, TAN_FRAC_PI_5 * r), r);
+        p.length() * p.y.signum()
+    }
+}
@@ -0,0 +1,53 @@
+use super::reject_from_segment;
+use crate::sdf::Sdf;
+use glam::{vec2, BVec3, Vec2};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// A closed polygon through `N` vertices, which may intersect itself.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Polygon<const N: usize> {
+    pub vertices: [Vec2; N],
+}
+
+impl<const N: usize> Polygon<N> {
+    pub const fn new(vertices: [Vec2; N]) -> Self {
+        Self { vertices }
+    }
+}
+
+impl Default for Polygon<5> {
+    fn default() -> Self {
+        Self {
+            vertices: [
+                vec2(0.0, 0.3),
+                vec2(0.0, -0.3),
+                vec2(-0.4, -0.2),
+                vec2(0.3, 0.0),
+                vec2(-0.4, 0.2),
+            ],
+        }
+    }
+}
+
+impl<const N: usize> Sdf for Polygon<N> {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        let vs = &self.vertices;
+        let mut d = (p - vs[0]).length_squared();
+        let mut s = 1.0;
+        let mut j = N - 1;
+        for i in 0..N {
+            let e = vs[j] - vs[i];
+            let w = p - vs[i];
+            d = d.min(reject_from_segment(w, e).length_squared());
+            // Winding number crossings
+            let c = BVec3::new(p.y >= vs[i].y, p.y < vs[j].y, e.perp_dot(w) > 0.0);
+            if c.all() || !c.any() {
+                s = -s;
+            }
+            j = i;
+        }
+        s * d.sqrt()
+    }
+}
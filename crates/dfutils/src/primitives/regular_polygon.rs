@@ -0,0 +1,38 @@
+use crate::sdf::Sdf;
+use core::f32::consts::PI;
+use glam::{vec2, Vec2};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// A polygon with `n` vertices on a circle of `radius`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RegularPolygon {
+    pub radius: f32,
+    pub n: u32,
+}
+
+impl RegularPolygon {
+    pub const fn new(radius: f32, n: u32) -> Self {
+        Self { radius, n }
+    }
+}
+
+impl Default for RegularPolygon {
+    fn default() -> Self {
+        Self { radius: 0.3, n: 5 }
+    }
+}
+
+impl Sdf for RegularPolygon {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        let an = PI / self.n as f32;
+        let acs = Vec2::from_angle(an);
+
+        let (s, c) = (((p.x.atan2(p.y) + PI * 2.0) % (2.0 * an)) - an).sin_cos();
+        let mut p = p.length() * vec2(c, s.abs());
+        p -= self.radius * acs;
+        p.y += (-p.y).clamp(0.0, self.radius * acs.y);
+        p.length() * p.x.signum()
+    }
+}
@@ -0,0 +1,51 @@
+use crate::sdf::Sdf;
+use core::f32::consts::PI;
+use glam::{vec2, Vec2};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// A star with `n` points on a circle of `radius`, the inner vertices move
+/// towards the center as `sharpness` goes from 0 to 1.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RegularStar {
+    pub radius: f32,
+    pub n: u32,
+    pub sharpness: f32,
+}
+
+impl RegularStar {
+    pub const fn new(radius: f32, n: u32, sharpness: f32) -> Self {
+        Self {
+            radius,
+            n,
+            sharpness,
+        }
+    }
+}
+
+impl Default for RegularStar {
+    fn default() -> Self {
+        Self {
+            radius: 0.3,
+            n: 5,
+            sharpness: 0.5,
+        }
+    }
+}
+
+impl Sdf for RegularStar {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        let n = self.n as f32;
+        let an = PI / n;
+        let en = PI / (self.sharpness * (n - 2.0) + 2.0);
+        let acs = Vec2::from_angle(an);
+        let ecs = Vec2::from_angle(en);
+
+        let (s, c) = (((p.x.atan2(p.y) + PI * 2.0) % (2.0 * an)) - an).sin_cos();
+        let mut p = p.length() * vec2(c, s.abs());
+        p -= self.radius * acs;
+        p += ecs * (-p.dot(ecs)).clamp(0.0, self.radius * acs.y / ecs.y);
+        p.length() * p.x.signum()
+    }
+}
@@ -0,0 +1,55 @@
+use super::{EquilateralTriangle, SQRT_3};
+use crate::sdf::Sdf;
+use glam::{vec2, vec3, Vec2};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// An equilateral triangle with `iterations` levels of holes.
+// Based on https://www.shadertoy.com/view/fdBcR3 (Jakob Thomsen 2/2/2022)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SierpinskiTriangle {
+    pub radius: f32,
+    pub iterations: u32,
+}
+
+impl SierpinskiTriangle {
+    pub const fn new(radius: f32, iterations: u32) -> Self {
+        Self { radius, iterations }
+    }
+}
+
+impl Default for SierpinskiTriangle {
+    fn default() -> Self {
+        Self {
+            radius: 0.4,
+            iterations: 4,
+        }
+    }
+}
+
+impl Sdf for SierpinskiTriangle {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        let r = self.radius;
+        // Centered on its bounding box rather than its centroid
+        let p = p + vec2(0.0, 0.25 * r);
+        let q = {
+            let p = p / r / SQRT_3;
+            let z = p.y / SQRT_3;
+            vec3(-z - p.x, -z + p.x, 2.0 * z) + 1.0 / 3.0
+        };
+        if q.x < 0.0 || q.y < 0.0 || q.z < 0.0 {
+            EquilateralTriangle::new(r).signed_distance(p)
+        } else {
+            let n = (self.iterations as f32).exp2();
+            let i = ((1.0 - q) * n).as_ivec3();
+            let f = i.x & i.y & i.z;
+            if f == 0 {
+                -(q % (1.0 / n)).min_element() * r * 3.0 / 2.0
+            } else {
+                let s = (f as f32).log2().floor().exp2() * 2.0 / n;
+                (((1.0 - q) * 2.0) % s).min_element() * r * 3.0 / 4.0
+            }
+        }
+    }
+}
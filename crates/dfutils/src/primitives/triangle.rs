@@ -0,0 +1,48 @@
+use super::reject_from_segment;
+use crate::sdf::Sdf;
+use glam::{vec2, Vec2};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Triangle {
+    pub a: Vec2,
+    pub b: Vec2,
+    pub c: Vec2,
+}
+
+impl Triangle {
+    pub const fn new(a: Vec2, b: Vec2, c: Vec2) -> Self {
+        Self { a, b, c }
+    }
+}
+
+impl Default for Triangle {
+    fn default() -> Self {
+        Self {
+            a: vec2(-0.25, -0.1),
+            b: vec2(0.15, 0.3),
+            c: vec2(0.1, -0.2),
+        }
+    }
+}
+
+impl Sdf for Triangle {
+    type T = f32;
+    fn signed_distance(&self, p: Vec2) -> f32 {
+        let edges = [
+            (self.b - self.a, p - self.a),
+            (self.c - self.b, p - self.b),
+            (self.a - self.c, p - self.c),
+        ];
+        // Inside if `p` is on the same side of every edge as the triangle
+        let orientation = edges[0].0.perp_dot(edges[2].0).signum();
+        let side = edges
+            .iter()
+            .map(|(e, w)| orientation * w.perp_dot(*e))
+            .fold(f32::INFINITY, f32::min);
+        let d = edges
+            .iter()
+            .map(|(e, w)| reject_from_segment(*w, *e).length())
+            .fold(f32::INFINITY, f32::min);
+        -side.signum() * d
+    }
+}
@@ -0,0 +1,24 @@
+//! The 3D counterparts of [`primitives`](crate::primitives), adapted from
+//! <https://iquilezles.org/articles/distfunctions/>
+
+mod capsule;
+mod cuboid;
+mod cylinder;
+mod plane;
+mod sphere;
+mod torus;
+
+pub use capsule::*;
+pub use cuboid::*;
+pub use cylinder::*;
+pub use plane::*;
+pub use sphere::*;
+pub use torus::*;
+
+use glam::Vec3;
+
+/// The closest point to `p` on the segment from `a` to `b`.
+fn closest_on_segment(p: Vec3, a: Vec3, b: Vec3) -> Vec3 {
+    let ab = b - a;
+    a + ab * ((p - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+}
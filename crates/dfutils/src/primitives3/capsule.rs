@@ -0,0 +1,38 @@
+use super::closest_on_segment;
+use crate::sdf::Sdf3;
+use glam::{vec3, Vec3};
+
+/// The points within `radius` of the segment from `a` to `b`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Capsule {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub radius: f32,
+}
+
+impl Capsule {
+    pub const fn new(a: Vec3, b: Vec3, radius: f32) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl Default for Capsule {
+    fn default() -> Self {
+        Self {
+            a: vec3(-0.15, 0.0, 0.0),
+            b: vec3(0.15, 0.0, 0.0),
+            radius: 0.1,
+        }
+    }
+}
+
+impl Sdf3 for Capsule {
+    type T = f32;
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        p.distance(closest_on_segment(p, self.a, self.b)) - self.radius
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        (p - closest_on_segment(p, self.a, self.b)).normalize_or_zero()
+    }
+}
@@ -0,0 +1,44 @@
+use crate::sdf::Sdf3;
+use glam::{vec3, Vec3};
+
+/// A box with the given side lengths.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cuboid {
+    pub size: Vec3,
+}
+
+impl Cuboid {
+    pub const fn new(size: Vec3) -> Self {
+        Self { size }
+    }
+}
+
+impl Default for Cuboid {
+    fn default() -> Self {
+        Self {
+            size: vec3(0.5, 0.3, 0.4),
+        }
+    }
+}
+
+impl Sdf3 for Cuboid {
+    type T = f32;
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        let q = p.abs() - self.size * 0.5;
+        q.max(Vec3::ZERO).length() + q.max_element().min(0.0)
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        let q = p.abs() - self.size * 0.5;
+        let s = p.signum();
+        if q.max_element() > 0.0 {
+            s * q.max(Vec3::ZERO).normalize()
+        } else if q.x > q.y && q.x > q.z {
+            s * Vec3::X
+        } else if q.y > q.z {
+            s * Vec3::Y
+        } else {
+            s * Vec3::Z
+        }
+    }
+}
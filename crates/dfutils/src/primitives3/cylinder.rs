@@ -0,0 +1,39 @@
+use crate::sdf::Sdf3;
+use glam::{vec2, vec3, Vec2, Vec3};
+
+/// A capped cylinder of `radius` around the segment from `a` to `b`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cylinder {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub radius: f32,
+}
+
+impl Cylinder {
+    pub const fn new(a: Vec3, b: Vec3, radius: f32) -> Self {
+        Self { a, b, radius }
+    }
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self {
+            a: vec3(0.0, -0.2, 0.0),
+            b: vec3(0.0, 0.2, 0.0),
+            radius: 0.15,
+        }
+    }
+}
+
+impl Sdf3 for Cylinder {
+    type T = f32;
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        let ab = self.b - self.a;
+        let t = (p - self.a).dot(ab) / ab.length_squared();
+        let q = vec2(
+            p.distance(self.a + t * ab) - self.radius,
+            ((t - 0.5).abs() - 0.5) * ab.length(),
+        );
+        q.max(Vec2::ZERO).length() + q.max_element().min(0.0)
+    }
+}
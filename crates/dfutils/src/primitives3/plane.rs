@@ -0,0 +1,31 @@
+use crate::sdf::Sdf3;
+use glam::Vec3;
+
+/// The half space below the plane through the origin, `normal` must be normalized.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Plane {
+    pub normal: Vec3,
+}
+
+impl Plane {
+    pub const fn new(normal: Vec3) -> Self {
+        Self { normal }
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self { normal: Vec3::Y }
+    }
+}
+
+impl Sdf3 for Plane {
+    type T = f32;
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        self.normal.dot(p)
+    }
+
+    fn gradient(&self, _p: Vec3) -> Vec3 {
+        self.normal
+    }
+}
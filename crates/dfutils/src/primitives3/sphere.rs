@@ -0,0 +1,30 @@
+use crate::sdf::Sdf3;
+use glam::Vec3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sphere {
+    pub radius: f32,
+}
+
+impl Sphere {
+    pub const fn new(radius: f32) -> Self {
+        Self { radius }
+    }
+}
+
+impl Default for Sphere {
+    fn default() -> Self {
+        Self { radius: 0.3 }
+    }
+}
+
+impl Sdf3 for Sphere {
+    type T = f32;
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        p.length() - self.radius
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        p.normalize_or_zero()
+    }
+}
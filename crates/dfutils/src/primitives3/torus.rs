@@ -0,0 +1,40 @@
+use crate::sdf::Sdf3;
+use glam::{vec2, vec3, Vec3, Vec3Swizzles};
+
+/// A torus around the y axis.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Torus {
+    pub major_radius: f32,
+    pub minor_radius: f32,
+}
+
+impl Torus {
+    pub const fn new(major_radius: f32, minor_radius: f32) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Self {
+            major_radius: 0.25,
+            minor_radius: 0.1,
+        }
+    }
+}
+
+impl Sdf3 for Torus {
+    type T = f32;
+    fn signed_distance(&self, p: Vec3) -> f32 {
+        vec2(p.xz().length() - self.major_radius, p.y).length() - self.minor_radius
+    }
+
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        let radial = p.xz().normalize_or_zero();
+        let q = vec2(p.xz().length() - self.major_radius, p.y);
+        vec3(radial.x * q.x, q.y, radial.y * q.x).normalize_or_zero()
+    }
+}
@@ -0,0 +1,14 @@
+use crate::{primitives3::*, sdf::Sdf3};
+use glam::Vec3;
+
+#[cfg_attr(feature = "strum", derive(strum::EnumIter, strum::IntoStaticStr))]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[enum_delegate::implement(Sdf3)]
+pub enum Shape3 {
+    Sphere(Sphere),
+    Cuboid(Cuboid),
+    Torus(Torus),
+    Capsule(Capsule),
+    Cylinder(Cylinder),
+    Plane(Plane),
+}
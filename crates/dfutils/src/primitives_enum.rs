@@ -12,4 +12,15 @@ pub enum Shape {
     Plane(Plane),
     Ray(Ray),
     LineSegment(LineSegment),
+    Capsule(Capsule),
+    EquilateralTriangle(EquilateralTriangle),
+    IsoscelesTriangle(IsoscelesTriangle),
+    Triangle(Triangle),
+    Polygon(Polygon<5>),
+    Hexagon(Hexagon),
+    Pentagon(Pentagon),
+    RegularPolygon(RegularPolygon),
+    RegularStar(RegularStar),
+    SierpinskiTriangle(SierpinskiTriangle),
+    KochSnowflake(KochSnowflake),
 }
@@ -1,4 +1,72 @@
-use glam::{vec2, Vec2};
+use core::f32::consts::FRAC_1_SQRT_2;
+use glam::{vec2, vec3, Vec2, Vec3};
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// How the boolean operators round off the edges where two shapes meet,
+/// within a blend radius. Adapted from
+/// <https://iquilezles.org/articles/smin/> and <https://mercury.sexy/hg_sdf/>
+#[cfg_attr(
+    feature = "strum",
+    derive(strum::EnumIter, strum::IntoStaticStr, strum::Display)
+)]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Blend {
+    /// Quadratic polynomial smooth minimum.
+    #[default]
+    Smooth,
+    /// Exponential smooth minimum, which also shifts the surfaces away from
+    /// the edges by up to the radius.
+    Exponential,
+    /// A 45 degree bevel.
+    Chamfer,
+    /// A quarter circle fillet.
+    Round,
+    /// Four steps along the bevel.
+    Stairs,
+}
+
+impl Blend {
+    const STEPS: f32 = 4.0;
+
+    /// The blended minimum of `a` and `b`, the hard minimum when `r` isn't positive.
+    pub fn union(self, a: f32, b: f32, r: f32) -> f32 {
+        if r <= 0.0 {
+            return a.min(b);
+        }
+        match self {
+            Blend::Smooth => {
+                let h = (r - (a - b).abs()).max(0.0) / r;
+                a.min(b) - 0.25 * h * h * r
+            }
+            Blend::Exponential => -r * ((-a / r).exp2() + (-b / r).exp2()).log2(),
+            Blend::Chamfer => a.min(b).min((a + b - r) * FRAC_1_SQRT_2),
+            Blend::Round => {
+                let u = vec2(r - a, r - b).max(Vec2::ZERO);
+                r.max(a.min(b)) - u.length()
+            }
+            Blend::Stairs => {
+                let s = r / Self::STEPS;
+                let u = b - r;
+                let x = u - a + s;
+                // GLSL `mod`, which is positive for negative `x`
+                let m = x - 2.0 * s * (x / (2.0 * s)).floor();
+                let stairs = 0.5 * (u + a + (m - s).abs());
+                a.min(b).min(stairs)
+            }
+        }
+    }
+
+    /// The blended maximum of `a` and `b`.
+    pub fn intersect(self, a: f32, b: f32, r: f32) -> f32 {
+        -self.union(-a, -b, r)
+    }
+
+    /// `b` with `a` blended out of it, like [`SignedDistance::subtract`].
+    pub fn subtract(self, a: f32, b: f32, r: f32) -> f32 {
+        self.intersect(-a, b, r)
+    }
+}
 
 pub trait SignedDistance
 where
@@ -43,6 +111,21 @@ where
     fn xor(&self, other: &Self) -> Self {
         self.intersect(other).subtract(&self.union(other))
     }
+
+    fn blend_union(&self, other: &Self, blend: Blend, r: f32) -> Self {
+        let d = blend.union(self.value(), other.value(), r);
+        self.union(other).with_new_distance(d)
+    }
+
+    fn blend_intersect(&self, other: &Self, blend: Blend, r: f32) -> Self {
+        let d = blend.intersect(self.value(), other.value(), r);
+        self.intersect(other).with_new_distance(d)
+    }
+
+    fn blend_subtract(&self, other: &Self, blend: Blend, r: f32) -> Self {
+        let d = blend.subtract(self.value(), other.value(), r);
+        self.subtract(other).with_new_distance(d)
+    }
 }
 
 impl SignedDistance for f32 {
@@ -78,3 +161,32 @@ pub trait Sdf {
         ) / (2.0 * h)
     }
 }
+
+/// The 3D counterpart of [`Sdf`].
+#[enum_delegate::register]
+pub trait Sdf3 {
+    type T: SignedDistance;
+    fn signed_distance(&self, p: Vec3) -> Self::T;
+
+    fn distance(&self, p: Vec3) -> Self::T {
+        let result = self.signed_distance(p);
+        result.with_new_distance(result.value().abs())
+    }
+
+    fn derivative(&self, p: Vec3, h: f32) -> Vec3 {
+        vec3(
+            self.signed_distance(p + h * Vec3::X).value()
+                - self.signed_distance(p - h * Vec3::X).value(),
+            self.signed_distance(p + h * Vec3::Y).value()
+                - self.signed_distance(p - h * Vec3::Y).value(),
+            self.signed_distance(p + h * Vec3::Z).value()
+                - self.signed_distance(p - h * Vec3::Z).value(),
+        ) / (2.0 * h)
+    }
+
+    /// The gradient of the distance, which is the outward normal on the
+    /// surface. Shapes with a closed form override the finite differences.
+    fn gradient(&self, p: Vec3) -> Vec3 {
+        self.derivative(p, 1e-3)
+    }
+}
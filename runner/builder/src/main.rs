@@ -17,11 +17,28 @@ fn build_shader(path_to_crate: &str, codegen_names: bool) -> Result<(), Box<dyn
     Ok(())
 }
 
+/// Every shader crate, embedded into the web build since it can't compile shaders at runtime.
+const SHADER_CRATES: &[&str] = &[
+    "mandelbrot",
+    "ray-marching",
+    "ray-marching-2d",
+    "sierpinski-triangle",
+    "koch-snowflake",
+    "sdfs-2d",
+    "sdfs-3d",
+    "hydrogen-wavefunction",
+    "spherical-harmonics",
+    "spherical-harmonics-shape",
+    "fun-rep-demo",
+    "sdf-builder",
+    "procedural-generation",
+];
+
 fn main() -> Result<(), Box<dyn Error>> {
-    build_shader("../../shaders/mandelbrot", true)?;
-    build_shader("../../shaders/koch-snowflake", false)?;
-    build_shader("../../shaders/sierpinski-triangle", false)?;
-    build_shader("../../shaders/ray-marching", false)?;
-    build_shader("../../shaders/ray-marching-2d", false)?;
+    for (i, crate_name) in SHADER_CRATES.iter().enumerate() {
+        // All shaders share the `main_fs` and `main_vs` entry points, so the names are only
+        // generated once.
+        build_shader(&format!("../../shaders/{crate_name}"), i == 0)?;
+    }
     Ok(())
 }
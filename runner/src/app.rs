@@ -1,4 +1,7 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::recording::Recording;
 use crate::{
+    controller::KeyInput,
     shader::{maybe_watch, CompiledShaderModules},
     state,
     window::{UserEvent, Window},
@@ -7,12 +10,24 @@ use crate::{
 use egui_winit::winit::{
     event::{DeviceEvent, ElementState, Event, KeyEvent, WindowEvent},
     event_loop::ControlFlow,
-    keyboard::{Key, NamedKey},
+    keyboard::{Key, KeyCode, NamedKey, PhysicalKey},
     window::CursorGrabMode,
 };
 
-async fn run(options: Options, window: Window, compiled_shader_modules: CompiledShaderModules) {
+async fn run(
+    options: Options,
+    window: Window,
+    compiled_shader_modules: CompiledShaderModules,
+    #[cfg(not(target_arch = "wasm32"))] recording: Option<Recording>,
+) {
     let event_loop = window.event_loop;
+    #[cfg(not(target_arch = "wasm32"))]
+    let from_image = options.from_image.clone();
+    #[cfg(not(target_arch = "wasm32"))]
+    let record = options.record.clone();
+    #[cfg(not(target_arch = "wasm32"))]
+    let audio = options.audio.clone();
+    let set = options.set.clone();
     let mut app = state::State::new(
         &window.window,
         event_loop.create_proxy(),
@@ -20,6 +35,29 @@ async fn run(options: Options, window: Window, compiled_shader_modules: Compiled
         options,
     )
     .await;
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = from_image {
+        app.load_image(&path);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = audio {
+        app.load_audio(&path);
+    }
+    if let Err(e) = app.set_params(&set) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = record
+        && let Err(e) = app.record(&path)
+    {
+        eprintln!("Failed to record input to {}: {e}", path.display());
+        std::process::exit(1);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(recording) = recording {
+        app.replay(recording);
+    }
 
     let exit = event_loop.run(|event, event_loop_window_target| {
         let window = &window.window;
@@ -48,8 +86,49 @@ async fn run(options: Options, window: Window, compiled_shader_modules: Compiled
                             },
                         ..
                     } => event_loop_window_target.exit(),
-                    WindowEvent::KeyboardInput { event, .. } => app.keyboard_input(event),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                state: ElementState::Pressed,
+                                physical_key: PhysicalKey::Code(KeyCode::F12),
+                                ..
+                            },
+                        ..
+                    } => app.screenshot(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                state: ElementState::Pressed,
+                                physical_key: PhysicalKey::Code(KeyCode::F10),
+                                ..
+                            },
+                        ..
+                    } => app.panorama(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                state: ElementState::Pressed,
+                                physical_key: PhysicalKey::Code(KeyCode::F11),
+                                ..
+                            },
+                        ..
+                    } => app.hdr_screenshot(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    WindowEvent::DroppedFile(path) => {
+                        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav")) {
+                            app.load_audio(&path)
+                        } else {
+                            app.load_image(&path)
+                        }
+                    }
+                    WindowEvent::KeyboardInput { event, .. } => {
+                        app.keyboard_input(KeyInput::from(&event))
+                    }
                     WindowEvent::Resized(size) => app.resize(size),
+                    WindowEvent::ModifiersChanged(modifiers) => app.set_modifiers(modifiers.state()),
                     WindowEvent::MouseInput { state, button, .. } => app.mouse_input(state, button),
                     WindowEvent::MouseWheel { delta, .. } => app.mouse_scroll(delta),
                     WindowEvent::CursorMoved { position, .. } => {
@@ -97,7 +176,7 @@ async fn run(options: Options, window: Window, compiled_shader_modules: Compiled
     }
 }
 
-pub fn start(options: Options) {
+pub fn start(mut options: Options) {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
             std::panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -107,6 +186,63 @@ pub fn start(options: Options) {
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(crate::Command::NewDemo { name, kind }) = &options.command {
+        if let Err(e) = crate::scaffold::new_demo(name, *kind) {
+            eprintln!("Failed to create demo: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if options.bench.is_some() {
+        let window = Window::new();
+        window.window.set_visible(false);
+        futures::executor::block_on(crate::bench::run(&options, &window.window));
+        return;
+    }
+
+    // The shader stored in the image is built instead of the one given by `--shader`
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = &options.from_image {
+        match crate::screenshot::load(path) {
+            Ok(metadata) => options.shader = metadata.shader,
+            Err(e) => {
+                eprintln!("Failed to load image: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // The recording starts with its own shader
+    #[cfg(not(target_arch = "wasm32"))]
+    let recording = options.replay.as_ref().map(|path| match crate::recording::load(path) {
+        Ok(recording) => {
+            options.shader = recording.shader;
+            recording
+        }
+        Err(e) => {
+            eprintln!("Failed to load recording: {e}");
+            std::process::exit(1);
+        }
+    });
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if options.headless
+        && let Some(recording) = recording
+    {
+        let mut replay = crate::recording::Replay::new(recording);
+        replay.run();
+        print!("{}", crate::params::serialize(replay.controller()));
+        return;
+    }
+
+    if let Err(e) = crate::params::check(options.shader, &options.set) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+
     let window = Window::new();
 
     // Build the shader before we pop open a window, since it might take a while.
@@ -147,6 +283,7 @@ pub fn start(options: Options) {
                 options,
                 window,
                 initial_shader,
+                recording,
             ));
         }
     }
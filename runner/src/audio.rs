@@ -0,0 +1,266 @@
+use shared::spectrum::{Spectrum, NUM_BANDS};
+use std::{f32::consts::TAU, fs, path::Path};
+
+/// Samples per FFT, about 46ms at 44.1kHz.
+const FFT_SIZE: usize = 2048;
+/// Frequency range of the bands in Hz.
+const MIN_FREQUENCY: f32 = 30.0;
+const MAX_FREQUENCY: f32 = 16000.0;
+/// Levels below this many decibels under full scale have no energy.
+const DYNAMIC_RANGE: f32 = 60.0;
+
+/// A WAV file mixed down to mono, analysed at the time of the clock so that
+/// the spectrum only depends on the time and not on the frame rate.
+pub struct Audio {
+    pub name: String,
+    sample_rate: u32,
+    samples: Vec<f32>,
+    window: Vec<f32>,
+}
+
+impl Audio {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let (sample_rate, samples) =
+            parse_wav(&bytes).map_err(|e| format!("{}: {e}", path.display()))?;
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        // Hann window
+        let window = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (TAU * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        Ok(Self {
+            name,
+            sample_rate,
+            samples,
+            window,
+        })
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+
+    /// The band energies of the samples centered on `time`, silent outside the file.
+    pub fn spectrum(&self, time: f32) -> Spectrum {
+        let center = (time * self.sample_rate as f32) as isize;
+        let start = center - FFT_SIZE as isize / 2;
+        let mut buffer = (0..FFT_SIZE)
+            .map(|i| {
+                let sample = usize::try_from(start + i as isize)
+                    .ok()
+                    .and_then(|j| self.samples.get(j))
+                    .copied()
+                    .unwrap_or_default();
+                (sample * self.window[i], 0.0)
+            })
+            .collect::<Vec<_>>();
+        fft(&mut buffer);
+
+        let bin_width = self.sample_rate as f32 / FFT_SIZE as f32;
+        let max_frequency = MAX_FREQUENCY.min(0.5 * self.sample_rate as f32);
+        let ratio = max_frequency / MIN_FREQUENCY;
+        let mut bands = [0.0; NUM_BANDS];
+        for (i, band) in bands.iter_mut().enumerate() {
+            let low = MIN_FREQUENCY * ratio.powf(i as f32 / NUM_BANDS as f32);
+            let high = MIN_FREQUENCY * ratio.powf((i + 1) as f32 / NUM_BANDS as f32);
+            let low = (low / bin_width).round() as usize;
+            let high = ((high / bin_width).round() as usize).max(low + 1);
+            let power = buffer[low..high.min(FFT_SIZE / 2)]
+                .iter()
+                .map(|(re, im)| re * re + im * im)
+                .sum::<f32>();
+            // A full scale sine has a magnitude of a quarter of the size with a Hann window
+            let amplitude = power.sqrt() / (FFT_SIZE as f32 / 4.0);
+            let decibels = 20.0 * amplitude.max(f32::MIN_POSITIVE).log10();
+            *band = (1.0 + decibels / DYNAMIC_RANGE).clamp(0.0, 1.0);
+        }
+        Spectrum { bands }
+    }
+}
+
+/// In-place radix-2 Cooley-Tukey FFT of complex numbers as `(re, im)`,
+/// the length must be a power of two.
+fn fft(x: &mut [(f32, f32)]) {
+    let n = x.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            x.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -TAU / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (re, im) = x[start + k + len / 2];
+                let t = (re * cos - im * sin, re * sin + im * cos);
+                let u = x[start + k];
+                x[start + k] = (u.0 + t.0, u.1 + t.1);
+                x[start + k + len / 2] = (u.0 - t.0, u.1 - t.1);
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Reads the sample rate and the samples of an uncompressed WAV file,
+/// averaging the channels.
+fn parse_wav(bytes: &[u8]) -> Result<(u32, Vec<f32>), String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a WAV file".to_string());
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let u32_at =
+        |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+    let mut format = None;
+    let mut data = None;
+    let mut i = 12;
+    while i + 8 <= bytes.len() {
+        let id = &bytes[i..i + 4];
+        let size = u32_at(i + 4) as usize;
+        let body = &bytes[i + 8..(i + 8 + size).min(bytes.len())];
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let mut tag = u16_at(i + 8);
+                // WAVE_FORMAT_EXTENSIBLE stores the format in the sub format GUID
+                if tag == 0xfffe && body.len() >= 26 {
+                    tag = u16_at(i + 8 + 24);
+                }
+                let channels = u16_at(i + 10) as usize;
+                let sample_rate = u32_at(i + 12);
+                let bits = u16_at(i + 22);
+                format = Some((tag, channels, sample_rate, bits));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        // Chunks are padded to an even size
+        i += 8 + size + size % 2;
+    }
+    let (tag, channels, sample_rate, bits) = format.ok_or("missing `fmt ` chunk")?;
+    let data = data.ok_or("missing `data` chunk")?;
+    if channels == 0 || sample_rate == 0 {
+        return Err("invalid format".to_string());
+    }
+
+    let sample: fn(&[u8]) -> f32 = match (tag, bits) {
+        (1, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+        (1, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (1, 24) => |b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2147483648.0,
+        (1, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2147483648.0,
+        (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        (3, 64) => |b| f64::from_le_bytes(b.try_into().unwrap()) as f32,
+        _ => {
+            return Err(format!(
+                "unsupported format {tag} with {bits} bits, expected PCM or float"
+            ))
+        }
+    };
+    let width = bits as usize / 8;
+    let samples = data
+        .chunks_exact(width * channels)
+        .map(|frame| frame.chunks_exact(width).map(sample).sum::<f32>() / channels as f32)
+        .collect();
+    Ok((sample_rate, samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fft_of_sine() {
+        const N: usize = 64;
+        const BIN: usize = 5;
+        let mut x: Vec<_> = (0..N)
+            .map(|i| ((TAU * (BIN * i) as f32 / N as f32).sin(), 0.0))
+            .collect();
+        fft(&mut x);
+        for (i, (re, im)) in x.into_iter().enumerate() {
+            let magnitude = (re * re + im * im).sqrt();
+            // A real sine splits its amplitude between the bin and its mirror
+            let expected = if i == BIN || i == N - BIN {
+                N as f32 / 2.0
+            } else {
+                0.0
+            };
+            assert!((magnitude - expected).abs() < 1e-3, "bin {i}: {magnitude}");
+        }
+    }
+
+    /// A WAV file with a `fmt ` chunk of the given format followed by an odd
+    /// sized chunk to skip, and the `data` chunk.
+    fn wav(tag: u16, channels: u16, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut fmt = vec![];
+        fmt.extend(tag.to_le_bytes());
+        fmt.extend(channels.to_le_bytes());
+        fmt.extend(44100u32.to_le_bytes());
+        let block_align = channels * bits / 8;
+        fmt.extend((44100 * block_align as u32).to_le_bytes());
+        fmt.extend(block_align.to_le_bytes());
+        fmt.extend(bits.to_le_bytes());
+
+        let mut chunks = vec![];
+        for (id, body) in [
+            (b"fmt ", &fmt[..]),
+            (b"LIST", &[1, 2, 3][..]),
+            (b"data", data),
+        ] {
+            chunks.extend(id);
+            chunks.extend((body.len() as u32).to_le_bytes());
+            chunks.extend(body);
+            if body.len() % 2 == 1 {
+                chunks.push(0);
+            }
+        }
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend((chunks.len() as u32 + 4).to_le_bytes());
+        bytes.extend(b"WAVE");
+        bytes.extend(chunks);
+        bytes
+    }
+
+    #[test]
+    fn wav_formats() {
+        // Stereo frames averaged to mono
+        let data: Vec<u8> = [16384i16, -16384, i16::MIN, i16::MIN]
+            .into_iter()
+            .flat_map(i16::to_le_bytes)
+            .collect();
+        assert_eq!(
+            parse_wav(&wav(1, 2, 16, &data)),
+            Ok((44100, vec![0.0, -1.0]))
+        );
+
+        let data = [0x00, 0x00, 0x40, 0x00, 0x00, 0xc0];
+        assert_eq!(
+            parse_wav(&wav(1, 1, 24, &data)),
+            Ok((44100, vec![0.5, -0.5]))
+        );
+
+        let data: Vec<u8> = [0.25f32, -0.75]
+            .into_iter()
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        assert_eq!(
+            parse_wav(&wav(3, 1, 32, &data)),
+            Ok((44100, vec![0.25, -0.75]))
+        );
+    }
+
+    #[test]
+    fn unsupported_wav() {
+        // ADPCM
+        let error = parse_wav(&wav(2, 1, 4, &[0; 4])).unwrap_err();
+        assert!(error.contains("unsupported format 2"), "{error}");
+        assert!(parse_wav(&wav(1, 1, 12, &[0; 4])).is_err());
+        assert!(parse_wav(&wav(1, 0, 16, &[0; 4])).is_err());
+        assert!(parse_wav(b"RIFF\0\0\0\0AVI ").is_err());
+    }
+}
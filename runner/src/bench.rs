@@ -0,0 +1,202 @@
+use crate::{
+    context::GraphicsContext,
+    controller::{new_controller, FrameContext, InputState},
+    render_pass::RenderPass,
+    shader::maybe_watch,
+    stereo::View,
+    texture::Texture,
+    Options, RustGPUShader,
+};
+use bytemuck::Zeroable;
+use egui_winit::winit::window::Window;
+use shared::{push_constants::Eye, spectrum::Spectrum};
+use std::{fmt::Write, time::Instant};
+use strum::IntoEnumIterator;
+
+/// Frame time statistics of a single shader, in milliseconds.
+struct Stats {
+    shader: RustGPUShader,
+    min: f64,
+    median: f64,
+    p95: f64,
+}
+
+impl Stats {
+    fn new(shader: RustGPUShader, mut frame_times: Vec<f64>) -> Self {
+        frame_times.sort_by(f64::total_cmp);
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = (p * frame_times.len() as f64).ceil() as usize;
+            frame_times[rank.clamp(1, frame_times.len()) - 1]
+        };
+        Self {
+            shader,
+            min: frame_times[0],
+            median: percentile(0.5),
+            p95: percentile(0.95),
+        }
+    }
+}
+
+/// Renders every selected shader offscreen with its default parameters at a
+/// fixed time, and prints the frame times as JSON.
+pub async fn run(options: &Options, window: &Window) {
+    if options.bench_frames == 0 {
+        eprintln!("--bench-frames must be at least 1");
+        std::process::exit(1);
+    }
+    let ctx = GraphicsContext::new(window, options).await;
+    let size = options.bench_size;
+    let shaders = match &options.bench {
+        Some(shaders) if !shaders.is_empty() => shaders.clone(),
+        _ => RustGPUShader::iter().collect(),
+    };
+
+    let input_state = InputState::default();
+    let frame_context = FrameContext {
+        dt: 0.0,
+        time: 0.0,
+        size,
+        scale_factor: 1.0,
+        input: &input_state,
+        spectrum: &Spectrum::zeroed(),
+    };
+    let target = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Bench Texture"),
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: ctx.config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let target = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let depth_texture = Texture::create_depth_texture(
+        &ctx.device,
+        &wgpu::SurfaceConfiguration {
+            width: size.width,
+            height: size.height,
+            ..ctx.config.clone()
+        },
+        "bench_depth_texture",
+    );
+
+    let mut rpass: Option<RenderPass> = None;
+    let mut stats = vec![];
+    for shader in shaders {
+        eprintln!("Benchmarking {shader}");
+        let mut controller = new_controller(shader, size);
+        controller.update(&frame_context);
+        controller.prepare_buffers();
+        let module = maybe_watch(
+            &Options {
+                shader,
+                ..options.clone()
+            },
+            None,
+        );
+        let buffers = controller.buffers();
+        if let Err(e) = module.validate(controller.push_constants().len(), &buffers) {
+            eprintln!("Skipping {shader}: {e}");
+            continue;
+        }
+        let depth_texture = buffers.use_depth_buffer.then_some(&depth_texture);
+        match &mut rpass {
+            Some(rpass) => rpass.new_module(&ctx, module, &buffers),
+            None => rpass = Some(RenderPass::new(&ctx, module, options.clone(), &buffers)),
+        }
+        drop(buffers);
+        let rpass = rpass.as_ref().unwrap();
+
+        let mut frame_times = Vec::with_capacity(options.bench_frames);
+        for frame in 0..options.bench_warmup + options.bench_frames {
+            controller.update(&frame_context);
+            let passes = [(
+                View::mono(size.width),
+                controller.eye_push_constants(Eye::mono(size.width)),
+            )];
+            let start = Instant::now();
+            rpass.render_offscreen(&ctx, &target, size.height, depth_texture, &passes);
+            if frame >= options.bench_warmup {
+                frame_times.push(start.elapsed().as_secs_f64() * 1000.0);
+            }
+        }
+        stats.push(Stats::new(shader, frame_times));
+    }
+
+    println!("{}", to_json(&ctx.adapter_info, options, &stats));
+}
+
+fn to_json(adapter_info: &wgpu::AdapterInfo, options: &Options, stats: &[Stats]) -> String {
+    let mut json = String::new();
+    json.push_str("{\n  \"adapter\": {\n");
+    let _ = writeln!(json, "    \"name\": {},", string(&adapter_info.name));
+    let _ = writeln!(json, "    \"vendor\": {},", adapter_info.vendor);
+    let _ = writeln!(json, "    \"device\": {},", adapter_info.device);
+    let device_type = format!("{:?}", adapter_info.device_type);
+    let _ = writeln!(json, "    \"device_type\": {},", string(&device_type));
+    let _ = writeln!(json, "    \"driver\": {},", string(&adapter_info.driver));
+    let _ = writeln!(json, "    \"driver_info\": {},", string(&adapter_info.driver_info));
+    let _ = writeln!(json, "    \"backend\": {}", string(adapter_info.backend.to_str()));
+    json.push_str("  },\n");
+    let size = options.bench_size;
+    let _ = writeln!(json, "  \"width\": {},", size.width);
+    let _ = writeln!(json, "  \"height\": {},", size.height);
+    let _ = writeln!(json, "  \"warmup_frames\": {},", options.bench_warmup);
+    let _ = writeln!(json, "  \"measured_frames\": {},", options.bench_frames);
+    json.push_str("  \"shaders\": [");
+    for (i, stats) in stats.iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let _ = write!(
+            json,
+            "{separator}\n    {{ \"shader\": {}, \"min_ms\": {:.4}, \"median_ms\": {:.4}, \"p95_ms\": {:.4} }}",
+            string(&stats.shader.to_string()),
+            stats.min,
+            stats.median,
+            stats.p95,
+        );
+    }
+    json.push_str("\n  ]\n}");
+    json
+}
+
+fn string(s: &str) -> String {
+    let mut escaped = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let frame_times = vec![
+            7.0, 13.0, 2.0, 19.0, 5.0, 11.0, 1.0, 16.0, 9.0, 20.0, 4.0, 14.0, 8.0, 18.0, 3.0, 12.0,
+            6.0, 17.0, 10.0, 15.0,
+        ];
+        let stats = Stats::new(RustGPUShader::Mandelbrot, frame_times);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.median, 10.0);
+        assert_eq!(stats.p95, 19.0);
+
+        let stats = Stats::new(RustGPUShader::Mandelbrot, vec![4.5]);
+        assert_eq!((stats.min, stats.median, stats.p95), (4.5, 4.5, 4.5));
+    }
+}
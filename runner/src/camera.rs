@@ -1,7 +1,8 @@
+use crate::controller::KeyInput;
 use core::f32::consts::PI;
 use egui_winit::winit::{
     dpi::PhysicalSize,
-    event::{ElementState, KeyEvent},
+    event::ElementState,
     keyboard::{KeyCode, PhysicalKey},
 };
 use glam::{vec3, Mat4, Quat, Vec2, Vec2Swizzles, Vec3};
@@ -24,10 +25,18 @@ impl RotationCamera {
     }
 
     pub fn build_view_projection_matrix(&self) -> Mat4 {
-        let view = Mat4::look_at_rh(Vec3::Z * self.zoom, Vec3::ZERO, Vec3::Y);
+        self.build_eye_view_projection_matrix(0.0)
+    }
+
+    /// The view projection of a camera moved sideways by `eye_offset`, with the
+    /// frustum skewed so that the origin stays at the center of the image.
+    pub fn build_eye_view_projection_matrix(&self, eye_offset: f32) -> Mat4 {
+        let eye = Vec3::X * eye_offset;
+        let view = Mat4::look_at_rh(Vec3::Z * self.zoom + eye, eye, Vec3::Y);
         let proj = Mat4::perspective_rh(PI / 4.0, self.aspect, 0.01, 100.0);
+        let skew = Mat4::from_translation(Vec3::X * eye_offset * proj.x_axis.x / self.zoom);
         let rot = Mat4::from_quat(self.rot);
-        proj * view * rot
+        skew * proj * view * rot
     }
 
     pub fn rotate(&mut self, v: Vec2) {
@@ -96,7 +105,7 @@ impl FirstPersonCamera {
         self.position += translate.normalize_or_zero() * 4.0 * dt;
     }
 
-    pub fn keyboard_input(&mut self, event: KeyEvent) {
+    pub fn keyboard_input(&mut self, event: KeyInput) {
         let pressed = event.state == ElementState::Pressed;
         let amount = if pressed { 1.0 } else { 0.0 };
         if let PhysicalKey::Code(keycode) = event.physical_key {
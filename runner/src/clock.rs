@@ -0,0 +1,91 @@
+use egui::Ui;
+use std::time::Instant;
+
+/// Duration of a single frame step while paused.
+const STEP: f64 = 1.0 / 60.0;
+
+/// The global playback clock shared by all controllers.
+pub struct Clock {
+    time: f64,
+    max_time: f64,
+    speed: f32,
+    paused: bool,
+    step: bool,
+    last_tick: Instant,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            max_time: 0.0,
+            speed: 1.0,
+            paused: false,
+            step: false,
+            last_tick: Instant::now(),
+        }
+    }
+
+    /// Advances the clock, should be called once per frame.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        let elapsed = (now - self.last_tick).as_secs_f64();
+        self.last_tick = now;
+        let dt = if self.step {
+            self.step = false;
+            STEP
+        } else if self.paused {
+            0.0
+        } else {
+            elapsed * self.speed as f64
+        };
+        self.time = (self.time + dt).max(0.0);
+        self.max_time = self.max_time.max(self.time);
+    }
+
+    /// Playback time in seconds.
+    pub fn time(&self) -> f32 {
+        self.time as f32
+    }
+
+    /// Jumps to `time` and pauses playback.
+    pub fn pause_at(&mut self, time: f32) {
+        self.time = time.max(0.0) as f64;
+        self.max_time = self.max_time.max(self.time);
+        self.paused = true;
+    }
+
+    /// Jumps to `time` without changing the playback state, used when replaying
+    /// a recording instead of [`Clock::tick`].
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.max(0.0) as f64;
+        self.max_time = self.max_time.max(self.time);
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button(if self.paused { "▶" } else { "⏸" }).clicked() {
+                self.paused = !self.paused;
+            }
+            if ui
+                .add_enabled(self.paused, egui::Button::new("⏭"))
+                .on_hover_text("Step one frame")
+                .clicked()
+            {
+                self.step = true;
+            }
+            ui.add(
+                egui::DragValue::new(&mut self.speed)
+                    .clamp_range(0.0..=8.0)
+                    .speed(0.01)
+                    .suffix("x"),
+            );
+        });
+        let max_time = self.max_time.max(1.0);
+        ui.add(
+            egui::Slider::new(&mut self.time, 0.0..=max_time)
+                .max_decimals(2)
+                .suffix("s"),
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub struct GraphicsContext<'a> {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
+    pub adapter_info: wgpu::AdapterInfo,
 }
 
 impl<'a> GraphicsContext<'a> {
@@ -80,6 +81,7 @@ impl<'a> GraphicsContext<'a> {
             device,
             queue,
             config,
+            adapter_info: adapter.get_info(),
         }
     }
 
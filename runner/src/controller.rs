@@ -1,4 +1,6 @@
 use crate::model::Vertex;
+use crate::params::Param;
+use crate::probe::Probe;
 use crate::window::UserEvent;
 use crate::{shaders, RustGPUShader};
 use egui::{Context, Ui};
@@ -6,8 +8,12 @@ use egui_winit::winit::{
     dpi::{PhysicalPosition, PhysicalSize},
     event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta},
     event_loop::EventLoopProxy,
+    keyboard::PhysicalKey,
 };
 use shaders::*;
+use glam::Vec2;
+use shared::{from_pixels, push_constants::Eye, spectrum::Spectrum};
+use std::{borrow::Cow, collections::HashSet};
 
 pub enum BindGroupBufferType<'a> {
     #[allow(dead_code)]
@@ -18,12 +24,117 @@ pub enum BindGroupBufferType<'a> {
 pub struct SSBO<'a> {
     pub data: &'a [u8],
     pub read_only: bool,
+    /// Uploaded every frame, rather than only when the buffers are recreated.
+    pub per_frame: bool,
 }
 
 pub struct Uniform<'a> {
     pub data: &'a [u8],
 }
 
+/// The parts of a [`KeyEvent`] passed to controllers, which unlike
+/// [`KeyEvent`] can be constructed when replaying a recording.
+#[derive(Clone, Copy, PartialEq)]
+pub struct KeyInput {
+    pub physical_key: PhysicalKey,
+    pub state: ElementState,
+}
+
+impl From<&KeyEvent> for KeyInput {
+    fn from(event: &KeyEvent) -> Self {
+        Self {
+            physical_key: event.physical_key,
+            state: event.state,
+        }
+    }
+}
+
+/// An event that changes the state of a controller.
+#[derive(Clone, Copy)]
+pub enum InputEvent {
+    Keyboard(KeyInput),
+    MouseInput(ElementState, MouseButton),
+    MouseMove(PhysicalPosition<f64>),
+    MouseDelta((f64, f64)),
+    MouseScroll(MouseScrollDelta),
+    Resize(PhysicalSize<u32>),
+}
+
+impl InputEvent {
+    pub fn apply(self, controller: &mut dyn Controller) {
+        match self {
+            InputEvent::Keyboard(input) => controller.keyboard_input(input),
+            InputEvent::MouseInput(state, button) => controller.mouse_input(state, button),
+            InputEvent::MouseMove(position) => controller.mouse_move(position),
+            InputEvent::MouseDelta(delta) => controller.mouse_delta(delta),
+            InputEvent::MouseScroll(delta) => controller.mouse_scroll(delta),
+            InputEvent::Resize(size) => controller.resize(size),
+        }
+    }
+}
+
+/// Keys and mouse buttons currently held, and the last cursor position.
+#[derive(Default)]
+pub struct InputState {
+    pub cursor: PhysicalPosition<f64>,
+    pub keys: HashSet<PhysicalKey>,
+    pub buttons: HashSet<MouseButton>,
+}
+
+impl InputState {
+    pub fn apply(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Keyboard(KeyInput {
+                physical_key,
+                state: ElementState::Pressed,
+            }) => {
+                self.keys.insert(physical_key);
+            }
+            InputEvent::Keyboard(KeyInput {
+                physical_key,
+                state: ElementState::Released,
+            }) => {
+                self.keys.remove(&physical_key);
+            }
+            InputEvent::MouseInput(ElementState::Pressed, button) => {
+                self.buttons.insert(button);
+            }
+            InputEvent::MouseInput(ElementState::Released, button) => {
+                self.buttons.remove(&button);
+            }
+            InputEvent::MouseMove(position) => self.cursor = position,
+            _ => {}
+        }
+    }
+
+    /// Releases the held keys and buttons, as a controller that is switched
+    /// out won't receive their release events.
+    pub fn release(&mut self, controller: &mut dyn Controller) {
+        for physical_key in self.keys.drain() {
+            controller.keyboard_input(KeyInput {
+                physical_key,
+                state: ElementState::Released,
+            });
+        }
+        for button in self.buttons.drain() {
+            controller.mouse_input(ElementState::Released, button);
+        }
+    }
+}
+
+/// Everything a controller may need to know about the current frame.
+pub struct FrameContext<'a> {
+    /// Seconds since the previous frame, unaffected by pausing the clock.
+    pub dt: f32,
+    /// Playback time of the clock in seconds.
+    pub time: f32,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    pub input: &'a InputState,
+    /// Band energies of the loaded audio at the clock's time, zero without audio.
+    pub spectrum: &'a Spectrum,
+}
+
 #[derive(Default)]
 pub struct BufferData<'a> {
     pub vertex: Option<&'a [Vertex]>,
@@ -36,13 +147,18 @@ pub trait Controller {
     fn new(size: PhysicalSize<u32>) -> Self
     where
         Self: Sized;
-    fn keyboard_input(&mut self, _input: KeyEvent) {}
+    fn keyboard_input(&mut self, _input: KeyInput) {}
     fn mouse_input(&mut self, _state: ElementState, _button: MouseButton) {}
     fn mouse_move(&mut self, _position: PhysicalPosition<f64>) {}
     fn mouse_delta(&mut self, _delta: (f64, f64)) {}
     fn mouse_scroll(&mut self, _delta: MouseScrollDelta) {}
     fn resize(&mut self, size: PhysicalSize<u32>);
-    fn update(&mut self);
+    /// Called when the controller becomes the active one, after being resized to the window.
+    fn on_activate(&mut self) {}
+    /// Called when another controller becomes active, after the keys and buttons
+    /// still held have been released.
+    fn on_deactivate(&mut self) {}
+    fn update(&mut self, ctx: &FrameContext);
     fn push_constants(&self) -> &[u8];
     fn ui(&mut self, _ctx: &Context, _ui: &mut Ui, _event_proxy: &EventLoopProxy<UserEvent>) {}
     fn has_ui(&self) -> bool {
@@ -51,9 +167,61 @@ pub trait Controller {
     fn buffers(&self) -> BufferData<'_> {
         BufferData::default()
     }
+    /// Brings the data of [`Controller::buffers`] up to date, returning whether it changed.
+    /// Controllers call it from their ui, and the bench before measuring.
+    fn prepare_buffers(&mut self) -> bool {
+        false
+    }
+    /// Whether the camera can be offset for stereo rendering.
+    fn has_stereo(&self) -> bool {
+        false
+    }
+    /// Push constants for a single eye, called once per eye in each frame.
+    fn eye_push_constants(&self, _eye: Eye) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.push_constants())
+    }
+    /// Push constants for a 2:1 equirectangular panorama around the camera,
+    /// if the shader supports it.
+    fn panorama_push_constants(&self, _width: u32) -> Option<Vec<u8>> {
+        None
+    }
     fn cursor_visible(&self) -> bool {
         true
     }
+    /// The signed distance at the center of every pixel, in rows from top to bottom.
+    fn distance_field(&self) -> Option<Vec<f32>> {
+        None
+    }
+    /// The world position under the cursor and the values evaluated there.
+    fn probe(&self) -> Option<Probe> {
+        None
+    }
+    /// Selects the pixel the shader writes debug values for, if it has a [`Controller::debug_buffer`].
+    fn inspect(&mut self, _pixel: Option<PhysicalPosition<u32>>) {}
+    /// Index of the writable storage buffer the shader writes debug values of the inspected pixel to.
+    fn debug_buffer(&self) -> Option<usize> {
+        None
+    }
+    /// Labels and values decoded from the contents of the debug buffer.
+    fn debug_values(&self, _buffer: &[u8]) -> Vec<(&'static str, String)> {
+        vec![]
+    }
+    /// Parameters that can be set by name, including the camera.
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![]
+    }
+}
+
+/// Evaluates `f` at the center of every pixel, in the coordinates of [`from_pixels`].
+pub fn sample_pixels(size: PhysicalSize<u32>, f: impl Fn(Vec2) -> f32 + Sync) -> Vec<f32> {
+    use rayon::prelude::*;
+    (0..size.width * size.height)
+        .into_par_iter()
+        .map(|i| {
+            let pixel = Vec2::new((i % size.width) as f32, (i / size.width) as f32) + 0.5;
+            f(from_pixels(pixel, size.into()))
+        })
+        .collect()
 }
 
 pub fn new_controller(shader: RustGPUShader, size: PhysicalSize<u32>) -> Box<dyn Controller> {
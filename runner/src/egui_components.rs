@@ -1,3 +1,4 @@
 pub mod drag_and_drop;
 pub mod enabled_number;
+pub mod palette;
 pub mod repetition;
@@ -0,0 +1,309 @@
+use crate::controller::{BindGroupBufferType, SSBO};
+use bytemuck::Zeroable;
+use egui::{Color32, ComboBox, DragValue, Rect, Sense, Stroke, Ui, Vec2};
+use shared::palette::{CosinePalette, Palette, LUT_SIZE};
+use strum::IntoEnumIterator;
+
+/// Viridis, Magma and Cividis are perceptually uniform and readable with the
+/// common forms of color blindness.
+#[derive(Clone, Copy, PartialEq, strum::EnumIter, strum::Display)]
+pub enum PaletteChoice {
+    Grayscale,
+    Viridis,
+    Magma,
+    Cividis,
+    BlueOrange,
+    Rainbow,
+    /// The palette as last edited.
+    Custom,
+}
+
+impl PaletteChoice {
+    fn mode(self) -> Option<Mode> {
+        use PaletteChoice::*;
+        let hex = |colors: &[u32]| {
+            let n = colors.len() - 1;
+            Mode::Gradient(
+                colors
+                    .iter()
+                    .enumerate()
+                    .map(|(i, hex)| Stop {
+                        position: i as f32 / n as f32,
+                        color: [(hex >> 16) as u8, (hex >> 8) as u8, *hex as u8],
+                    })
+                    .collect(),
+            )
+        };
+        Some(match self {
+            Grayscale => hex(&[0x000000, 0xffffff]),
+            Viridis => hex(&[
+                0x440154, 0x472d7b, 0x3b528b, 0x2c728e, 0x21918c, 0x28ae80, 0x5ec962, 0xaddc30,
+                0xfde725,
+            ]),
+            Magma => hex(&[
+                0x000004, 0x1c1044, 0x4f127b, 0x812581, 0xb5367a, 0xe55064, 0xfb8761, 0xfec287,
+                0xfcfdbf,
+            ]),
+            Cividis => hex(&[
+                0x00204d, 0x00336f, 0x39486b, 0x575d6d, 0x707173, 0x8a8779, 0xa69d75, 0xc4b56c,
+                0xe4cf5b, 0xffea46,
+            ]),
+            BlueOrange => hex(&[0xa6d9ff, 0xe6994d]),
+            Rainbow => Mode::Cosine([
+                [0.5, 0.5, 0.5],
+                [0.5, 0.5, 0.5],
+                [1.0, 1.0, 1.0],
+                [0.0, 0.33, 0.67],
+            ]),
+            Custom => return None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Stop {
+    position: f32,
+    /// sRGB, like the colors of the shaders
+    color: [u8; 3],
+}
+
+#[derive(Clone, PartialEq)]
+enum Mode {
+    /// Stops sorted by position, at least two.
+    Gradient(Vec<Stop>),
+    /// The `a`, `b`, `c` and `d` of a [`CosinePalette`].
+    Cosine([[f32; 3]; 4]),
+}
+
+/// A palette picked from the built-ins or edited as a gradient or a cosine
+/// palette, bound by shaders as a [`Palette`] storage buffer.
+pub struct PaletteEditor {
+    pub choice: PaletteChoice,
+    built: PaletteChoice,
+    mode: Mode,
+    selected: usize,
+    palette: Box<Palette>,
+}
+
+impl PaletteEditor {
+    pub fn new(choice: PaletteChoice) -> Self {
+        let mut editor = Self {
+            choice,
+            built: choice,
+            mode: choice
+                .mode()
+                .unwrap_or_else(|| PaletteChoice::Grayscale.mode().unwrap()),
+            selected: 0,
+            palette: Box::new(Palette::zeroed()),
+        };
+        editor.build();
+        editor
+    }
+
+    /// Switches to the palette of `choice` if it was changed, e.g. by a parameter.
+    pub fn update(&mut self) {
+        if self.choice != self.built {
+            if let Some(mode) = self.choice.mode() {
+                self.mode = mode;
+                self.selected = 0;
+            }
+            self.built = self.choice;
+            self.build();
+        }
+    }
+
+    /// A per frame storage buffer holding the [`Palette`].
+    pub fn buffer(&self) -> BindGroupBufferType<'_> {
+        BindGroupBufferType::SSBO(SSBO {
+            data: bytemuck::bytes_of(&*self.palette),
+            read_only: true,
+            per_frame: true,
+        })
+    }
+
+    fn build(&mut self) {
+        let palette = &mut *self.palette;
+        match &self.mode {
+            Mode::Gradient(stops) => {
+                palette.kind = Palette::LUT;
+                for (i, color) in palette.lut.iter_mut().enumerate() {
+                    let [r, g, b] = sample_gradient(stops, i as f32 / (LUT_SIZE - 1) as f32);
+                    *color = glam::vec4(r, g, b, 1.0).into();
+                }
+            }
+            Mode::Cosine([a, b, c, d]) => {
+                palette.kind = Palette::COSINE;
+                let v = |x: &[f32; 3]| glam::Vec3::from(*x).extend(0.0).into();
+                palette.cosine = CosinePalette {
+                    a: v(a),
+                    b: v(b),
+                    c: v(c),
+                    d: v(d),
+                };
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Palette:");
+            ComboBox::from_id_source("palette")
+                .selected_text(self.choice.to_string())
+                .show_ui(ui, |ui| {
+                    for choice in PaletteChoice::iter() {
+                        ui.selectable_value(&mut self.choice, choice, choice.to_string());
+                    }
+                });
+        });
+        self.update();
+
+        let mut mode = self.mode.clone();
+        ui.horizontal(|ui| {
+            let gradient = matches!(mode, Mode::Gradient(_));
+            if ui.selectable_label(gradient, "Gradient").clicked() && !gradient {
+                // Keeps the look of the cosine palette
+                mode = Mode::Gradient(
+                    (0..8)
+                        .map(|i| {
+                            let position = i as f32 / 7.0;
+                            Stop {
+                                position,
+                                color: to_srgb(self.palette.sample(position).into()),
+                            }
+                        })
+                        .collect(),
+                );
+                self.selected = 0;
+            }
+            if ui.selectable_label(!gradient, "Cosine").clicked() && gradient {
+                mode = PaletteChoice::Rainbow.mode().unwrap();
+            }
+        });
+
+        self.gradient_bar(ui, &mut mode);
+        match &mut mode {
+            Mode::Gradient(stops) => {
+                let selected = self.selected.min(stops.len() - 1);
+                ui.horizontal(|ui| {
+                    ui.color_edit_button_srgb(&mut stops[selected].color);
+                    let (min, max) = position_bounds(stops, selected);
+                    ui.add(
+                        DragValue::new(&mut stops[selected].position)
+                            .clamp_range(min..=max)
+                            .speed(0.01),
+                    );
+                    if ui.button("+").on_hover_text("Add a stop").clicked() {
+                        // Halfway to the next stop, or to the previous one for the last
+                        let i = selected.min(stops.len() - 2);
+                        let position = 0.5 * (stops[i].position + stops[i + 1].position);
+                        let color = to_srgb(sample_gradient(stops, position));
+                        stops.insert(i + 1, Stop { position, color });
+                        self.selected = i + 1;
+                    }
+                    if ui
+                        .add_enabled(stops.len() > 2, egui::Button::new("−"))
+                        .on_hover_text("Remove the stop")
+                        .clicked()
+                    {
+                        stops.remove(selected);
+                        self.selected = selected.min(stops.len() - 1);
+                    }
+                });
+            }
+            Mode::Cosine(coefficients) => {
+                for (label, coefficient) in ["a", "b", "c", "d"].iter().zip(coefficients) {
+                    ui.horizontal(|ui| {
+                        ui.label(*label);
+                        for x in coefficient {
+                            ui.add(DragValue::new(x).clamp_range(-2.0..=2.0).speed(0.01));
+                        }
+                    });
+                }
+            }
+        }
+
+        if mode != self.mode {
+            self.mode = mode;
+            self.choice = PaletteChoice::Custom;
+            self.built = PaletteChoice::Custom;
+            self.build();
+        }
+    }
+
+    /// The palette with draggable handles for the stops of a gradient.
+    fn gradient_bar(&mut self, ui: &mut Ui, mode: &mut Mode) {
+        let width = ui.available_width().min(250.0);
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(width, 20.0), Sense::hover());
+        const SEGMENTS: usize = 64;
+        for i in 0..SEGMENTS {
+            let t = (i as f32 + 0.5) / SEGMENTS as f32;
+            let color = to_srgb(self.palette.sample(t).into());
+            let x = |i: usize| rect.left() + rect.width() * i as f32 / SEGMENTS as f32;
+            ui.painter().rect_filled(
+                Rect::from_x_y_ranges(x(i)..=x(i + 1), rect.y_range()),
+                0.0,
+                Color32::from_rgb(color[0], color[1], color[2]),
+            );
+        }
+
+        let Mode::Gradient(stops) = mode else {
+            return;
+        };
+        let (handles, _) = ui.allocate_exact_size(Vec2::new(width, 12.0), Sense::hover());
+        for i in 0..stops.len() {
+            let x = rect.left() + rect.width() * stops[i].position;
+            let handle = Rect::from_center_size(
+                egui::pos2(x, handles.center().y),
+                Vec2::splat(handles.height()),
+            );
+            let response = ui.interact(handle, ui.id().with(("palette_stop", i)), Sense::drag());
+            if response.drag_started() || response.clicked() {
+                self.selected = i;
+            }
+            if response.dragged() {
+                let (min, max) = position_bounds(stops, i);
+                stops[i].position = (stops[i].position + response.drag_delta().x / rect.width())
+                    .clamp(min, max);
+            }
+            let [r, g, b] = stops[i].color;
+            let stroke = if i == self.selected {
+                Stroke::new(2.0, ui.visuals().strong_text_color())
+            } else {
+                Stroke::new(1.0, ui.visuals().weak_text_color())
+            };
+            ui.painter().add(egui::Shape::convex_polygon(
+                vec![
+                    egui::pos2(x, handle.top()),
+                    handle.right_bottom(),
+                    handle.left_bottom(),
+                ],
+                Color32::from_rgb(r, g, b),
+                stroke,
+            ));
+        }
+    }
+}
+
+/// Stops can't be moved past their neighbours, so they stay sorted.
+fn position_bounds(stops: &[Stop], i: usize) -> (f32, f32) {
+    let min = if i == 0 { 0.0 } else { stops[i - 1].position };
+    let max = stops.get(i + 1).map_or(1.0, |stop| stop.position);
+    (min, max)
+}
+
+/// The color at `t` between 0 and 1 as sRGB floats.
+fn sample_gradient(stops: &[Stop], t: f32) -> [f32; 3] {
+    let color = |stop: &Stop| glam::Vec3::from(stop.color.map(|x| x as f32 / 255.0));
+    let i = stops
+        .iter()
+        .position(|stop| stop.position > t)
+        .unwrap_or(stops.len())
+        .clamp(1, stops.len() - 1);
+    let (a, b) = (&stops[i - 1], &stops[i]);
+    let x = ((t - a.position) / (b.position - a.position).max(f32::EPSILON)).clamp(0.0, 1.0);
+    color(a).lerp(color(b), x).into()
+}
+
+fn to_srgb(color: [f32; 3]) -> [u8; 3] {
+    color.map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
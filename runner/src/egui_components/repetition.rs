@@ -1,8 +1,9 @@
+use crate::params::Param;
 use egui::{ComboBox, DragValue, Ui};
 use glam::{UVec2, Vec2};
 use strum::IntoEnumIterator;
 
-#[derive(Clone, Copy, PartialEq, strum::EnumIter, strum::Display)]
+#[derive(Clone, Copy, Debug, PartialEq, strum::EnumIter, strum::Display)]
 #[repr(u32)]
 pub enum RepetitionValue {
     None,
@@ -13,7 +14,7 @@ pub enum RepetitionValue {
     Mirrored,
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Repetition {
     pub current: RepetitionValue,
     pub unlimited: Vec2,
@@ -89,6 +90,55 @@ impl Repetition {
             }
         }
     }
+
+    /// The kind of repetition, followed by the parameters of that kind.
+    pub fn params(&mut self) -> Vec<Param<'_>> {
+        use RepetitionValue::*;
+        let current = self.current;
+        let dim = |name, x| Param::f32(name, x, 0.01..=1.0);
+        let mut params = vec![Param::choice("repetition", &mut self.current)];
+        match current {
+            None => {}
+            Unlimited | Mirrored => {
+                let dims = if current == Unlimited {
+                    &mut self.unlimited
+                } else {
+                    &mut self.mirrored
+                };
+                params.extend([
+                    dim("repetition_dim_x", &mut dims.x),
+                    dim("repetition_dim_y", &mut dims.y),
+                ]);
+            }
+            Limited => {
+                let (dims, n1, n2) = &mut self.limited;
+                params.extend([
+                    Param::u32("repetition_n1_x", &mut n1.x, 0..=4),
+                    Param::u32("repetition_n1_y", &mut n1.y, 0..=4),
+                    Param::u32("repetition_n2_x", &mut n2.x, 0..=4),
+                    Param::u32("repetition_n2_y", &mut n2.y, 0..=4),
+                    dim("repetition_dim_x", &mut dims.x),
+                    dim("repetition_dim_y", &mut dims.y),
+                ]);
+            }
+            Rectangular => {
+                let (d, n) = &mut self.rectangular;
+                params.extend([
+                    Param::u32("repetition_n_x", &mut n.x, 1..=4),
+                    Param::u32("repetition_n_y", &mut n.y, 1..=4),
+                    dim("repetition_dim", d),
+                ]);
+            }
+            Angular => {
+                let (radius, n) = &mut self.angular;
+                params.extend([
+                    Param::u32("repetition_n", n, 1..=10),
+                    dim("repetition_radius", radius),
+                ]);
+            }
+        }
+        params
+    }
 }
 
 fn drag_value_dim(ui: &mut Ui, value: &mut f32) {
@@ -0,0 +1,54 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Writes a Portable Float Map, `data` holds rows from top to bottom with
+/// either 1 (grayscale) or 3 (RGB) channels per pixel.
+pub fn save_pfm(
+    path: &Path,
+    width: u32,
+    height: u32,
+    channels: usize,
+    data: &[f32],
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let kind = if channels == 1 { "Pf" } else { "PF" };
+    // A negative scale marks the data as little endian
+    write!(writer, "{kind}\n{width} {height}\n-1.0\n")?;
+    // PFM stores the rows from bottom to top
+    for row in data.chunks(width as usize * channels).rev() {
+        for x in row {
+            writer.write_all(&x.to_le_bytes())?;
+        }
+    }
+    writer.flush()
+}
+
+/// Writes an OpenEXR image with the same layout as [`save_pfm`].
+#[cfg(feature = "exr")]
+pub fn save_exr(
+    path: &Path,
+    width: u32,
+    height: u32,
+    channels: usize,
+    data: &[f32],
+) -> Result<(), exr::error::Error> {
+    use exr::prelude::*;
+    let (width, height) = (width as usize, height as usize);
+    let pixel = |Vec2(x, y): Vec2<usize>| (y * width + x) * channels;
+    if channels == 1 {
+        let channels = SpecificChannels::build()
+            .with_channel("Y")
+            .with_pixel_fn(|position| (data[pixel(position)],));
+        Image::from_channels((width, height), channels)
+            .write()
+            .to_file(path)
+    } else {
+        write_rgb_file(path, width, height, |x, y| {
+            let i = pixel(Vec2(x, y));
+            (data[i], data[i + 1], data[i + 2])
+        })
+    }
+}
@@ -0,0 +1,56 @@
+use egui::{Color32, Ui};
+use egui_winit::winit::dpi::PhysicalPosition;
+use std::fmt;
+
+/// The rendered color of a pixel and the debug values the shader wrote for it.
+pub struct Inspection {
+    pub pixel: PhysicalPosition<u32>,
+    pub color: [u8; 4],
+    pub values: Vec<(&'static str, String)>,
+}
+
+impl Inspection {
+    fn hex(&self) -> String {
+        let [r, g, b, a] = self.color;
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+
+    pub fn ui(&self, ui: &mut Ui) {
+        let [r, g, b, a] = self.color;
+        egui::Grid::new("inspector").num_columns(2).show(ui, |ui| {
+            ui.label("pixel");
+            ui.monospace(format!("{}, {}", self.pixel.x, self.pixel.y));
+            ui.end_row();
+            ui.label("color");
+            ui.horizontal(|ui| {
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(16.0, 16.0), egui::Sense::hover());
+                ui.painter()
+                    .rect_filled(rect, 2.0, Color32::from_rgba_unmultiplied(r, g, b, a));
+                ui.monospace(self.hex());
+            });
+            ui.end_row();
+            ui.label("rgba");
+            ui.monospace(format!("{r} {g} {b} {a}"));
+            ui.end_row();
+            for (label, value) in &self.values {
+                ui.label(*label);
+                ui.monospace(value);
+                ui.end_row();
+            }
+        });
+        if ui.button("Copy").clicked() {
+            ui.output_mut(|output| output.copied_text = self.to_string());
+        }
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pixel={},{} color={}", self.pixel.x, self.pixel.y, self.hex())?;
+        for (label, value) in &self.values {
+            write!(f, " {label}={value}")?;
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,43 @@
+use egui_winit::winit::dpi::PhysicalSize;
+use std::path::PathBuf;
 use structopt::StructOpt;
 use strum::{Display, EnumIter, EnumString};
 
 mod app;
+#[cfg(not(target_arch = "wasm32"))]
+mod audio;
+#[cfg(not(target_arch = "wasm32"))]
+mod bench;
 mod camera;
+mod clock;
 mod context;
 mod controller;
 mod egui_components;
 mod fps_counter;
+#[cfg(not(target_arch = "wasm32"))]
+mod hdr;
+#[cfg(not(target_arch = "wasm32"))]
+mod inspector;
 mod model;
+mod modulation;
+mod params;
+#[cfg(not(target_arch = "wasm32"))]
+mod presets;
+mod probe;
+#[cfg(not(target_arch = "wasm32"))]
+mod recording;
+mod reflection;
 mod render_pass;
+#[cfg(not(target_arch = "wasm32"))]
+mod scaffold;
+#[cfg(not(target_arch = "wasm32"))]
+mod screenshot;
 mod shader;
 mod shaders;
 mod state;
+mod stereo;
+#[cfg(not(target_arch = "wasm32"))]
+mod svg;
 mod texture;
 mod ui;
 mod window;
@@ -33,9 +59,40 @@ pub enum RustGPUShader {
     ProceduralGeneration,
 }
 
-#[derive(StructOpt, Clone, Copy)]
+/// The kind of demo created by `new-demo`.
+#[derive(EnumString, Display, PartialEq, Eq, Copy, Clone)]
+pub enum DemoKind {
+    /// A fullscreen 2D shader with pan and zoom
+    #[strum(serialize = "2d")]
+    TwoD,
+    /// A ray marched 3D scene with an orbiting camera
+    #[strum(serialize = "3d")]
+    ThreeD,
+    /// A vertex and index buffer drawn with a rotation camera
+    #[strum(serialize = "mesh")]
+    Mesh,
+}
+
+#[derive(StructOpt, Clone)]
+pub enum Command {
+    /// Create a new demo crate with its push constants and controller,
+    /// and register it with the runner
+    NewDemo {
+        /// Name of the demo, e.g. `my-demo`
+        name: String,
+
+        /// One of `2d`, `3d` or `mesh`
+        #[structopt(long, default_value = "2d")]
+        kind: DemoKind,
+    },
+}
+
+#[derive(StructOpt, Clone)]
 #[structopt(name = "example-runner-wgpu")]
 pub struct Options {
+    #[structopt(subcommand)]
+    command: Option<Command>,
+
     #[structopt(short, long, default_value = "SdfBuilder")]
     shader: RustGPUShader,
 
@@ -43,6 +100,58 @@ pub struct Options {
     // https://github.com/gfx-rs/wgpu/issues/5128
     #[structopt(long)]
     validate_spirv: bool,
+
+    /// Restore the shader and view stored in a screenshot
+    #[structopt(long, parse(from_os_str))]
+    from_image: Option<PathBuf>,
+
+    /// Set a parameter of the selected shader, e.g. `--set num_iterations=200`
+    #[structopt(long = "set", number_of_values = 1, parse(try_from_str = params::parse_assignment))]
+    set: Vec<(String, String)>,
+
+    /// Record all input to a file, to be replayed with `--replay`
+    #[structopt(long, parse(from_os_str))]
+    record: Option<PathBuf>,
+
+    /// Drive the spectrum from a WAV file, analysed at the time of the clock
+    #[structopt(long, parse(from_os_str))]
+    audio: Option<PathBuf>,
+
+    /// Replay input recorded with `--record`
+    #[structopt(long, parse(from_os_str), conflicts_with = "record")]
+    replay: Option<PathBuf>,
+
+    /// Replay without a window, and print the parameters of the shader active at the end
+    #[structopt(long, requires = "replay")]
+    headless: bool,
+
+    /// Benchmark the given shaders, or all of them, and print the frame times as JSON
+    #[structopt(long, min_values = 0)]
+    bench: Option<Vec<RustGPUShader>>,
+
+    /// Resolution of the benchmark
+    #[structopt(long, default_value = "1280x720", parse(try_from_str = parse_size))]
+    bench_size: PhysicalSize<u32>,
+
+    /// Frames rendered before measuring
+    #[structopt(long, default_value = "30")]
+    bench_warmup: usize,
+
+    /// Frames measured per shader
+    #[structopt(long, default_value = "200")]
+    bench_frames: usize,
+}
+
+/// Parses a resolution written as `WIDTHxHEIGHT`, e.g. `1280x720`.
+fn parse_size(s: &str) -> Result<PhysicalSize<u32>, String> {
+    let error = || format!("expected `WIDTHxHEIGHT`, got `{s}`");
+    let (width, height) = s.split_once('x').ok_or_else(error)?;
+    let width = width.trim().parse::<u32>().map_err(|_| error())?;
+    let height = height.trim().parse::<u32>().map_err(|_| error())?;
+    if width == 0 || height == 0 {
+        return Err(error());
+    }
+    Ok(PhysicalSize::new(width, height))
 }
 
 pub fn main() {
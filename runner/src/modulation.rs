@@ -0,0 +1,404 @@
+use crate::{controller::Controller, params::Param};
+use egui::{ComboBox, DragValue, Ui};
+use shared::spectrum::{Spectrum, NUM_BANDS};
+use std::{f32::consts::TAU, fmt, str::FromStr};
+use strum::IntoEnumIterator;
+
+#[derive(Clone, Copy, PartialEq, strum::EnumIter, strum::Display, strum::EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum Waveform {
+    Sine,
+    Triangle,
+    /// Smoothly interpolated random values, one per cycle.
+    Noise,
+    /// A new random value every cycle.
+    SteppedRandom,
+    /// An ADSR envelope triggered every cycle, with the gate open for half of it.
+    Envelope,
+    /// The energy of a frequency band of the loaded audio.
+    Band,
+}
+
+/// Times in seconds and the sustain level of an [`Waveform::Envelope`].
+#[derive(Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            attack: 0.1,
+            decay: 0.2,
+            sustain: 0.5,
+            release: 0.5,
+        }
+    }
+}
+
+impl Envelope {
+    /// The level `t` seconds after the trigger, with the gate closing at `gate`.
+    fn level(&self, t: f32, gate: f32) -> f32 {
+        let held = |t: f32| {
+            if t < self.attack {
+                t / self.attack
+            } else {
+                let decayed = ((t - self.attack) / self.decay.max(f32::EPSILON)).min(1.0);
+                1.0 - (1.0 - self.sustain) * decayed
+            }
+        };
+        if t < gate {
+            held(t)
+        } else {
+            held(gate) * (1.0 - (t - gate) / self.release.max(f32::EPSILON)).max(0.0)
+        }
+    }
+}
+
+/// Moves a numeric parameter around the value it would otherwise have.
+#[derive(Clone, PartialEq)]
+pub struct Modulator {
+    pub param: String,
+    pub waveform: Waveform,
+    /// Cycles per second of the clock.
+    pub rate: f32,
+    /// The largest offset from the unmodulated value, in units of the parameter.
+    pub depth: f32,
+    /// Offset in cycles.
+    pub phase: f32,
+    pub envelope: Envelope,
+    /// The [`Waveform::Band`] index.
+    pub band: usize,
+    /// The unmodulated value, taken from the parameter whenever it was changed
+    /// by something else since the last frame.
+    base: Option<f32>,
+    written: Option<f32>,
+}
+
+impl Modulator {
+    fn new(param: &Param) -> Self {
+        let depth = match param.number_range() {
+            Some(range) => 0.1 * (range.end() - range.start()),
+            None => 1.0,
+        };
+        Self {
+            param: param.name.to_string(),
+            waveform: Waveform::Sine,
+            rate: 0.5,
+            depth,
+            phase: 0.0,
+            envelope: Envelope::default(),
+            band: 0,
+            base: None,
+            written: None,
+        }
+    }
+
+    /// The offset at `time`, between -1 and 1 for oscillators or 0 and 1 for
+    /// envelopes and bands, before scaling by the depth.
+    pub fn offset(&self, time: f32, spectrum: &Spectrum) -> f32 {
+        let cycles = time * self.rate + self.phase;
+        match self.waveform {
+            Waveform::Sine => (TAU * cycles).sin(),
+            Waveform::Triangle => 4.0 * ((cycles + 0.75).rem_euclid(1.0) - 0.5).abs() - 1.0,
+            Waveform::Noise => {
+                let i = cycles.floor();
+                let t = cycles - i;
+                let t = t * t * (3.0 - 2.0 * t);
+                random(i) + (random(i + 1.0) - random(i)) * t
+            }
+            Waveform::SteppedRandom => random(cycles.floor()),
+            Waveform::Envelope => {
+                let period = 1.0 / self.rate.max(f32::EPSILON);
+                let t = cycles.rem_euclid(1.0) * period;
+                self.envelope.level(t, 0.5 * period)
+            }
+            Waveform::Band => spectrum.bands[self.band.min(NUM_BANDS - 1)],
+        }
+    }
+
+    fn apply(&mut self, param: &mut Param, time: f32, spectrum: &Spectrum) {
+        let Some(current) = param.number() else {
+            return;
+        };
+        if self.written != Some(current) {
+            self.base = Some(current);
+        }
+        let base = self.base.unwrap_or(current);
+        param.set_number(base + self.depth * self.offset(time, spectrum));
+        self.written = param.number();
+    }
+
+    fn restore(&mut self, param: &mut Param) {
+        if let Some(base) = self.base {
+            param.set_number(base);
+            self.written = param.number();
+        }
+    }
+
+    /// Returns whether the modulator should be removed.
+    fn ui(&mut self, ui: &mut Ui, id: usize, params: &[&String]) -> bool {
+        let mut remove = false;
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source(("modulator param", id))
+                .width(90.0)
+                .selected_text(&self.param)
+                .show_ui(ui, |ui| {
+                    for &name in params {
+                        ui.selectable_value(&mut self.param, name.clone(), name);
+                    }
+                });
+            ComboBox::from_id_source(("modulator waveform", id))
+                .width(90.0)
+                .selected_text(self.waveform.to_string())
+                .show_ui(ui, |ui| {
+                    for waveform in Waveform::iter() {
+                        ui.selectable_value(&mut self.waveform, waveform, waveform.to_string());
+                    }
+                });
+            remove = ui.small_button("✖").clicked();
+        });
+        ui.horizontal(|ui| {
+            if self.waveform == Waveform::Band {
+                ui.add(
+                    DragValue::new(&mut self.band)
+                        .clamp_range(0..=NUM_BANDS - 1)
+                        .prefix("band "),
+                );
+                ui.add(DragValue::new(&mut self.depth).speed(0.01).prefix("× "));
+                return;
+            }
+            ui.add(
+                DragValue::new(&mut self.rate)
+                    .clamp_range(0.001..=20.0)
+                    .speed(0.01)
+                    .suffix(" Hz"),
+            );
+            ui.add(DragValue::new(&mut self.depth).speed(0.01).prefix("± "));
+            ui.add(
+                DragValue::new(&mut self.phase)
+                    .clamp_range(0.0..=1.0)
+                    .speed(0.01)
+                    .prefix("phase "),
+            );
+        });
+        if self.waveform == Waveform::Envelope {
+            let envelope = &mut self.envelope;
+            ui.horizontal(|ui| {
+                for (value, prefix) in [
+                    (&mut envelope.attack, "A "),
+                    (&mut envelope.decay, "D "),
+                    (&mut envelope.sustain, "S "),
+                    (&mut envelope.release, "R "),
+                ] {
+                    let max = if prefix == "S " { 1.0 } else { 10.0 };
+                    ui.add(
+                        DragValue::new(value)
+                            .clamp_range(0.0..=max)
+                            .speed(0.01)
+                            .prefix(prefix),
+                    );
+                }
+            });
+        }
+        remove
+    }
+}
+
+/// Written as `param=Waveform rate=.. depth=.. phase=..`, followed by the
+/// envelope for [`Waveform::Envelope`] or the band for [`Waveform::Band`].
+impl fmt::Display for Modulator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}={} rate={} depth={} phase={}",
+            self.param, self.waveform, self.rate, self.depth, self.phase
+        )?;
+        if self.waveform == Waveform::Envelope {
+            let Envelope {
+                attack,
+                decay,
+                sustain,
+                release,
+            } = self.envelope;
+            write!(
+                f,
+                " attack={attack} decay={decay} sustain={sustain} release={release}"
+            )?;
+        }
+        if self.waveform == Waveform::Band {
+            write!(f, " band={}", self.band)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Modulator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut fields = s.split_whitespace();
+        let (param, waveform) = fields
+            .next()
+            .and_then(|field| field.split_once('='))
+            .ok_or_else(|| format!("expected `param=waveform`, got `{s}`"))?;
+        let mut modulator = Self {
+            param: param.to_string(),
+            waveform: waveform
+                .parse()
+                .map_err(|_| format!("unknown waveform `{waveform}`"))?,
+            rate: 0.5,
+            depth: 1.0,
+            phase: 0.0,
+            envelope: Envelope::default(),
+            band: 0,
+            base: None,
+            written: None,
+        };
+        for field in fields {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| format!("expected `key=value`, got `{field}`"))?;
+            let error = || format!("invalid value `{value}` for `{key}`");
+            if key == "band" {
+                modulator.band = value
+                    .parse()
+                    .ok()
+                    .filter(|band| *band < NUM_BANDS)
+                    .ok_or_else(error)?;
+                continue;
+            }
+            let value = value.parse().map_err(|_| error())?;
+            match key {
+                "rate" => modulator.rate = value,
+                "depth" => modulator.depth = value,
+                "phase" => modulator.phase = value,
+                "attack" => modulator.envelope.attack = value,
+                "decay" => modulator.envelope.decay = value,
+                "sustain" => modulator.envelope.sustain = value,
+                "release" => modulator.envelope.release = value,
+                _ => return Err(format!("unknown modulator setting `{key}`")),
+            }
+        }
+        Ok(modulator)
+    }
+}
+
+/// A random value between -1 and 1 for each integer `x`.
+fn random(x: f32) -> f32 {
+    let mut h = (x as i32 as u32 ^ 0x6a09e667).wrapping_mul(0x9e3779b9);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85ebca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2ae35);
+    h ^= h >> 16;
+    h as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// The modulators of the active controller, at most one per parameter,
+/// driven by the global clock.
+#[derive(Default)]
+pub struct Modulation {
+    pub modulators: Vec<Modulator>,
+}
+
+impl Modulation {
+    /// Modulates the parameters, should be called before the controller's update.
+    pub fn apply(&mut self, controller: &mut dyn Controller, time: f32, spectrum: &Spectrum) {
+        if self.modulators.is_empty() {
+            return;
+        }
+        let mut params = controller.params();
+        for modulator in &mut self.modulators {
+            if let Some(param) = params.iter_mut().find(|p| p.name == modulator.param) {
+                modulator.apply(param, time, spectrum);
+            }
+        }
+    }
+
+    /// Sets the modulated parameters back to their unmodulated values, e.g.
+    /// before saving them or switching shader.
+    pub fn restore(&mut self, controller: &mut dyn Controller) {
+        if self.modulators.is_empty() {
+            return;
+        }
+        let mut params = controller.params();
+        for modulator in &mut self.modulators {
+            if let Some(param) = params.iter_mut().find(|p| p.name == modulator.param) {
+                modulator.restore(param);
+            }
+        }
+    }
+
+    /// Restores the current parameters and replaces the modulators.
+    pub fn replace(&mut self, controller: &mut dyn Controller, modulators: Vec<Modulator>) {
+        self.restore(controller);
+        self.modulators = modulators;
+    }
+
+    /// One modulator per line.
+    pub fn serialize(&self) -> String {
+        self.modulators
+            .iter()
+            .map(|modulator| format!("{modulator}\n"))
+            .collect()
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, controller: &mut dyn Controller) {
+        let params = controller.params();
+        let numeric = params
+            .iter()
+            .filter(|param| param.number().is_some())
+            .collect::<Vec<_>>();
+        let names = numeric
+            .iter()
+            .map(|param| param.name.to_string())
+            .collect::<Vec<_>>();
+        let used = self
+            .modulators
+            .iter()
+            .map(|modulator| modulator.param.clone())
+            .collect::<Vec<_>>();
+        // Removed modulators, or their previous parameter, are restored once
+        // the parameters are no longer borrowed
+        let mut restore = vec![];
+        let mut removed = None;
+        for (i, modulator) in self.modulators.iter_mut().enumerate() {
+            let available = names
+                .iter()
+                .filter(|name| **name == modulator.param || !used.contains(name))
+                .collect::<Vec<_>>();
+            let before = modulator.clone();
+            if modulator.ui(ui, i, &available) {
+                removed = Some(i);
+                restore.push(before);
+            } else if modulator.param != before.param {
+                modulator.base = None;
+                modulator.written = None;
+                restore.push(before);
+            }
+            ui.separator();
+        }
+        let unmodulated = numeric
+            .iter()
+            .find(|param| self.modulators.iter().all(|m| m.param != param.name));
+        let add = ui
+            .add_enabled(unmodulated.is_some(), egui::Button::new("Add modulator"))
+            .clicked();
+        let added = unmodulated
+            .filter(|_| add)
+            .map(|param| Modulator::new(param));
+        drop(params);
+        if let Some(i) = removed {
+            self.modulators.remove(i);
+        }
+        Modulation {
+            modulators: restore,
+        }
+        .restore(controller);
+        self.modulators.extend(added);
+    }
+}
@@ -0,0 +1,384 @@
+use crate::{
+    controller::{new_controller, Controller},
+    egui_components::enabled_number::EnabledNumber,
+    RustGPUShader,
+};
+use egui_winit::winit::dpi::PhysicalSize;
+use std::{borrow::Cow, fmt::Display, ops::RangeInclusive, str::FromStr};
+use strum::IntoEnumIterator;
+
+/// A named, mutable view of a single controller field.
+pub struct Param<'a> {
+    pub name: Cow<'static, str>,
+    pub value: ParamValue<'a>,
+}
+
+pub enum ParamValue<'a> {
+    F32(&'a mut f32, Option<RangeInclusive<f32>>),
+    U32(&'a mut u32, RangeInclusive<u32>),
+    I32(&'a mut i32, RangeInclusive<i32>),
+    Bool(&'a mut bool),
+    /// A number that can be switched off, written as `off` when disabled.
+    Enabled(&'a mut EnabledNumber<f32>, RangeInclusive<f32>),
+    Enum(&'a mut dyn EnumParam),
+    /// A value with a text form of its own, e.g. a whole scene.
+    Text(&'a mut dyn TextParam),
+}
+
+/// Enums that can be selected by their display name.
+pub trait EnumParam {
+    fn name(&self) -> String;
+    fn names(&self) -> Vec<String>;
+    fn select(&mut self, name: &str) -> bool;
+}
+
+/// Values written as a single line of text.
+pub trait TextParam {
+    fn to_text(&self) -> String;
+    fn set_text(&mut self, s: &str) -> Result<(), String>;
+}
+
+impl<T: IntoEnumIterator + Display> EnumParam for T {
+    fn name(&self) -> String {
+        self.to_string()
+    }
+
+    fn names(&self) -> Vec<String> {
+        T::iter().map(|x| x.to_string()).collect()
+    }
+
+    fn select(&mut self, name: &str) -> bool {
+        if let Some(x) = T::iter().find(|x| x.to_string().eq_ignore_ascii_case(name)) {
+            *self = x;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<'a> Param<'a> {
+    fn new(name: impl Into<Cow<'static, str>>, value: ParamValue<'a>) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+
+    pub fn f32(
+        name: impl Into<Cow<'static, str>>,
+        value: &'a mut f32,
+        range: RangeInclusive<f32>,
+    ) -> Self {
+        Self::new(name, ParamValue::F32(value, Some(range)))
+    }
+
+    /// An `f32` without bounds, e.g. a camera position.
+    pub fn unbounded(name: impl Into<Cow<'static, str>>, value: &'a mut f32) -> Self {
+        Self::new(name, ParamValue::F32(value, None))
+    }
+
+    pub fn u32(
+        name: impl Into<Cow<'static, str>>,
+        value: &'a mut u32,
+        range: RangeInclusive<u32>,
+    ) -> Self {
+        Self::new(name, ParamValue::U32(value, range))
+    }
+
+    pub fn i32(
+        name: impl Into<Cow<'static, str>>,
+        value: &'a mut i32,
+        range: RangeInclusive<i32>,
+    ) -> Self {
+        Self::new(name, ParamValue::I32(value, range))
+    }
+
+    pub fn bool(name: impl Into<Cow<'static, str>>, value: &'a mut bool) -> Self {
+        Self::new(name, ParamValue::Bool(value))
+    }
+
+    pub fn enabled(
+        name: impl Into<Cow<'static, str>>,
+        value: &'a mut EnabledNumber<f32>,
+        range: RangeInclusive<f32>,
+    ) -> Self {
+        Self::new(name, ParamValue::Enabled(value, range))
+    }
+
+    pub fn choice(name: impl Into<Cow<'static, str>>, value: &'a mut dyn EnumParam) -> Self {
+        Self::new(name, ParamValue::Enum(value))
+    }
+
+    pub fn text(name: impl Into<Cow<'static, str>>, value: &'a mut dyn TextParam) -> Self {
+        Self::new(name, ParamValue::Text(value))
+    }
+
+    pub fn value_string(&self) -> String {
+        match &self.value {
+            ParamValue::F32(x, _) => x.to_string(),
+            ParamValue::U32(x, _) => x.to_string(),
+            ParamValue::I32(x, _) => x.to_string(),
+            ParamValue::Bool(x) => x.to_string(),
+            ParamValue::Enabled(x, _) => {
+                if x.enabled {
+                    x.value.to_string()
+                } else {
+                    "off".to_string()
+                }
+            }
+            ParamValue::Enum(x) => x.name(),
+            ParamValue::Text(x) => x.to_text(),
+        }
+    }
+
+    /// The accepted values, e.g. `2..=200` or `Real|Complex`.
+    pub fn domain(&self) -> String {
+        match &self.value {
+            ParamValue::F32(_, Some(range)) => format!("{range:?}"),
+            ParamValue::F32(_, None) => "any finite number".to_string(),
+            ParamValue::U32(_, range) => format!("{range:?}"),
+            ParamValue::I32(_, range) => format!("{range:?}"),
+            ParamValue::Bool(_) => "true|false".to_string(),
+            ParamValue::Enabled(_, range) => format!("{range:?} or off"),
+            ParamValue::Enum(x) => x.names().join("|"),
+            ParamValue::Text(_) => "text".to_string(),
+        }
+    }
+
+    /// The value of a numeric parameter, `None` for booleans, enums, text and
+    /// disabled numbers.
+    pub fn number(&self) -> Option<f32> {
+        match &self.value {
+            ParamValue::F32(x, _) => Some(**x),
+            ParamValue::U32(x, _) => Some(**x as f32),
+            ParamValue::I32(x, _) => Some(**x as f32),
+            ParamValue::Enabled(x, _) => x.enabled.then_some(x.value),
+            ParamValue::Bool(_) | ParamValue::Enum(_) | ParamValue::Text(_) => None,
+        }
+    }
+
+    /// The bounds of a numeric parameter, `None` if it is unbounded or not numeric.
+    pub fn number_range(&self) -> Option<RangeInclusive<f32>> {
+        match &self.value {
+            ParamValue::F32(_, range) => range.clone(),
+            ParamValue::U32(_, range) => Some(*range.start() as f32..=*range.end() as f32),
+            ParamValue::I32(_, range) => Some(*range.start() as f32..=*range.end() as f32),
+            ParamValue::Enabled(_, range) => Some(range.clone()),
+            ParamValue::Bool(_) | ParamValue::Enum(_) | ParamValue::Text(_) => None,
+        }
+    }
+
+    /// Sets a numeric parameter, clamped to its bounds and rounded for integers.
+    /// Does nothing for other parameters.
+    pub fn set_number(&mut self, x: f32) {
+        let x = match self.number_range() {
+            Some(range) => x.clamp(*range.start(), *range.end()),
+            None => x,
+        };
+        match &mut self.value {
+            ParamValue::F32(value, _) => **value = x,
+            ParamValue::U32(value, _) => **value = x.round() as u32,
+            ParamValue::I32(value, _) => **value = x.round() as i32,
+            ParamValue::Enabled(value, _) => value.value = x,
+            ParamValue::Bool(_) | ParamValue::Enum(_) | ParamValue::Text(_) => {}
+        }
+    }
+
+    pub fn set(&mut self, s: &str) -> Result<(), String> {
+        let s = s.trim();
+        let error = format!(
+            "invalid value `{s}` for `{}`, expected {}",
+            self.name,
+            self.domain()
+        );
+        match &mut self.value {
+            ParamValue::F32(x, range) => **x = parse_f32(s, range.as_ref()).ok_or(error)?,
+            ParamValue::U32(x, range) => **x = parse_in(s, Some(range)).ok_or(error)?,
+            ParamValue::I32(x, range) => **x = parse_in(s, Some(range)).ok_or(error)?,
+            ParamValue::Bool(x) => **x = s.parse().map_err(|_| error)?,
+            ParamValue::Enabled(x, range) => {
+                if s == "off" {
+                    x.enabled = false;
+                } else {
+                    x.value = parse_f32(s, Some(range)).ok_or(error)?;
+                    x.enabled = true;
+                }
+            }
+            ParamValue::Enum(x) => {
+                if !x.select(s) {
+                    return Err(error);
+                }
+            }
+            ParamValue::Text(x) => {
+                x.set_text(s)
+                    .map_err(|e| format!("invalid value for `{}`: {e}", self.name))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a finite `f32`, as `NaN` and infinities would pass unbounded params
+/// and break the views depending on them.
+fn parse_f32(s: &str, range: Option<&RangeInclusive<f32>>) -> Option<f32> {
+    parse_in(s, range).filter(|x| x.is_finite())
+}
+
+fn parse_in<T: FromStr + PartialOrd>(s: &str, range: Option<&RangeInclusive<T>>) -> Option<T> {
+    s.parse()
+        .ok()
+        .filter(|x| range.is_none_or(|range| range.contains(x)))
+}
+
+/// Sets the parameter called `key`, failing with a list of the valid
+/// parameters if there is none.
+pub fn set(controller: &mut dyn Controller, key: &str, value: &str) -> Result<(), String> {
+    let mut params = controller.params();
+    if let Some(param) = params.iter_mut().find(|param| param.name == key) {
+        return param.set(value);
+    }
+    let valid = if params.is_empty() {
+        " none".to_string()
+    } else {
+        params
+            .iter()
+            .map(|param| format!("\n  {} ({})", param.name, param.domain()))
+            .collect()
+    };
+    Err(format!(
+        "unknown parameter `{key}`, valid parameters:{valid}"
+    ))
+}
+
+/// Checks `key=value` assignments against a new controller of `shader`, so
+/// that mistakes are reported before a window opens and the shader compiles.
+pub fn check(shader: RustGPUShader, assignments: &[(String, String)]) -> Result<(), String> {
+    let mut controller = new_controller(shader, PhysicalSize::new(1280, 720));
+    for (key, value) in assignments {
+        set(&mut *controller, key, value).map_err(|e| format!("{shader}: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Serializes the parameters as `key=value` lines.
+pub fn serialize(controller: &mut dyn Controller) -> String {
+    controller
+        .params()
+        .iter()
+        .map(|param| format!("{}={}\n", param.name, param.value_string()))
+        .collect()
+}
+
+/// Splits a `key=value` assignment.
+pub fn parse_assignment(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .ok_or_else(|| format!("expected `key=value`, got `{s}`"))
+}
+
+/// Applies serialized `key=value` lines in order, so that ranges depending on
+/// earlier parameters are up to date.
+pub fn deserialize(controller: &mut dyn Controller, s: &str) -> Result<(), String> {
+    for line in s.lines().filter(|line| !line.trim().is_empty()) {
+        let (key, value) = parse_assignment(line)?;
+        set(controller, &key, &value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: PhysicalSize<u32> = PhysicalSize::new(1280, 720);
+
+    /// Moves every parameter away from its default, in order, as the later
+    /// ones can depend on the earlier ones.
+    fn change_params(controller: &mut dyn Controller) {
+        let mut i = 0;
+        while let Some(mut param) = controller.params().into_iter().nth(i) {
+            let number = match param.number_range() {
+                Some(range) => Some(range.start() + 0.3 * (range.end() - range.start())),
+                None => param.number().map(|x| x + 0.25),
+            };
+            if let Some(x) = number {
+                param.set_number(x);
+            }
+            match &mut param.value {
+                ParamValue::Bool(x) => **x = !**x,
+                ParamValue::Enabled(x, _) => x.enabled = !x.enabled,
+                ParamValue::Enum(x) => {
+                    let names = x.names();
+                    x.select(names.last().unwrap());
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for shader in RustGPUShader::iter() {
+            let mut controller = new_controller(shader, SIZE);
+            let default = serialize(&mut *controller);
+            change_params(&mut *controller);
+            let changed = serialize(&mut *controller);
+            assert!(default.is_empty() || changed != default, "{shader}");
+
+            let mut restored = new_controller(shader, SIZE);
+            if let Err(e) = deserialize(&mut *restored, &changed) {
+                panic!("{shader}: {e}\n{changed}");
+            }
+            assert_eq!(serialize(&mut *restored), changed, "{shader}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let mut controller = new_controller(RustGPUShader::Mandelbrot, SIZE);
+        let controller = &mut *controller;
+
+        let error = set(controller, "iterations", "10").unwrap_err();
+        assert!(
+            error.starts_with("unknown parameter `iterations`"),
+            "{error}"
+        );
+        assert!(error.contains("\n  num_iterations (2..=200)"), "{error}");
+
+        for (key, value) in [
+            ("num_iterations", "201"),
+            ("num_iterations", "-1"),
+            ("num_iterations", "2.5"),
+            ("exponent", "0.5"),
+            ("camera_x", "NaN"),
+            ("camera_x", "inf"),
+            ("zoom", "-inf"),
+            ("palette", "NoSuchPalette"),
+        ] {
+            let error = set(controller, key, value).unwrap_err();
+            assert!(
+                error.starts_with(&format!("invalid value `{value}` for `{key}`")),
+                "{error}"
+            );
+        }
+        assert_eq!(serialize(controller), {
+            let mut fresh = new_controller(RustGPUShader::Mandelbrot, SIZE);
+            serialize(&mut *fresh)
+        });
+
+        assert_eq!(
+            parse_assignment(" num_iterations = 50 "),
+            Ok(("num_iterations".to_string(), "50".to_string()))
+        );
+        assert!(parse_assignment("num_iterations").is_err());
+        assert!(deserialize(controller, "num_iterations=50\n\nexponent").is_err());
+        assert!(check(RustGPUShader::Mandelbrot, &[("zoom".into(), "2".into())]).is_ok());
+        let error = check(RustGPUShader::Mandelbrot, &[("n".into(), "2".into())]).unwrap_err();
+        assert!(
+            error.starts_with("Mandelbrot: unknown parameter `n`"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,506 @@
+use crate::{
+    controller::Controller,
+    modulation::{Modulation, Modulator},
+    params, RustGPUShader,
+};
+use egui::{vec2, ColorImage, TextureHandle, TextureOptions, Ui};
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+/// Prefix of the modulator lines of a preset.
+const MODULATE: &str = "modulate ";
+/// Width of the saved thumbnails.
+const THUMBNAIL_WIDTH: u32 = 160;
+/// Width of the thumbnails in the browser.
+const THUMBNAIL_SIZE: f32 = 64.0;
+
+/// A named set of parameters, including the camera.
+struct Preset {
+    name: String,
+    /// `key=value` lines, as written by [`params::serialize`].
+    params: String,
+    /// One [`Modulator`] per line.
+    modulation: String,
+    thumbnail: Thumbnail,
+}
+
+enum Thumbnail {
+    None,
+    /// Loaded but not yet uploaded to egui.
+    Image(ColorImage),
+    Texture(TextureHandle),
+}
+
+impl Thumbnail {
+    fn load(path: &Path) -> Self {
+        match load_png(path) {
+            Some(image) => Thumbnail::Image(image),
+            None => Thumbnail::None,
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        if let Thumbnail::Image(image) = self {
+            let image = std::mem::take(image);
+            *self = Thumbnail::Texture(ui.ctx().load_texture(
+                "preset-thumbnail",
+                image,
+                TextureOptions::LINEAR,
+            ));
+        }
+        if let Thumbnail::Texture(texture) = self {
+            let [width, height] = texture.size().map(|x| x as f32);
+            ui.image((texture.id(), THUMBNAIL_SIZE * vec2(1.0, height / width)));
+        } else {
+            let size = THUMBNAIL_SIZE * vec2(1.0, 9.0 / 16.0);
+            ui.add_sized(size, egui::Label::new("-"));
+        }
+    }
+}
+
+enum Action {
+    Apply(usize),
+    StartRename(usize),
+    Rename,
+    Delete(usize),
+    Save,
+}
+
+/// The presets of a shader, stored in `presets/<shader>.txt` as `[name]`
+/// headers each followed by `key=value` lines and `modulate` lines. Thumbnails are PNGs in
+/// `presets/<shader>/` named after the preset and taken when it is saved.
+/// Applying a preset without one only takes it for the session, so that
+/// browsing the shipped presets doesn't write into the checkout.
+pub struct Presets {
+    shader: RustGPUShader,
+    presets: Vec<Preset>,
+    new_name: String,
+    renaming: Option<(usize, String)>,
+    /// Whether the name being edited should take the keyboard focus.
+    focus_renaming: bool,
+    error: Option<String>,
+    /// The preset whose thumbnail is taken from the next frame, and whether
+    /// it is written to disk.
+    pub pending_thumbnail: Option<(usize, bool)>,
+}
+
+impl Presets {
+    pub fn load(shader: RustGPUShader) -> Self {
+        let mut presets = Self {
+            shader,
+            presets: vec![],
+            new_name: String::new(),
+            renaming: None,
+            focus_renaming: false,
+            error: None,
+            pending_thumbnail: None,
+        };
+        let path = presets.path();
+        match fs::read_to_string(&path).map(|s| parse(&s)) {
+            Ok(Ok(parsed)) => {
+                presets.presets = parsed
+                    .into_iter()
+                    .map(|preset| Preset {
+                        thumbnail: Thumbnail::load(&presets.thumbnail_path(&preset.name)),
+                        ..preset
+                    })
+                    .collect();
+            }
+            Ok(Err(e)) => presets.error = Some(format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => presets.error = Some(format!("{}: {e}", path.display())),
+        }
+        presets
+    }
+
+    fn dir() -> PathBuf {
+        Path::new(option_env!("SHADERS_DIR").unwrap_or(env!("CARGO_MANIFEST_DIR")))
+            .join("..")
+            .join("presets")
+    }
+
+    fn path(&self) -> PathBuf {
+        Self::dir().join(format!("{}.txt", self.shader))
+    }
+
+    fn thumbnail_path(&self, name: &str) -> PathBuf {
+        Self::dir()
+            .join(self.shader.to_string())
+            .join(format!("{}.png", thumbnail_stem(name)))
+    }
+
+    fn write(&self) -> Result<(), String> {
+        let path = self.path();
+        let contents = self
+            .presets
+            .iter()
+            .map(|preset| {
+                let modulation = preset
+                    .modulation
+                    .lines()
+                    .map(|line| format!("{MODULATE}{line}\n"))
+                    .collect::<String>();
+                format!("[{}]\n{}{modulation}", preset.name, preset.params)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::create_dir_all(Self::dir())
+            .and_then(|()| fs::write(&path, contents))
+            .map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    fn apply(
+        &mut self,
+        index: usize,
+        controller: &mut dyn Controller,
+        modulation: &mut Modulation,
+    ) -> Result<(), String> {
+        let preset = &self.presets[index];
+        let modulators = preset
+            .modulation
+            .lines()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("{}: {e}", preset.name))?;
+        // The new modulators take the preset's values as their base on the next frame
+        modulation.replace(controller, modulators);
+        params::deserialize(controller, &preset.params)
+            .map_err(|e| format!("{}: {e}", preset.name))?;
+        if matches!(preset.thumbnail, Thumbnail::None) {
+            self.pending_thumbnail = Some((index, false));
+        }
+        Ok(())
+    }
+
+    /// Saves the current unmodulated parameters and the modulators under
+    /// `name`, replacing any preset with that name.
+    fn save(
+        &mut self,
+        name: &str,
+        controller: &mut dyn Controller,
+        modulation: &mut Modulation,
+    ) -> Result<(), String> {
+        validate_name(name)?;
+        modulation.restore(controller);
+        let params = params::serialize(controller);
+        let modulation = modulation.serialize();
+        let index = match self.presets.iter().position(|preset| preset.name == name) {
+            Some(index) => {
+                self.presets[index].params = params;
+                self.presets[index].modulation = modulation;
+                index
+            }
+            None => {
+                self.presets.push(Preset {
+                    name: name.to_string(),
+                    params,
+                    modulation,
+                    thumbnail: Thumbnail::None,
+                });
+                self.presets.len() - 1
+            }
+        };
+        self.pending_thumbnail = Some((index, true));
+        self.write()
+    }
+
+    fn rename(&mut self, index: usize, name: &str) -> Result<(), String> {
+        if self.presets[index].name == name {
+            return Ok(());
+        }
+        validate_name(name)?;
+        if self.presets.iter().any(|preset| preset.name == name) {
+            return Err(format!("a preset called `{name}` already exists"));
+        }
+        let from = self.thumbnail_path(&self.presets[index].name);
+        if from.exists() {
+            let to = self.thumbnail_path(name);
+            fs::rename(&from, &to).map_err(|e| format!("{}: {e}", from.display()))?;
+        }
+        self.presets[index].name = name.to_string();
+        self.write()
+    }
+
+    fn delete(&mut self, index: usize) -> Result<(), String> {
+        let preset = self.presets.remove(index);
+        self.pending_thumbnail = match self.pending_thumbnail {
+            Some((i, _)) if i == index => None,
+            Some((i, save)) if i > index => Some((i - 1, save)),
+            pending => pending,
+        };
+        let path = self.thumbnail_path(&preset.name);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        }
+        self.write()
+    }
+
+    /// Scales a frame down to the thumbnail of the pending preset, saving it
+    /// if the preset was just saved.
+    pub fn set_thumbnail(&mut self, width: u32, height: u32, rgba: &[u8]) {
+        let Some((index, save)) = self.pending_thumbnail.take() else {
+            return;
+        };
+        let image = downscale(width, height, rgba, THUMBNAIL_WIDTH);
+        let path = self.thumbnail_path(&self.presets[index].name);
+        if save && let Err(e) = save_png(&path, &image) {
+            self.error = Some(format!("{}: {e}", path.display()));
+        }
+        self.presets[index].thumbnail = Thumbnail::Image(image);
+    }
+
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        controller: &mut dyn Controller,
+        modulation: &mut Modulation,
+    ) {
+        let mut action = None;
+        for (i, preset) in self.presets.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                preset.thumbnail.ui(ui);
+                ui.vertical(|ui| {
+                    match &mut self.renaming {
+                        Some((index, name)) if *index == i => {
+                            let response = ui.text_edit_singleline(name);
+                            if response.lost_focus() {
+                                action = Some(Action::Rename);
+                            }
+                            if std::mem::take(&mut self.focus_renaming) {
+                                response.request_focus();
+                            }
+                        }
+                        _ => {
+                            ui.label(&preset.name);
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Apply").clicked() {
+                            action = Some(Action::Apply(i));
+                        }
+                        if ui.small_button("Rename").clicked() {
+                            action = Some(Action::StartRename(i));
+                        }
+                        if ui.small_button("Delete").clicked() {
+                            action = Some(Action::Delete(i));
+                        }
+                    });
+                });
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_name).desired_width(80.0));
+            if ui.button("Save").clicked() {
+                action = Some(Action::Save);
+            }
+        });
+        let result = match action {
+            Some(Action::Apply(i)) => self.apply(i, controller, modulation),
+            Some(Action::StartRename(i)) => {
+                self.renaming = Some((i, self.presets[i].name.clone()));
+                self.focus_renaming = true;
+                Ok(())
+            }
+            Some(Action::Rename) => match self.renaming.take() {
+                Some((i, name)) => self.rename(i, name.trim()),
+                None => Ok(()),
+            },
+            Some(Action::Delete(i)) => {
+                self.renaming = None;
+                self.delete(i)
+            }
+            Some(Action::Save) => {
+                let name = self.new_name.trim().to_string();
+                self.save(&name, controller, modulation)
+                    .map(|()| self.new_name.clear())
+            }
+            None => return self.error_ui(ui),
+        };
+        self.error = result.err();
+        self.error_ui(ui);
+    }
+
+    fn error_ui(&self, ui: &mut Ui) {
+        if let Some(e) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['[', ']', '\n']) {
+        Err(format!("`{name}` is not a valid preset name"))
+    } else {
+        Ok(())
+    }
+}
+
+/// A file stem that is distinct for every preset name, even on case-insensitive
+/// file systems: upper case letters are written as `-` followed by the lower
+/// case letter, and anything else but lower case letters and digits as `_`
+/// followed by the hex of its UTF-8 bytes.
+fn thumbnail_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.chars() {
+        if c.is_ascii_lowercase() || c.is_ascii_digit() {
+            stem.push(c);
+        } else if c.is_ascii_uppercase() {
+            stem.push('-');
+            stem.push(c.to_ascii_lowercase());
+        } else {
+            for byte in c.to_string().bytes() {
+                stem.push_str(&format!("_{byte:02x}"));
+            }
+        }
+    }
+    stem
+}
+
+/// Parses `[name]` headers each followed by `key=value` lines, and modulators
+/// on lines starting with `modulate `. Blank lines and lines starting with `#`
+/// are ignored.
+fn parse(s: &str) -> Result<Vec<Preset>, String> {
+    let mut presets: Vec<Preset> = vec![];
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            presets.push(Preset {
+                name: name.trim().to_string(),
+                params: String::new(),
+                modulation: String::new(),
+                thumbnail: Thumbnail::None,
+            });
+        } else if let Some(preset) = presets.last_mut() {
+            let error = |e| format!("line {}: {e}", i + 1);
+            if let Some(modulator) = line.strip_prefix(MODULATE) {
+                modulator.parse::<Modulator>().map_err(error)?;
+                preset.modulation.push_str(modulator);
+                preset.modulation.push('\n');
+            } else {
+                params::parse_assignment(line).map_err(error)?;
+                preset.params.push_str(line);
+                preset.params.push('\n');
+            }
+        } else {
+            return Err(format!("line {}: expected a `[name]` header", i + 1));
+        }
+    }
+    Ok(presets)
+}
+
+/// Averages blocks of pixels down to `max_width`, keeping the aspect ratio.
+fn downscale(width: u32, height: u32, rgba: &[u8], max_width: u32) -> ColorImage {
+    let new_width = max_width.min(width).max(1);
+    let new_height = (height * new_width / width).max(1);
+    let (width, height) = (width as usize, height as usize);
+    let (new_width, new_height) = (new_width as usize, new_height as usize);
+    let mut pixels = Vec::with_capacity(new_width * new_height * 4);
+    for y in 0..new_height {
+        let rows = y * height / new_height
+            ..((y + 1) * height / new_height).max(y * height / new_height + 1);
+        for x in 0..new_width {
+            let columns =
+                x * width / new_width..((x + 1) * width / new_width).max(x * width / new_width + 1);
+            let mut sum = [0u32; 4];
+            for row in rows.clone() {
+                for column in columns.clone() {
+                    let i = 4 * (row * width + column);
+                    for (s, &c) in sum.iter_mut().zip(&rgba[i..i + 4]) {
+                        *s += c as u32;
+                    }
+                }
+            }
+            let count = (rows.len() * columns.len()) as u32;
+            pixels.extend(sum.map(|s| (s / count) as u8));
+        }
+    }
+    ColorImage::from_rgba_unmultiplied([new_width, new_height], &pixels)
+}
+
+fn save_png(path: &Path, image: &ColorImage) -> Result<(), png::EncodingError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let [width, height] = image.size;
+    let writer = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    let rgba = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+        .collect::<Vec<_>>();
+    encoder.write_header()?.write_image_data(&rgba)
+}
+
+fn load_png(path: &Path) -> Option<ColorImage> {
+    let mut reader = png::Decoder::new(File::open(path).ok()?).read_info().ok()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).ok()?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return None;
+    }
+    let size = [info.width as usize, info.height as usize];
+    Some(ColorImage::from_rgba_unmultiplied(
+        size,
+        &buf[..info.buffer_size()],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::new_controller;
+    use egui_winit::winit::dpi::PhysicalSize;
+    use std::collections::HashSet;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn shipped_presets() {
+        for entry in fs::read_dir(Presets::dir()).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                let stem = path.file_stem().unwrap().to_str().unwrap();
+                assert!(stem.parse::<RustGPUShader>().is_ok(), "{}", path.display());
+            }
+        }
+        for shader in RustGPUShader::iter() {
+            let mut presets = Presets::load(shader);
+            assert_eq!(presets.error, None, "{shader}");
+            for i in 0..presets.presets.len() {
+                let mut controller = new_controller(shader, PhysicalSize::new(1280, 720));
+                let mut modulation = Modulation::default();
+                let result = presets.apply(i, &mut *controller, &mut modulation);
+                assert_eq!(result, Ok(()), "{shader}");
+                // Unknown parameters are rejected by `apply`, but not unknown modulated ones
+                let names = controller
+                    .params()
+                    .into_iter()
+                    .map(|param| param.name.into_owned())
+                    .collect::<HashSet<_>>();
+                for modulator in presets.presets[i].modulation.lines() {
+                    let modulator = modulator.parse::<Modulator>().unwrap();
+                    assert!(names.contains(&modulator.param), "{shader}: {modulator}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn thumbnail_stems() {
+        assert_eq!(thumbnail_stem("Multibrot 3"), "-multibrot_203");
+        assert_eq!(thumbnail_stem("Multibrot-3"), "-multibrot_2d3");
+        assert_eq!(thumbnail_stem("é"), "_c3_a9");
+        let names = ["a b", "a-b", "a_b", "a_20b", "A b", "-a b", "ab"];
+        let stems: HashSet<_> = names.iter().map(|name| thumbnail_stem(name)).collect();
+        assert_eq!(stems.len(), names.len());
+    }
+}
@@ -0,0 +1,52 @@
+use egui::Ui;
+use glam::Vec2;
+use std::fmt;
+
+/// The world position under the cursor and the values evaluated there.
+pub struct Probe {
+    pub position: Vec2,
+    pub values: Vec<(&'static str, String)>,
+}
+
+impl Probe {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            values: vec![],
+        }
+    }
+
+    pub fn with(mut self, label: &'static str, value: impl ToString) -> Self {
+        self.values.push((label, value.to_string()));
+        self
+    }
+
+    pub fn ui(&self, ui: &mut Ui) {
+        egui::Grid::new("probe").num_columns(2).show(ui, |ui| {
+            ui.label("x");
+            ui.monospace(self.position.x.to_string());
+            ui.end_row();
+            ui.label("y");
+            ui.monospace(self.position.y.to_string());
+            ui.end_row();
+            for (label, value) in &self.values {
+                ui.label(*label);
+                ui.monospace(value);
+                ui.end_row();
+            }
+        });
+        if ui.button("Copy").clicked() {
+            ui.output_mut(|output| output.copied_text = self.to_string());
+        }
+    }
+}
+
+impl fmt::Display for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "x={} y={}", self.position.x, self.position.y)?;
+        for (label, value) in &self.values {
+            write!(f, " {label}={value}")?;
+        }
+        Ok(())
+    }
+}
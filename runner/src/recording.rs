@@ -0,0 +1,414 @@
+use crate::{
+    controller::{new_controller, Controller, FrameContext, InputEvent, InputState, KeyInput},
+    RustGPUShader,
+};
+use bytemuck::Zeroable;
+use egui_winit::winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, MouseButton, MouseScrollDelta},
+    keyboard::{KeyCode, PhysicalKey},
+};
+use shared::spectrum::Spectrum;
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+use strum::IntoEnumIterator;
+
+/// A recorded event, either for the active controller or switching to another one.
+#[derive(Clone, Copy)]
+pub enum Event {
+    Input(InputEvent),
+    SwitchShader(RustGPUShader),
+}
+
+/// The events received before a frame, the clock time of that frame
+/// and the seconds since the previous one.
+pub struct Frame {
+    pub time: f32,
+    pub dt: f32,
+    pub events: Vec<Event>,
+}
+
+/// Input recorded with [`Recorder`], one line per frame or event:
+///
+/// ```text
+/// shader RayMarching
+/// size 1280 720
+/// frame 0.016 0.016
+/// key KeyW pressed
+/// button left released
+/// move 640.5 360
+/// ```
+pub struct Recording {
+    pub shader: RustGPUShader,
+    pub size: PhysicalSize<u32>,
+    pub frames: VecDeque<Frame>,
+}
+
+/// Writes the input of every frame to a file as it happens.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    events: Vec<Event>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, shader: RustGPUShader, size: PhysicalSize<u32>) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_header(&mut writer, shader, size)?;
+        Ok(Self {
+            writer,
+            events: vec![],
+        })
+    }
+
+    pub fn event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Writes the events received since the previous frame, should be called
+    /// once per frame after the clock has advanced.
+    pub fn frame(&mut self, time: f32, dt: f32) -> io::Result<()> {
+        write_frame(&mut self.writer, time, dt, &self.events)?;
+        self.events.clear();
+        self.writer.flush()
+    }
+}
+
+/// Replays a recording without a window, feeding the events of every frame
+/// to the active controller and then updating it at the recorded time.
+pub struct Replay {
+    controllers: Vec<Box<dyn Controller>>,
+    active_shader: RustGPUShader,
+    size: PhysicalSize<u32>,
+    input_state: InputState,
+    frames: VecDeque<Frame>,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        let Recording {
+            shader,
+            size,
+            frames,
+        } = recording;
+        let mut controllers = RustGPUShader::iter()
+            .map(|shader| new_controller(shader, size))
+            .collect::<Vec<_>>();
+        controllers[shader as usize].on_activate();
+        Self {
+            controllers,
+            active_shader: shader,
+            size,
+            input_state: InputState::default(),
+            frames,
+        }
+    }
+
+    pub fn controller(&mut self) -> &mut dyn Controller {
+        &mut *self.controllers[self.active_shader as usize]
+    }
+
+    /// Replays the next frame, returning `false` once there are none left.
+    pub fn step(&mut self) -> bool {
+        let Some(frame) = self.frames.pop_front() else {
+            return false;
+        };
+        for event in frame.events {
+            match event {
+                Event::Input(event) => {
+                    if let InputEvent::Resize(size) = event {
+                        self.size = size;
+                    }
+                    self.input_state.apply(event);
+                    event.apply(self.controller());
+                }
+                Event::SwitchShader(shader) => self.switch_shader(shader),
+            }
+        }
+        let spectrum = Spectrum::zeroed();
+        let frame_context = FrameContext {
+            dt: frame.dt,
+            time: frame.time,
+            size: self.size,
+            scale_factor: 1.0,
+            input: &self.input_state,
+            spectrum: &spectrum,
+        };
+        let controller = &mut *self.controllers[self.active_shader as usize];
+        controller.update(&frame_context);
+        controller.prepare_buffers();
+        true
+    }
+
+    /// Replays all the remaining frames.
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+
+    fn switch_shader(&mut self, shader: RustGPUShader) {
+        if shader == self.active_shader {
+            return;
+        }
+        let controller = &mut *self.controllers[self.active_shader as usize];
+        self.input_state.release(controller);
+        controller.on_deactivate();
+        self.active_shader = shader;
+        let size = self.size;
+        self.controller().resize(size);
+        self.controller().on_activate();
+    }
+}
+
+fn write_header(
+    writer: &mut impl Write,
+    shader: RustGPUShader,
+    size: PhysicalSize<u32>,
+) -> io::Result<()> {
+    writeln!(writer, "shader {shader}")?;
+    writeln!(writer, "size {} {}", size.width, size.height)
+}
+
+fn write_frame(writer: &mut impl Write, time: f32, dt: f32, events: &[Event]) -> io::Result<()> {
+    writeln!(writer, "frame {time} {dt}")?;
+    for &event in events {
+        if let Some(line) = serialize(event) {
+            writeln!(writer, "{line}")?;
+        }
+    }
+    Ok(())
+}
+
+fn serialize(event: Event) -> Option<String> {
+    let event = match event {
+        Event::Input(event) => event,
+        Event::SwitchShader(shader) => return Some(format!("switch {shader}")),
+    };
+    Some(match event {
+        InputEvent::Keyboard(KeyInput {
+            physical_key: PhysicalKey::Code(code),
+            state,
+        }) => {
+            let (_, name) = KEY_CODES.iter().find(|(key_code, _)| *key_code == code)?;
+            format!("key {name} {}", state_name(state))
+        }
+        // Keys without a code can't be replayed, and are ignored by the controllers
+        InputEvent::Keyboard(_) => return None,
+        InputEvent::MouseInput(state, button) => {
+            let button = match button {
+                MouseButton::Left => "left".to_string(),
+                MouseButton::Right => "right".to_string(),
+                MouseButton::Middle => "middle".to_string(),
+                MouseButton::Back => "back".to_string(),
+                MouseButton::Forward => "forward".to_string(),
+                MouseButton::Other(n) => n.to_string(),
+            };
+            format!("button {button} {}", state_name(state))
+        }
+        InputEvent::MouseMove(position) => format!("move {} {}", position.x, position.y),
+        InputEvent::MouseDelta((x, y)) => format!("delta {x} {y}"),
+        InputEvent::MouseScroll(MouseScrollDelta::LineDelta(x, y)) => {
+            format!("scroll line {x} {y}")
+        }
+        InputEvent::MouseScroll(MouseScrollDelta::PixelDelta(delta)) => {
+            format!("scroll pixel {} {}", delta.x, delta.y)
+        }
+        InputEvent::Resize(size) => format!("resize {} {}", size.width, size.height),
+    })
+}
+
+fn state_name(state: ElementState) -> &'static str {
+    match state {
+        ElementState::Pressed => "pressed",
+        ElementState::Released => "released",
+    }
+}
+
+pub fn load(path: &Path) -> Result<Recording, String> {
+    let s = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    parse(&s).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn parse(s: &str) -> Result<Recording, String> {
+    let mut shader = None;
+    let mut size = None;
+    let mut frames = VecDeque::<Frame>::new();
+    for (i, line) in s.lines().enumerate() {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let Some((&kind, args)) = words.split_first() else {
+            continue;
+        };
+        let error = |message: &str| format!("line {}: {message}", i + 1);
+        let number = |j: usize| -> Result<f64, String> {
+            args.get(j)
+                .and_then(|arg| arg.parse().ok())
+                .ok_or_else(|| error("expected a number"))
+        };
+        let event = match kind {
+            "shader" => {
+                shader = Some(parse_shader(args.first()).map_err(|e| error(&e))?);
+                continue;
+            }
+            "size" => {
+                size = Some(PhysicalSize::new(number(0)? as u32, number(1)? as u32));
+                continue;
+            }
+            "frame" => {
+                frames.push_back(Frame {
+                    time: number(0)? as f32,
+                    dt: number(1)? as f32,
+                    events: vec![],
+                });
+                continue;
+            }
+            "key" => {
+                let (code, _) = args
+                    .first()
+                    .and_then(|name| KEY_CODES.iter().find(|(_, key_name)| key_name == name))
+                    .ok_or_else(|| error("unknown key"))?;
+                let physical_key = PhysicalKey::Code(*code);
+                let state = parse_state(args.get(1)).map_err(|e| error(&e))?;
+                Event::Input(InputEvent::Keyboard(KeyInput {
+                    physical_key,
+                    state,
+                }))
+            }
+            "button" => {
+                let button = match args.first() {
+                    Some(&"left") => MouseButton::Left,
+                    Some(&"right") => MouseButton::Right,
+                    Some(&"middle") => MouseButton::Middle,
+                    Some(&"back") => MouseButton::Back,
+                    Some(&"forward") => MouseButton::Forward,
+                    _ => MouseButton::Other(number(0)? as u16),
+                };
+                let state = parse_state(args.get(1)).map_err(|e| error(&e))?;
+                Event::Input(InputEvent::MouseInput(state, button))
+            }
+            "move" => Event::Input(InputEvent::MouseMove(PhysicalPosition::new(
+                number(0)?,
+                number(1)?,
+            ))),
+            "delta" => Event::Input(InputEvent::MouseDelta((number(0)?, number(1)?))),
+            "scroll" => Event::Input(InputEvent::MouseScroll(match args.first() {
+                Some(&"line") => MouseScrollDelta::LineDelta(number(1)? as f32, number(2)? as f32),
+                Some(&"pixel") => {
+                    MouseScrollDelta::PixelDelta(PhysicalPosition::new(number(1)?, number(2)?))
+                }
+                _ => return Err(error("expected `line` or `pixel`")),
+            })),
+            "resize" => Event::Input(InputEvent::Resize(PhysicalSize::new(
+                number(0)? as u32,
+                number(1)? as u32,
+            ))),
+            "switch" => Event::SwitchShader(parse_shader(args.first()).map_err(|e| error(&e))?),
+            _ => return Err(error(&format!("unknown entry `{kind}`"))),
+        };
+        frames
+            .back_mut()
+            .ok_or_else(|| error("event before the first frame"))?
+            .events
+            .push(event);
+    }
+    Ok(Recording {
+        shader: shader.ok_or("missing `shader`")?,
+        size: size.ok_or("missing `size`")?,
+        frames,
+    })
+}
+
+fn parse_shader(name: Option<&&str>) -> Result<RustGPUShader, String> {
+    let name = name.ok_or("expected a shader")?;
+    name.parse().map_err(|_| format!("unknown shader `{name}`"))
+}
+
+fn parse_state(name: Option<&&str>) -> Result<ElementState, String> {
+    match name {
+        Some(&"pressed") => Ok(ElementState::Pressed),
+        Some(&"released") => Ok(ElementState::Released),
+        _ => Err("expected `pressed` or `released`".to_string()),
+    }
+}
+
+macro_rules! key_codes {
+    ($($name:ident)*) => {
+        /// Key codes and the names they are recorded with, the same on every platform.
+        const KEY_CODES: &[(KeyCode, &str)] = &[$((KeyCode::$name, stringify!($name))),*];
+    };
+}
+
+key_codes! {
+    Backquote Backslash BracketLeft BracketRight Comma Digit0 Digit1 Digit2 Digit3 Digit4 Digit5
+    Digit6 Digit7 Digit8 Digit9 Equal IntlBackslash IntlRo IntlYen KeyA KeyB KeyC KeyD KeyE KeyF
+    KeyG KeyH KeyI KeyJ KeyK KeyL KeyM KeyN KeyO KeyP KeyQ KeyR KeyS KeyT KeyU KeyV KeyW KeyX KeyY
+    KeyZ Minus Period Quote Semicolon Slash AltLeft AltRight Backspace CapsLock ContextMenu
+    ControlLeft ControlRight Enter SuperLeft SuperRight ShiftLeft ShiftRight Space Tab Convert
+    KanaMode Lang1 Lang2 Lang3 Lang4 Lang5 NonConvert Delete End Help Home Insert PageDown PageUp
+    ArrowDown ArrowLeft ArrowRight ArrowUp NumLock Numpad0 Numpad1 Numpad2 Numpad3 Numpad4 Numpad5
+    Numpad6 Numpad7 Numpad8 Numpad9 NumpadAdd NumpadBackspace NumpadClear NumpadClearEntry
+    NumpadComma NumpadDecimal NumpadDivide NumpadEnter NumpadEqual NumpadHash NumpadMemoryAdd
+    NumpadMemoryClear NumpadMemoryRecall NumpadMemoryStore NumpadMemorySubtract NumpadMultiply
+    NumpadParenLeft NumpadParenRight NumpadStar NumpadSubtract Escape Fn FnLock PrintScreen
+    ScrollLock Pause BrowserBack BrowserFavorites BrowserForward BrowserHome BrowserRefresh
+    BrowserSearch BrowserStop Eject LaunchApp1 LaunchApp2 LaunchMail MediaPlayPause MediaSelect
+    MediaStop MediaTrackNext MediaTrackPrevious Power Sleep AudioVolumeDown AudioVolumeMute
+    AudioVolumeUp WakeUp Meta Hyper Turbo Abort Resume Suspend Again Copy Cut Find Open Paste Props
+    Select Undo Hiragana Katakana F1 F2 F3 F4 F5 F6 F7 F8 F9 F10 F11 F12 F13 F14 F15 F16 F17 F18 F19
+    F20 F21 F22 F23 F24 F25 F26 F27 F28 F29 F30 F31 F32 F33 F34 F35
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::vec2;
+
+    const RECORDING: &str = "\
+shader Mandelbrot
+size 200 100
+frame 0 0
+move 100 100
+scroll line 0 -1
+button left pressed
+frame 0.016 0.016
+move 130 90
+button left released
+key KeyW pressed
+key KeyW released
+frame 0.032 0.016
+switch SdfBuilder
+move 150 50
+";
+
+    #[test]
+    fn round_trip() {
+        let recording = parse(RECORDING).unwrap();
+        let mut written = vec![];
+        write_header(&mut written, recording.shader, recording.size).unwrap();
+        for frame in &recording.frames {
+            write_frame(&mut written, frame.time, frame.dt, &frame.events).unwrap();
+        }
+        assert_eq!(String::from_utf8(written).unwrap(), RECORDING);
+
+        assert!(
+            parse("shader Mandelbrot\nsize 200 100\nframe 0 0\nkey NoSuchKey pressed").is_err()
+        );
+        assert!(parse("shader Mandelbrot\nsize 200 100\nmove 1 2").is_err());
+    }
+
+    #[test]
+    fn replay() {
+        let mut replay = Replay::new(parse(RECORDING).unwrap());
+        assert!(replay.step() && replay.step());
+        let params = crate::params::serialize(replay.controller());
+        for param in ["camera_x=-30\n", "camera_y=10\n", "zoom=1.1\n"] {
+            assert!(params.contains(param), "{param} not in {params}");
+        }
+
+        assert!(replay.step());
+        let probe = replay.controller().probe().unwrap();
+        assert_eq!(probe.position, vec2(0.5, 0.0));
+        assert!(!replay.step());
+    }
+}
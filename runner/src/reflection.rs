@@ -0,0 +1,609 @@
+//! Just enough SPIR-V reflection to check that a shader's push constants and
+//! bindings match what the runner creates from its controller.
+
+use crate::controller::{BindGroupBufferType, BufferData, SSBO};
+use std::collections::{HashMap, HashSet};
+
+const MAGIC: u32 = 0x0723_0203;
+const HEADER_WORDS: usize = 5;
+
+mod op {
+    pub const ENTRY_POINT: u32 = 15;
+    pub const TYPE_INT: u32 = 21;
+    pub const TYPE_FLOAT: u32 = 22;
+    pub const TYPE_VECTOR: u32 = 23;
+    pub const TYPE_MATRIX: u32 = 24;
+    pub const TYPE_ARRAY: u32 = 28;
+    pub const TYPE_RUNTIME_ARRAY: u32 = 29;
+    pub const TYPE_STRUCT: u32 = 30;
+    pub const TYPE_POINTER: u32 = 32;
+    pub const CONSTANT: u32 = 43;
+    pub const FUNCTION: u32 = 54;
+    pub const FUNCTION_END: u32 = 56;
+    pub const FUNCTION_CALL: u32 = 57;
+    pub const VARIABLE: u32 = 59;
+    pub const LOAD: u32 = 61;
+    pub const STORE: u32 = 62;
+    pub const COPY_MEMORY: u32 = 63;
+    pub const ACCESS_CHAIN: u32 = 65;
+    pub const IN_BOUNDS_ACCESS_CHAIN: u32 = 66;
+    pub const PTR_ACCESS_CHAIN: u32 = 67;
+    pub const ARRAY_LENGTH: u32 = 68;
+    pub const DECORATE: u32 = 71;
+    pub const MEMBER_DECORATE: u32 = 72;
+}
+
+mod decoration {
+    pub const ARRAY_STRIDE: u32 = 6;
+    pub const NON_WRITABLE: u32 = 24;
+    pub const BINDING: u32 = 33;
+    pub const DESCRIPTOR_SET: u32 = 34;
+    pub const OFFSET: u32 = 35;
+}
+
+mod storage_class {
+    pub const UNIFORM: u32 = 2;
+    pub const PUSH_CONSTANT: u32 = 9;
+    pub const STORAGE_BUFFER: u32 = 12;
+}
+
+const EXECUTION_MODEL_VERTEX: u32 = 0;
+
+#[derive(Clone, Copy)]
+pub enum BindingKind {
+    Uniform,
+    Storage { read_only: bool },
+}
+
+/// A buffer declared by the shader.
+pub struct Binding {
+    pub set: u32,
+    pub binding: u32,
+    pub kind: BindingKind,
+    /// Size of the buffer without a trailing runtime array.
+    pub min_size: u32,
+    /// Whether a vertex entry point uses the buffer.
+    pub in_vertex: bool,
+}
+
+/// The push constant block and buffers used by the entry points of a shader.
+#[derive(Default)]
+pub struct Reflection {
+    pub push_constant_size: Option<u32>,
+    pub bindings: Vec<Binding>,
+}
+
+impl Reflection {
+    /// Adds the push constants and buffers used by the entry points of a SPIR-V module.
+    pub fn add_module(&mut self, words: &[u32]) -> Result<(), String> {
+        let module = Module::parse(words)?;
+        for (variable, vertex) in module.used_variables() {
+            let Some(&(storage_class, pointee)) = module.variables.get(&variable) else {
+                continue;
+            };
+            match storage_class {
+                storage_class::PUSH_CONSTANT => {
+                    let size = module.size_of(pointee);
+                    self.push_constant_size = Some(self.push_constant_size.unwrap_or(0).max(size));
+                }
+                storage_class::UNIFORM | storage_class::STORAGE_BUFFER => {
+                    let decorations = module.decorations.get(&variable);
+                    let decoration = |kind| decorations.and_then(|d| d.get(&kind)).copied();
+                    let read_only = decoration(decoration::NON_WRITABLE).is_some()
+                        || module.all_members_non_writable(pointee);
+                    let kind = if storage_class == storage_class::UNIFORM {
+                        BindingKind::Uniform
+                    } else {
+                        BindingKind::Storage { read_only }
+                    };
+                    let set = decoration(decoration::DESCRIPTOR_SET).unwrap_or(0);
+                    let binding = decoration(decoration::BINDING).unwrap_or(0);
+                    let min_size = module.size_of(pointee);
+                    match self
+                        .bindings
+                        .iter_mut()
+                        .find(|b| b.set == set && b.binding == binding)
+                    {
+                        Some(existing) => {
+                            existing.in_vertex |= vertex;
+                            existing.min_size = existing.min_size.max(min_size);
+                        }
+                        None => self.bindings.push(Binding {
+                            set,
+                            binding,
+                            kind,
+                            min_size,
+                            in_vertex: vertex,
+                        }),
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.bindings.sort_by_key(|b| (b.set, b.binding));
+        Ok(())
+    }
+
+    /// Checks the reflected layout against the push constants and buffers of a controller.
+    pub fn validate(
+        &self,
+        push_constants_size: usize,
+        buffer_data: &BufferData,
+    ) -> Result<(), String> {
+        if let Some(size) = self.push_constant_size
+            && size as usize != push_constants_size
+        {
+            return Err(format!(
+                "the shader declares {size} bytes of push constants, \
+                 but the controller's `ShaderConstants` are {push_constants_size} bytes"
+            ));
+        }
+        let buffers = &buffer_data.bind_group_buffers;
+        for binding in &self.bindings {
+            let Binding {
+                set,
+                binding: index,
+                ..
+            } = *binding;
+            let Some(buffer) = buffers.get(set as usize) else {
+                return Err(format!(
+                    "the shader uses descriptor set {set}, but the controller only has {} bind group buffers",
+                    buffers.len()
+                ));
+            };
+            if index != 0 {
+                return Err(format!(
+                    "the shader uses binding {index} in descriptor set {set}, \
+                     but the runner only creates binding 0 in each set"
+                ));
+            }
+            if binding.in_vertex {
+                return Err(format!(
+                    "the vertex shader uses descriptor set {set}, \
+                     but bind groups are only visible to the fragment shader"
+                ));
+            }
+            let (data, name) = match (buffer, binding.kind) {
+                (BindGroupBufferType::Uniform(uniform), BindingKind::Uniform) => {
+                    (uniform.data, "uniform")
+                }
+                (
+                    BindGroupBufferType::SSBO(SSBO {
+                        data, read_only, ..
+                    }),
+                    BindingKind::Storage {
+                        read_only: shader_read_only,
+                    },
+                ) => {
+                    if *read_only && !shader_read_only {
+                        return Err(format!(
+                            "the shader writes to the storage buffer in descriptor set {set}, \
+                             but the controller declares it read only"
+                        ));
+                    }
+                    (*data, "storage buffer")
+                }
+                (BindGroupBufferType::Uniform(_), BindingKind::Storage { .. }) => {
+                    return Err(format!(
+                        "the shader declares a storage buffer in descriptor set {set}, \
+                         but the controller provides a uniform buffer"
+                    ));
+                }
+                (BindGroupBufferType::SSBO(_), BindingKind::Uniform) => {
+                    return Err(format!(
+                        "the shader declares a uniform buffer in descriptor set {set}, \
+                         but the controller provides a storage buffer"
+                    ));
+                }
+            };
+            if data.len() < binding.min_size as usize {
+                return Err(format!(
+                    "the shader needs at least {} bytes in the {name} in descriptor set {set}, \
+                     but the controller provides {}",
+                    binding.min_size,
+                    data.len()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Type {
+    Scalar(u32),
+    Composite { element: u32, count: u32 },
+    Array { element: u32, length: u32 },
+    RuntimeArray,
+    Struct(Vec<u32>),
+    Other,
+}
+
+#[derive(Default)]
+struct Module {
+    entry_points: Vec<(u32, u32)>,
+    types: HashMap<u32, Type>,
+    constants: HashMap<u32, u32>,
+    /// Storage class and pointee type of every global variable.
+    variables: HashMap<u32, (u32, u32)>,
+    pointers: HashMap<u32, u32>,
+    decorations: HashMap<u32, HashMap<u32, u32>>,
+    member_decorations: HashMap<(u32, u32), HashMap<u32, u32>>,
+    /// Global variables and functions referenced by every function.
+    references: HashMap<u32, Vec<u32>>,
+}
+
+impl Module {
+    fn parse(words: &[u32]) -> Result<Self, String> {
+        if words.len() < HEADER_WORDS || words[0] != MAGIC {
+            return Err("not a SPIR-V module".to_string());
+        }
+        let mut module = Self::default();
+        let mut function = None;
+        let mut rest = &words[HEADER_WORDS..];
+        while let Some(&first) = rest.first() {
+            let (count, opcode) = ((first >> 16) as usize, first & 0xffff);
+            if count == 0 || count > rest.len() {
+                return Err("truncated SPIR-V instruction".to_string());
+            }
+            let operands = &rest[1..count];
+            rest = &rest[count..];
+            let operand = |i: usize| operands.get(i).copied().unwrap_or(0);
+            match opcode {
+                op::ENTRY_POINT => module.entry_points.push((operand(0), operand(1))),
+                op::TYPE_INT | op::TYPE_FLOAT => {
+                    module
+                        .types
+                        .insert(operand(0), Type::Scalar(operand(1) / 8));
+                }
+                op::TYPE_VECTOR | op::TYPE_MATRIX => {
+                    let (element, count) = (operand(1), operand(2));
+                    module
+                        .types
+                        .insert(operand(0), Type::Composite { element, count });
+                }
+                op::TYPE_ARRAY => {
+                    let (element, length) = (operand(1), operand(2));
+                    module
+                        .types
+                        .insert(operand(0), Type::Array { element, length });
+                }
+                op::TYPE_RUNTIME_ARRAY => {
+                    module.types.insert(operand(0), Type::RuntimeArray);
+                }
+                op::TYPE_STRUCT => {
+                    module
+                        .types
+                        .insert(operand(0), Type::Struct(operands[1..].to_vec()));
+                }
+                op::TYPE_POINTER => {
+                    module.pointers.insert(operand(0), operand(2));
+                    module.types.insert(operand(0), Type::Other);
+                }
+                op::CONSTANT => {
+                    module.constants.insert(operand(1), operand(2));
+                }
+                op::VARIABLE if function.is_none() => {
+                    let pointee = module.pointers.get(&operand(0)).copied().unwrap_or(0);
+                    module.variables.insert(operand(1), (operand(2), pointee));
+                }
+                op::DECORATE => {
+                    let entry = module.decorations.entry(operand(0)).or_default();
+                    entry.insert(operand(1), operand(2));
+                }
+                op::MEMBER_DECORATE => {
+                    let entry = module
+                        .member_decorations
+                        .entry((operand(0), operand(1)))
+                        .or_default();
+                    entry.insert(operand(2), operand(3));
+                }
+                op::FUNCTION => {
+                    function = Some(operand(1));
+                    module.references.entry(operand(1)).or_default();
+                }
+                op::FUNCTION_END => function = None,
+                _ => {
+                    if let Some(function) = function {
+                        // Operands that can refer to a global variable or a function
+                        let referenced: &[u32] = match opcode {
+                            op::LOAD
+                            | op::ACCESS_CHAIN
+                            | op::IN_BOUNDS_ACCESS_CHAIN
+                            | op::PTR_ACCESS_CHAIN
+                            | op::ARRAY_LENGTH => &operands[2..3.min(operands.len())],
+                            op::STORE => &operands[..1],
+                            op::COPY_MEMORY => &operands[..2.min(operands.len())],
+                            op::FUNCTION_CALL => operands.get(2..).unwrap_or(&[]),
+                            _ => &[],
+                        };
+                        let references = module.references.entry(function).or_default();
+                        references.extend_from_slice(referenced);
+                    }
+                }
+            }
+        }
+        Ok(module)
+    }
+
+    /// The global variables reachable from each entry point, and whether
+    /// a vertex entry point reaches them.
+    fn used_variables(&self) -> HashMap<u32, bool> {
+        let mut used = HashMap::new();
+        for &(execution_model, entry) in &self.entry_points {
+            let vertex = execution_model == EXECUTION_MODEL_VERTEX;
+            let mut visited = HashSet::new();
+            let mut stack = vec![entry];
+            while let Some(id) = stack.pop() {
+                if !visited.insert(id) {
+                    continue;
+                }
+                if self.variables.contains_key(&id) {
+                    *used.entry(id).or_insert(false) |= vertex;
+                }
+                if let Some(references) = self.references.get(&id) {
+                    stack.extend(references);
+                }
+            }
+        }
+        used
+    }
+
+    /// Size of a type in bytes, not counting runtime arrays.
+    fn size_of(&self, id: u32) -> u32 {
+        match self.types.get(&id) {
+            Some(Type::Scalar(size)) => *size,
+            Some(Type::Composite { element, count }) => count * self.size_of(*element),
+            Some(Type::Array { element, length }) => {
+                let length = self.constants.get(length).copied().unwrap_or(0);
+                let stride = self
+                    .decorations
+                    .get(&id)
+                    .and_then(|d| d.get(&decoration::ARRAY_STRIDE))
+                    .copied()
+                    .unwrap_or_else(|| self.size_of(*element));
+                length * stride
+            }
+            Some(Type::Struct(members)) => {
+                let mut end = 0;
+                for (i, &member) in members.iter().enumerate() {
+                    let offset = self
+                        .member_decorations
+                        .get(&(id, i as u32))
+                        .and_then(|d| d.get(&decoration::OFFSET))
+                        .copied()
+                        .unwrap_or(end);
+                    end = end.max(offset + self.size_of(member));
+                }
+                end
+            }
+            Some(Type::RuntimeArray | Type::Other) | None => 0,
+        }
+    }
+
+    fn all_members_non_writable(&self, id: u32) -> bool {
+        match self.types.get(&id) {
+            Some(Type::Struct(members)) => (0..members.len() as u32).all(|i| {
+                self.member_decorations
+                    .get(&(id, i))
+                    .is_some_and(|d| d.contains_key(&decoration::NON_WRITABLE))
+            }),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::Uniform;
+
+    const EXECUTION_MODEL_FRAGMENT: u32 = 4;
+
+    // Ids of the types shared by the test modules
+    const FLOAT: u32 = 1;
+    const VEC4: u32 = 2;
+    const UINT: u32 = 3;
+    const THREE: u32 = 4;
+    const VOID: u32 = 5;
+    const FUNCTION_TYPE: u32 = 6;
+
+    /// Assembles a module from instructions given as an opcode and its operands.
+    fn assemble(instructions: &[(u32, &[u32])]) -> Vec<u32> {
+        let mut words = vec![MAGIC, 0x0001_0300, 0, 100, 0];
+        let types: &[(u32, &[u32])] = &[
+            (op::TYPE_FLOAT, &[FLOAT, 32]),
+            (op::TYPE_VECTOR, &[VEC4, FLOAT, 4]),
+            (op::TYPE_INT, &[UINT, 32, 0]),
+            (op::CONSTANT, &[UINT, THREE, 3]),
+        ];
+        for (opcode, operands) in types.iter().chain(instructions) {
+            words.push(((operands.len() as u32 + 1) << 16) | opcode);
+            words.extend_from_slice(operands);
+        }
+        words
+    }
+
+    /// A function that loads from each of the variables.
+    fn function(id: u32, variables: &[u32]) -> Vec<(u32, Vec<u32>)> {
+        let mut instructions = vec![(op::FUNCTION, vec![VOID, id, 0, FUNCTION_TYPE])];
+        for (i, &variable) in variables.iter().enumerate() {
+            instructions.push((op::LOAD, vec![FLOAT, 1000 + id * 10 + i as u32, variable]));
+        }
+        instructions.push((op::FUNCTION_END, vec![]));
+        instructions
+    }
+
+    fn reflect(instructions: &[(u32, Vec<u32>)]) -> Reflection {
+        let instructions: Vec<_> = instructions
+            .iter()
+            .map(|(opcode, operands)| (*opcode, operands.as_slice()))
+            .collect();
+        let mut reflection = Reflection::default();
+        reflection.add_module(&assemble(&instructions)).unwrap();
+        reflection
+    }
+
+    #[test]
+    fn push_constant_size() {
+        let mut instructions = vec![
+            (op::ENTRY_POINT, vec![EXECUTION_MODEL_FRAGMENT, 20]),
+            // struct { float, vec4 } with the vec4 aligned to 16 bytes
+            (op::TYPE_STRUCT, vec![10, FLOAT, VEC4]),
+            (op::MEMBER_DECORATE, vec![10, 0, decoration::OFFSET, 0]),
+            (op::MEMBER_DECORATE, vec![10, 1, decoration::OFFSET, 16]),
+            (op::TYPE_POINTER, vec![11, storage_class::PUSH_CONSTANT, 10]),
+            (op::VARIABLE, vec![11, 12, storage_class::PUSH_CONSTANT]),
+        ];
+        instructions.extend(function(20, &[12]));
+        assert_eq!(reflect(&instructions).push_constant_size, Some(32));
+
+        // Without offsets the members are packed
+        instructions.retain(|(opcode, _)| *opcode != op::MEMBER_DECORATE);
+        assert_eq!(reflect(&instructions).push_constant_size, Some(20));
+
+        // An unused block is not reflected
+        instructions.retain(|(opcode, _)| *opcode != op::LOAD);
+        assert_eq!(reflect(&instructions).push_constant_size, None);
+    }
+
+    #[test]
+    fn array_stride() {
+        let mut instructions = vec![
+            (op::ENTRY_POINT, vec![EXECUTION_MODEL_FRAGMENT, 20]),
+            // struct { float[3], uint[] }
+            (op::TYPE_ARRAY, vec![10, FLOAT, THREE]),
+            (op::DECORATE, vec![10, decoration::ARRAY_STRIDE, 16]),
+            (op::TYPE_RUNTIME_ARRAY, vec![11, UINT]),
+            (op::TYPE_STRUCT, vec![12, 10, 11]),
+            (
+                op::TYPE_POINTER,
+                vec![13, storage_class::STORAGE_BUFFER, 12],
+            ),
+            (op::VARIABLE, vec![13, 14, storage_class::STORAGE_BUFFER]),
+        ];
+        instructions.extend(function(20, &[14]));
+        assert_eq!(reflect(&instructions).bindings[0].min_size, 48);
+
+        instructions.retain(|(opcode, _)| *opcode != op::DECORATE);
+        assert_eq!(reflect(&instructions).bindings[0].min_size, 12);
+    }
+
+    #[test]
+    fn bindings() {
+        let mut instructions = vec![(op::ENTRY_POINT, vec![EXECUTION_MODEL_FRAGMENT, 20])];
+        // One struct { vec4 } buffer per descriptor set
+        let buffers = [
+            (storage_class::UNIFORM, None),
+            (storage_class::STORAGE_BUFFER, None),
+            (storage_class::STORAGE_BUFFER, Some(op::DECORATE)),
+            (storage_class::STORAGE_BUFFER, Some(op::MEMBER_DECORATE)),
+        ];
+        let mut variables = vec![];
+        for (set, (class, non_writable)) in buffers.into_iter().enumerate() {
+            let (set, id) = (set as u32, 100 + set as u32 * 10);
+            instructions.extend([
+                (op::TYPE_STRUCT, vec![id, VEC4]),
+                (op::TYPE_POINTER, vec![id + 1, class, id]),
+                (op::VARIABLE, vec![id + 1, id + 2, class]),
+                (op::DECORATE, vec![id + 2, decoration::DESCRIPTOR_SET, set]),
+                (op::DECORATE, vec![id + 2, decoration::BINDING, 0]),
+            ]);
+            match non_writable {
+                Some(op::DECORATE) => {
+                    instructions.push((op::DECORATE, vec![id + 2, decoration::NON_WRITABLE]));
+                }
+                Some(_) => {
+                    instructions.push((op::MEMBER_DECORATE, vec![id, 0, decoration::NON_WRITABLE]))
+                }
+                None => {}
+            }
+            variables.push(id + 2);
+        }
+        // Declared in reverse so the bindings have to be sorted
+        variables.reverse();
+        instructions.extend(function(20, &variables));
+
+        let reflection = reflect(&instructions);
+        let kinds: Vec<_> = reflection
+            .bindings
+            .iter()
+            .map(|b| {
+                assert_eq!((b.binding, b.min_size, b.in_vertex), (0, 16, false));
+                (b.set, b.kind)
+            })
+            .collect();
+        assert!(matches!(
+            kinds[..],
+            [
+                (0, BindingKind::Uniform),
+                (1, BindingKind::Storage { read_only: false }),
+                (2, BindingKind::Storage { read_only: true }),
+                (3, BindingKind::Storage { read_only: true }),
+            ]
+        ));
+    }
+
+    #[test]
+    fn vertex_stage() {
+        let mut instructions = vec![
+            (op::ENTRY_POINT, vec![EXECUTION_MODEL_VERTEX, 20]),
+            (op::ENTRY_POINT, vec![EXECUTION_MODEL_FRAGMENT, 21]),
+            (op::TYPE_STRUCT, vec![10, VEC4]),
+            (op::TYPE_POINTER, vec![11, storage_class::UNIFORM, 10]),
+        ];
+        // Set 0 is used by the fragment shader, set 1 by both, and set 2 by neither
+        for set in 0..3 {
+            instructions.extend([
+                (op::VARIABLE, vec![11, 12 + set, storage_class::UNIFORM]),
+                (
+                    op::DECORATE,
+                    vec![12 + set, decoration::DESCRIPTOR_SET, set],
+                ),
+            ]);
+        }
+        // The vertex shader reaches set 1 through a function call
+        instructions.extend(function(20, &[]));
+        instructions.insert(
+            instructions.len() - 1,
+            (op::FUNCTION_CALL, vec![VOID, 30, 22]),
+        );
+        instructions.extend(function(21, &[12, 13]));
+        instructions.extend(function(22, &[13]));
+
+        let reflection = reflect(&instructions);
+        let stages: Vec<_> = reflection
+            .bindings
+            .iter()
+            .map(|b| (b.set, b.in_vertex))
+            .collect();
+        assert_eq!(stages, [(0, false), (1, true)]);
+
+        let data = [0; 16];
+        let buffer_data = BufferData {
+            bind_group_buffers: vec![
+                BindGroupBufferType::Uniform(Uniform { data: &data }),
+                BindGroupBufferType::Uniform(Uniform { data: &data }),
+            ],
+            ..Default::default()
+        };
+        let error = reflection.validate(0, &buffer_data).unwrap_err();
+        assert!(error.contains("vertex shader uses descriptor set 1"));
+    }
+
+    #[test]
+    fn invalid_module() {
+        let mut reflection = Reflection::default();
+        let words = assemble(&[(op::TYPE_STRUCT, &[10, FLOAT, VEC4])]);
+        assert!(reflection.add_module(&words).is_ok());
+
+        assert!(reflection.add_module(&words[..3]).is_err());
+        let mut wrong_magic = words.clone();
+        wrong_magic[0] = MAGIC.swap_bytes();
+        assert!(reflection.add_module(&wrong_magic).is_err());
+        // The last instruction is missing its final operand
+        assert!(reflection.add_module(&words[..words.len() - 1]).is_err());
+        let mut zero_length = words.clone();
+        zero_length.push(op::FUNCTION_END);
+        assert!(reflection.add_module(&zero_length).is_err());
+    }
+}
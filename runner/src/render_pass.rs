@@ -1,13 +1,19 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::inspector::Inspection;
 use crate::{
     context::GraphicsContext,
     controller::{BindGroupBufferType, BufferData, Controller, SSBO},
     model::Vertex,
     shader::CompiledShaderModules,
+    stereo::{Stereo, View},
     texture::Texture,
     ui::{Ui, UiState},
     Options,
 };
 use egui_winit::winit::window::Window;
+#[cfg(not(target_arch = "wasm32"))]
+use egui_winit::winit::dpi::PhysicalPosition;
+use std::borrow::Cow;
 use wgpu::{util::DeviceExt, BindGroupLayout, TextureView};
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -22,13 +28,21 @@ mod shaders {
     include!(concat!(env!("OUT_DIR"), "/entry_points.rs"));
 }
 
+/// Format of the offscreen target for HDR exports.
+#[cfg(not(target_arch = "wasm32"))]
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+
 pub struct RenderPass {
     render_pipeline: wgpu::RenderPipeline,
+    #[cfg(not(target_arch = "wasm32"))]
+    hdr_pipeline: wgpu::RenderPipeline,
     ui_renderer: egui_wgpu::Renderer,
     options: Options,
     vertex_buffer: Option<wgpu::Buffer>,
     index_buffer: Option<wgpu::Buffer>,
     bind_groups: Vec<wgpu::BindGroup>,
+    /// The buffers of `bind_groups`, kept for reading back writable storage buffers.
+    bind_group_buffers: Vec<wgpu::Buffer>,
 }
 
 impl RenderPass {
@@ -56,22 +70,34 @@ impl RenderPass {
             &ctx.device,
             &pipeline_layout,
             ctx.config.format,
-            compiled_shader_modules,
+            &compiled_shader_modules,
+            buffer_data,
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        let hdr_pipeline = create_pipeline(
+            &options,
+            &ctx.device,
+            &pipeline_layout,
+            HDR_FORMAT,
+            &compiled_shader_modules,
             buffer_data,
         );
         let vertex_buffer = maybe_create_vertex_buffer(ctx, buffer_data);
         let index_buffer = maybe_create_index_buffer(ctx, buffer_data);
-        let bind_groups = maybe_create_bind_groups(ctx, buffer_data);
+        let (bind_group_buffers, bind_groups) = maybe_create_bind_groups(ctx, buffer_data);
 
         let ui_renderer = egui_wgpu::Renderer::new(&ctx.device, ctx.config.format, None, 1);
 
         Self {
             render_pipeline,
+            #[cfg(not(target_arch = "wasm32"))]
+            hdr_pipeline,
             ui_renderer,
             options,
             vertex_buffer,
             index_buffer,
             bind_groups,
+            bind_group_buffers,
         }
     }
 
@@ -101,7 +127,13 @@ impl RenderPass {
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        self.render_shader(ctx, &output_view, controller, depth_texture);
+        self.render_shader(
+            ctx,
+            &output_view,
+            controller,
+            depth_texture,
+            &ui_state.stereo,
+        );
         self.render_ui(ctx, &output_view, window, ui, ui_state, controller);
 
         output.present();
@@ -115,13 +147,47 @@ impl RenderPass {
         output_view: &TextureView,
         controller: &dyn Controller,
         depth_texture: Option<&Texture>,
+        stereo: &Stereo,
+    ) {
+        self.write_per_frame_buffers(ctx, &controller.buffers());
+        let passes = eye_passes(ctx, controller, stereo);
+        let pipeline = &self.render_pipeline;
+        let height = ctx.config.height;
+        self.draw(ctx, pipeline, output_view, height, depth_texture, &passes);
+    }
+
+    /// Uploads the storage buffers the controller changes every frame.
+    fn write_per_frame_buffers(&self, ctx: &GraphicsContext, buffer_data: &BufferData) {
+        for (buffer, data) in self
+            .bind_group_buffers
+            .iter()
+            .zip(&buffer_data.bind_group_buffers)
+        {
+            if let BindGroupBufferType::SSBO(ssbo) = data
+                && ssbo.per_frame
+                && buffer.size() == ssbo.data.len() as u64
+            {
+                ctx.queue.write_buffer(buffer, 0, ssbo.data);
+            }
+        }
+    }
+
+    /// Draws the shader once per view, each with its own push constants.
+    fn draw(
+        &self,
+        ctx: &GraphicsContext,
+        pipeline: &wgpu::RenderPipeline,
+        output_view: &TextureView,
+        height: u32,
+        depth_texture: Option<&Texture>,
+        passes: &[(View, Cow<'_, [u8]>)],
     ) {
         let mut encoder = ctx
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                 label: Some("Shader Encoder"),
             });
-        {
+        for (i, (view, push_constants)) in passes.iter().enumerate() {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Shader Render Pass"),
                 occlusion_query_set: None,
@@ -130,11 +196,14 @@ impl RenderPass {
                     view: output_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(if self.index_buffer.is_some() {
-                            wgpu::Color::BLACK
+                        // Later eyes are composited onto the first one
+                        load: if i > 0 {
+                            wgpu::LoadOp::Load
+                        } else if self.index_buffer.is_some() {
+                            wgpu::LoadOp::Clear(wgpu::Color::BLACK)
                         } else {
-                            wgpu::Color::GREEN
-                        }),
+                            wgpu::LoadOp::Clear(wgpu::Color::GREEN)
+                        },
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -150,11 +219,20 @@ impl RenderPass {
                 }),
             });
 
-            rpass.set_pipeline(&self.render_pipeline);
+            rpass.set_pipeline(pipeline);
+            rpass.set_viewport(
+                view.eye.viewport_x,
+                0.0,
+                view.eye.viewport_width,
+                height as f32,
+                0.0,
+                1.0,
+            );
+            rpass.set_blend_constant(view.color_mask);
             rpass.set_push_constants(
                 wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                 0,
-                controller.push_constants(),
+                push_constants,
             );
             for (i, bind_group) in self.bind_groups.iter().enumerate() {
                 rpass.set_bind_group(i as u32, bind_group, &[]);
@@ -179,6 +257,219 @@ impl RenderPass {
         ctx.queue.submit(Some(encoder.finish()));
     }
 
+    /// Renders the shader into `target` and waits for the GPU to finish.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_offscreen(
+        &self,
+        ctx: &GraphicsContext,
+        target: &TextureView,
+        height: u32,
+        depth_texture: Option<&Texture>,
+        passes: &[(View, Cow<'_, [u8]>)],
+    ) {
+        let pipeline = &self.render_pipeline;
+        self.draw(ctx, pipeline, target, height, depth_texture, passes);
+        ctx.device.poll(wgpu::Maintain::Wait);
+    }
+
+    /// Renders the shader without the ui into an offscreen texture
+    /// and reads it back as RGBA8.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn screenshot(
+        &self,
+        ctx: &GraphicsContext,
+        controller: &dyn Controller,
+        depth_texture: Option<&Texture>,
+        stereo: &Stereo,
+    ) -> Result<Vec<u8>, String> {
+        let passes = eye_passes(ctx, controller, stereo);
+        let size = extent(ctx.config.width, ctx.config.height);
+        self.capture_rgba8(ctx, size, depth_texture, &passes)
+    }
+
+    /// Renders the shader like [`Self::screenshot`] and reads back the color of
+    /// `pixel`, along with the controller's debug values if it writes any.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn inspect(
+        &self,
+        ctx: &GraphicsContext,
+        controller: &dyn Controller,
+        depth_texture: Option<&Texture>,
+        stereo: &Stereo,
+        pixel: PhysicalPosition<u32>,
+    ) -> Result<Inspection, String> {
+        if pixel.x >= ctx.config.width || pixel.y >= ctx.config.height {
+            return Err(format!("Pixel {}, {} is outside the window", pixel.x, pixel.y));
+        }
+        let rgba = self.screenshot(ctx, controller, depth_texture, stereo)?;
+        let i = 4 * (pixel.y * ctx.config.width + pixel.x) as usize;
+        let values = match controller.debug_buffer() {
+            Some(index) => controller.debug_values(&self.read_buffer(ctx, index)?),
+            None => vec![],
+        };
+        Ok(Inspection {
+            pixel,
+            color: [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]],
+            values,
+        })
+    }
+
+    /// Copies the contents of a writable storage buffer back from the GPU.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_buffer(&self, ctx: &GraphicsContext, index: usize) -> Result<Vec<u8>, String> {
+        let source = self
+            .bind_group_buffers
+            .get(index)
+            .filter(|buffer| buffer.usage().contains(wgpu::BufferUsages::COPY_SRC))
+            .ok_or_else(|| format!("Bind group {index} is not a writable storage buffer"))?;
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: source.size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Encoder"),
+            });
+        encoder.copy_buffer_to_buffer(source, 0, &buffer, 0, source.size());
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let data = slice.get_mapped_range().to_vec();
+        Ok(data)
+    }
+
+    /// Renders a 2:1 equirectangular panorama around the camera as RGBA8.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn panorama(
+        &self,
+        ctx: &GraphicsContext,
+        controller: &dyn Controller,
+        width: u32,
+    ) -> Result<Vec<u8>, String> {
+        let push_constants = controller
+            .panorama_push_constants(width)
+            .ok_or("The shader does not support panoramas")?;
+        let passes = [(View::mono(width), Cow::Owned(push_constants))];
+        self.capture_rgba8(ctx, extent(width, width / 2), None, &passes)
+    }
+
+    /// Renders the shader into a float target without clamping and reads it
+    /// back as linear RGBA.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn hdr_screenshot(
+        &self,
+        ctx: &GraphicsContext,
+        controller: &dyn Controller,
+        depth_texture: Option<&Texture>,
+    ) -> Vec<f32> {
+        let width = ctx.config.width;
+        let push_constants = controller.eye_push_constants(shared::push_constants::Eye::mono(width));
+        let passes = [(View::mono(width), push_constants)];
+        let size = extent(width, ctx.config.height);
+        self.capture(ctx, HDR_FORMAT, size, depth_texture, &passes)
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn capture_rgba8(
+        &self,
+        ctx: &GraphicsContext,
+        size: wgpu::Extent3d,
+        depth_texture: Option<&Texture>,
+        passes: &[(View, Cow<'_, [u8]>)],
+    ) -> Result<Vec<u8>, String> {
+        let format = ctx.config.format;
+        let bgra = match format {
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            _ => return Err(format!("Unsupported surface format {format:?}")),
+        };
+        Ok(self
+            .capture(ctx, format, size, depth_texture, passes)
+            .chunks(4)
+            .flat_map(|pixel| {
+                if bgra {
+                    [pixel[2], pixel[1], pixel[0], pixel[3]]
+                } else {
+                    [pixel[0], pixel[1], pixel[2], pixel[3]]
+                }
+            })
+            .collect())
+    }
+
+    /// Draws into an offscreen texture of the given format, either the surface
+    /// format or [`HDR_FORMAT`], and returns its tightly packed rows.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn capture(
+        &self,
+        ctx: &GraphicsContext,
+        format: wgpu::TextureFormat,
+        size: wgpu::Extent3d,
+        depth_texture: Option<&Texture>,
+        passes: &[(View, Cow<'_, [u8]>)],
+    ) -> Vec<u8> {
+        let pipeline = if format == HDR_FORMAT {
+            &self.hdr_pipeline
+        } else {
+            &self.render_pipeline
+        };
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Screenshot Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.draw(ctx, pipeline, &view, size.height, depth_texture, passes);
+
+        let row_size = format.block_copy_size(None).unwrap() * size.width;
+        let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screenshot Buffer"),
+            size: (padded_row_size * size.height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Screenshot Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let data = slice.get_mapped_range();
+        data.chunks(padded_row_size as usize)
+            .flat_map(|row| &row[..row_size as usize])
+            .copied()
+            .collect()
+    }
+
     fn render_ui(
         &mut self,
         ctx: &GraphicsContext,
@@ -265,18 +556,58 @@ impl RenderPass {
             &ctx.device,
             &pipeline_layout,
             ctx.config.format,
-            new_module,
+            &new_module,
             buffer_data,
         );
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.hdr_pipeline = create_pipeline(
+                &self.options,
+                &ctx.device,
+                &pipeline_layout,
+                HDR_FORMAT,
+                &new_module,
+                buffer_data,
+            );
+        }
     }
 
     pub fn new_buffers(&mut self, ctx: &GraphicsContext, buffer_data: &BufferData) {
         self.vertex_buffer = maybe_create_vertex_buffer(ctx, buffer_data);
         self.index_buffer = maybe_create_index_buffer(ctx, buffer_data);
-        self.bind_groups = maybe_create_bind_groups(ctx, buffer_data);
+        (self.bind_group_buffers, self.bind_groups) = maybe_create_bind_groups(ctx, buffer_data);
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn extent(width: u32, height: u32) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    }
+}
+
+/// One pass per eye, or a single pass if stereo is off or unsupported.
+fn eye_passes<'a>(
+    ctx: &GraphicsContext,
+    controller: &'a dyn Controller,
+    stereo: &Stereo,
+) -> Vec<(View, Cow<'a, [u8]>)> {
+    let views = if controller.has_stereo() {
+        stereo.views(ctx.config.width)
+    } else {
+        vec![View::mono(ctx.config.width)]
+    };
+    views
+        .into_iter()
+        .map(|view| {
+            let push_constants = controller.eye_push_constants(view.eye);
+            (view, push_constants)
+        })
+        .collect()
+}
+
 fn maybe_create_vertex_buffer(
     ctx: &GraphicsContext,
     buffer_data: &BufferData,
@@ -308,7 +639,7 @@ fn maybe_create_index_buffer(
 fn maybe_create_bind_groups(
     ctx: &GraphicsContext,
     buffer_data: &BufferData,
-) -> Vec<wgpu::BindGroup> {
+) -> (Vec<wgpu::Buffer>, Vec<wgpu::BindGroup>) {
     buffer_data
         .bind_group_buffers
         .iter()
@@ -316,35 +647,41 @@ fn maybe_create_bind_groups(
         .enumerate()
         .map(|(i, (buffer, layout))| {
             let buffer = ctx.device.create_buffer_init(&match buffer {
-                BindGroupBufferType::SSBO(ssbo) => wgpu::util::BufferInitDescriptor {
-                    label: Some("Bind Group Buffer"),
-                    contents: ssbo.data,
-                    usage: wgpu::BufferUsages::STORAGE,
-                },
+                BindGroupBufferType::SSBO(ssbo) => {
+                    let mut usage = wgpu::BufferUsages::STORAGE;
+                    usage.set(wgpu::BufferUsages::COPY_SRC, !ssbo.read_only);
+                    usage.set(wgpu::BufferUsages::COPY_DST, ssbo.per_frame);
+                    wgpu::util::BufferInitDescriptor {
+                        label: Some("Bind Group Buffer"),
+                        contents: ssbo.data,
+                        usage,
+                    }
+                }
                 BindGroupBufferType::Uniform(uniform) => wgpu::util::BufferInitDescriptor {
                     label: Some("Bind Group Buffer"),
                     contents: uniform.data,
                     usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
                 },
             });
-            ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
                 layout: &layout,
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
                     resource: buffer.as_entire_binding(),
                 }],
                 label: Some(&format!("bind_group {}", i)),
-            })
+            });
+            (buffer, bind_group)
         })
-        .collect()
+        .unzip()
 }
 
 fn create_pipeline(
     options: &Options,
     device: &wgpu::Device,
     pipeline_layout: &wgpu::PipelineLayout,
-    surface_format: wgpu::TextureFormat,
-    compiled_shader_modules: CompiledShaderModules,
+    format: wgpu::TextureFormat,
+    compiled_shader_modules: &CompiledShaderModules,
     buffer_data: &BufferData,
 ) -> wgpu::RenderPipeline {
     // FIXME(eddyb) automate this decision by default.
@@ -422,8 +759,17 @@ fn create_pipeline(
             module: fs_module,
             entry_point: fs_entry_point,
             targets: &[Some(wgpu::ColorTargetState {
-                format: surface_format,
-                blend: None,
+                format,
+                // Only the channels in the blend constant are written, for anaglyph stereo.
+                // 32 bit float targets can't be blended, so they are always written fully.
+                blend: (format != wgpu::TextureFormat::Rgba32Float).then_some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::Constant,
+                        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent::REPLACE,
+                }),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
         }),
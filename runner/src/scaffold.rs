@@ -0,0 +1,223 @@
+use crate::{DemoKind, RustGPUShader};
+use std::{fs, path::Path};
+
+const CARGO_TOML: &str = include_str!("../templates/new-demo/Cargo.toml.tmpl");
+
+/// The shader, push constants and controller templates of a kind of demo.
+fn templates(kind: DemoKind) -> [&'static str; 3] {
+    match kind {
+        DemoKind::TwoD => [
+            include_str!("../templates/new-demo/2d/lib.rs.tmpl"),
+            include_str!("../templates/new-demo/2d/push_constants.rs.tmpl"),
+            include_str!("../templates/new-demo/2d/controller.rs.tmpl"),
+        ],
+        DemoKind::ThreeD => [
+            include_str!("../templates/new-demo/3d/lib.rs.tmpl"),
+            include_str!("../templates/new-demo/3d/push_constants.rs.tmpl"),
+            include_str!("../templates/new-demo/3d/controller.rs.tmpl"),
+        ],
+        DemoKind::Mesh => [
+            include_str!("../templates/new-demo/mesh/lib.rs.tmpl"),
+            include_str!("../templates/new-demo/mesh/push_constants.rs.tmpl"),
+            include_str!("../templates/new-demo/mesh/controller.rs.tmpl"),
+        ],
+    }
+}
+
+/// The forms of a demo name used by the crate, its modules and the shader enum.
+struct Names {
+    /// `my-demo`, the shader crate directory
+    dir: String,
+    /// `my_demo`, the crate, push constants and controller modules
+    module: String,
+    /// `MyDemo`, the `RustGPUShader` variant
+    variant: String,
+}
+
+impl Names {
+    fn new(name: &str) -> Result<Self, String> {
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(format!(
+                "`{name}` is not a valid demo name, use letters, digits, `-` and `_`"
+            ));
+        }
+        // Words are separated by `-`, `_` or a lowercase letter followed by an uppercase one
+        let mut words = vec![String::new()];
+        let mut prev = '-';
+        for c in name.chars() {
+            if c == '-' || c == '_' || (prev.is_ascii_lowercase() && c.is_ascii_uppercase()) {
+                words.push(String::new());
+            }
+            if c != '-' && c != '_' {
+                words.last_mut().unwrap().push(c.to_ascii_lowercase());
+            }
+            prev = c;
+        }
+        words.retain(|word| !word.is_empty());
+        let variant = words
+            .iter()
+            .map(|word| word[..1].to_ascii_uppercase() + &word[1..])
+            .collect();
+        Ok(Self {
+            dir: words.join("-"),
+            module: words.join("_"),
+            variant,
+        })
+    }
+
+    fn fill(&self, template: &str) -> String {
+        template.replace("{{module}}", &self.module)
+    }
+}
+
+/// Creates a shader crate for a new demo from the templates of `kind`, with its
+/// push constants and controller, and registers it everywhere shaders are listed.
+pub fn new_demo(name: &str, kind: DemoKind) -> Result<(), String> {
+    let names = Names::new(name)?;
+    let root =
+        Path::new(option_env!("SHADERS_DIR").unwrap_or(env!("CARGO_MANIFEST_DIR"))).join("..");
+    let crate_dir = root.join("shaders").join(&names.dir);
+    if crate_dir.exists() {
+        return Err(format!("{} already exists", crate_dir.display()));
+    }
+    if names.variant.parse::<RustGPUShader>().is_ok() {
+        return Err(format!("`RustGPUShader::{}` already exists", names.variant));
+    }
+    let Names {
+        dir,
+        module,
+        variant,
+    } = &names;
+
+    // Every file is edited in memory first, so nothing is written if one can't be
+    let mut edits = vec![];
+    let mut edit = |path: &str, f: &dyn Fn(&str) -> Result<String, String>| {
+        let path = root.join(path);
+        let contents = fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let contents = f(&contents).map_err(|e| format!("{}: {e}", path.display()))?;
+        edits.push((path, contents));
+        Ok::<_, String>(())
+    };
+    edit("Cargo.toml", &|s| {
+        insert_before_end(s, "members = [", "]", &format!("  \"shaders/{dir}\","))
+    })?;
+    edit("runner/builder/src/main.rs", &|s| {
+        insert_before_end(s, "const SHADER_CRATES", "];", &format!("    \"{dir}\","))
+    })?;
+    edit("shaders/shared/src/push_constants.rs", &|s| {
+        let s = insert_after_last(s, "pub mod ", &format!("pub mod {module};"))?;
+        insert_before_end(
+            &s,
+            "pub fn largest_size()",
+            "}",
+            &format!("        .max(size_of::<{module}::ShaderConstants>())"),
+        )
+    })?;
+    edit("runner/src/shaders.rs", &|s| {
+        insert_after_last(s, "pub mod ", &format!("pub mod {module};"))
+    })?;
+    edit("runner/src/lib.rs", &|s| {
+        insert_before_end(
+            s,
+            "pub enum RustGPUShader {",
+            "}",
+            &format!("    {variant},"),
+        )
+    })?;
+    edit("runner/src/shader.rs", &|s| {
+        let s = insert_before_end(
+            s,
+            "let crate_name = match options.shader {",
+            "        };",
+            &format!("            RustGPUShader::{variant} => \"{dir}\","),
+        )?;
+        insert_before_end(
+            &s,
+            "pub fn embedded(",
+            "    };",
+            &match_arm(
+                8,
+                &format!("RustGPUShader::{variant}"),
+                &format!("wgpu::include_spirv_raw!(env!(\"{module}.spv\"))"),
+            ),
+        )
+    })?;
+    edit("runner/src/controller.rs", &|s| {
+        insert_before_end(
+            s,
+            "pub fn new_controller(",
+            "    }",
+            &match_arm(
+                8,
+                &format!("RustGPUShader::{variant}"),
+                &format!("Box::new({module}::Controller::new(size))"),
+            ),
+        )
+    })?;
+
+    let [lib, push_constants, controller] = templates(kind);
+    let mut files = vec![
+        (crate_dir.join("Cargo.toml"), names.fill(CARGO_TOML)),
+        (crate_dir.join("src").join("lib.rs"), names.fill(lib)),
+        (
+            root.join(format!("shaders/shared/src/push_constants/{module}.rs")),
+            names.fill(push_constants),
+        ),
+        (
+            root.join(format!("runner/src/shaders/{module}.rs")),
+            names.fill(controller),
+        ),
+    ];
+    files.extend(edits);
+
+    fs::create_dir_all(crate_dir.join("src"))
+        .map_err(|e| format!("{}: {e}", crate_dir.display()))?;
+    for (path, contents) in files {
+        fs::write(&path, contents).map_err(|e| format!("{}: {e}", path.display()))?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Inserts `line` before the first line equal to `end` after the line containing `start`.
+fn insert_before_end(s: &str, start: &str, end: &str, line: &str) -> Result<String, String> {
+    let mut lines = s.lines().collect::<Vec<_>>();
+    let start = lines
+        .iter()
+        .position(|l| l.contains(start))
+        .ok_or_else(|| format!("`{start}` not found"))?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .position(|l| *l == end)
+            .ok_or_else(|| format!("end of `{}` not found", lines[start].trim()))?;
+    lines.insert(end, line);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Inserts `line` after the last line starting with `prefix`.
+fn insert_after_last(s: &str, prefix: &str, line: &str) -> Result<String, String> {
+    let mut lines = s.lines().collect::<Vec<_>>();
+    let last = lines
+        .iter()
+        .rposition(|l| l.starts_with(prefix))
+        .ok_or_else(|| format!("no line starting with `{prefix}`"))?;
+    lines.insert(last + 1, line);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// A match arm indented by `indent`, with the body in a block if the line
+/// would be longer than rustfmt allows.
+fn match_arm(indent: usize, pattern: &str, body: &str) -> String {
+    let pad = " ".repeat(indent);
+    let line = format!("{pad}{pattern} => {body},");
+    if line.len() <= 100 {
+        line
+    } else {
+        format!("{pad}{pattern} => {{\n{pad}    {body}\n{pad}}}")
+    }
+}
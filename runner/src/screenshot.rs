@@ -0,0 +1,70 @@
+use crate::RustGPUShader;
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SHADER_KEY: &str = "RustGPUShader";
+const PARAMS_KEY: &str = "Parameters";
+const TIME_KEY: &str = "Time";
+
+/// Everything needed to restore the view of a screenshot,
+/// stored in the tEXt chunks of the PNG.
+pub struct Metadata {
+    pub shader: RustGPUShader,
+    /// Controller parameters as `key=value` lines.
+    pub params: String,
+    pub time: f32,
+}
+
+/// A unique file name starting with `stem`, e.g. `Mandelbrot-1718000000000.png`.
+pub fn file_name(stem: &str, extension: &str) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis());
+    PathBuf::from(format!("{stem}-{millis}.{extension}"))
+}
+
+pub fn save(
+    path: &Path,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    metadata: &Metadata,
+) -> Result<(), png::EncodingError> {
+    let writer = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    encoder.add_text_chunk(SHADER_KEY.to_string(), metadata.shader.to_string())?;
+    encoder.add_text_chunk(PARAMS_KEY.to_string(), metadata.params.clone())?;
+    encoder.add_text_chunk(TIME_KEY.to_string(), metadata.time.to_string())?;
+    encoder.write_header()?.write_image_data(rgba)
+}
+
+pub fn load(path: &Path) -> Result<Metadata, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let reader = png::Decoder::new(file)
+        .read_info()
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    let text = |key: &str| {
+        reader
+            .info()
+            .uncompressed_latin1_text
+            .iter()
+            .find(|chunk| chunk.keyword == key)
+            .map(|chunk| chunk.text.clone())
+            .ok_or_else(|| format!("{}: missing `{key}` metadata", path.display()))
+    };
+    let shader = text(SHADER_KEY)?;
+    Ok(Metadata {
+        shader: shader
+            .parse()
+            .map_err(|_| format!("{}: unknown shader `{shader}`", path.display()))?,
+        params: text(PARAMS_KEY)?,
+        time: text(TIME_KEY)?.parse().unwrap_or_default(),
+    })
+}
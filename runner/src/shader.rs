@@ -1,4 +1,4 @@
-use crate::{Options, RustGPUShader};
+use crate::{controller::BufferData, reflection::Reflection, Options, RustGPUShader};
 use std::borrow::Cow;
 
 pub struct CompiledShaderModules {
@@ -29,6 +29,20 @@ impl CompiledShaderModules {
                 .collect::<Vec<_>>()
         );
     }
+
+    /// Reflects every module and checks that the shader's push constants and
+    /// buffers match those of the controller, as wgpu panics on a mismatch.
+    pub fn validate(
+        &self,
+        push_constants_size: usize,
+        buffer_data: &BufferData,
+    ) -> Result<(), String> {
+        let mut reflection = Reflection::default();
+        for (_, spv_module) in &self.named_spv_modules {
+            reflection.add_module(&spv_module.source)?;
+        }
+        reflection.validate(push_constants_size, buffer_data)
+    }
 }
 
 pub fn maybe_watch(
@@ -37,6 +51,10 @@ pub fn maybe_watch(
         Box<dyn FnMut(CompiledShaderModules) + Send + 'static>,
     >,
 ) -> CompiledShaderModules {
+    #[cfg(target_arch = "wasm32")]
+    {
+        embedded(options.shader)
+    }
     #[cfg(not(target_arch = "wasm32"))]
     {
         use spirv_builder::{CompileResult, MetadataPrintout, SpirvBuilder};
@@ -136,3 +154,35 @@ pub fn maybe_watch(
         handle_compile_result(initial_result)
     }
 }
+
+/// The shader precompiled by the builder, as the web build can't run `spirv-builder`.
+/// The modules are named after the shader packages, with `-` replaced by `_`.
+#[cfg(target_arch = "wasm32")]
+pub fn embedded(shader: RustGPUShader) -> CompiledShaderModules {
+    let module = match shader {
+        RustGPUShader::Mandelbrot => wgpu::include_spirv_raw!(env!("mandelbrot.spv")),
+        RustGPUShader::RayMarching => wgpu::include_spirv_raw!(env!("ray_marching.spv")),
+        RustGPUShader::RayMarching2D => wgpu::include_spirv_raw!(env!("ray_marching_2d.spv")),
+        RustGPUShader::SierpinskiTriangle => {
+            wgpu::include_spirv_raw!(env!("sierpinski_triangle.spv"))
+        }
+        RustGPUShader::KochSnowflake => wgpu::include_spirv_raw!(env!("koch_snowflake.spv")),
+        RustGPUShader::SDFs2D => wgpu::include_spirv_raw!(env!("sdfs_2d.spv")),
+        RustGPUShader::SDFs3D => wgpu::include_spirv_raw!(env!("sdfs_3d.spv")),
+        RustGPUShader::HydrogenWavefunction => {
+            wgpu::include_spirv_raw!(env!("hydrogen_wavefunction.spv"))
+        }
+        RustGPUShader::SphericalHarmonics => {
+            wgpu::include_spirv_raw!(env!("spherical_harmonics.spv"))
+        }
+        RustGPUShader::SphericalHarmonicsShape => wgpu::include_spirv_raw!(env!("test_mesh.spv")),
+        RustGPUShader::FunRepDemo => wgpu::include_spirv_raw!(env!("fun_rep_demo.spv")),
+        RustGPUShader::SdfBuilder => wgpu::include_spirv_raw!(env!("sdf_builder.spv")),
+        RustGPUShader::ProceduralGeneration => {
+            wgpu::include_spirv_raw!(env!("procudural_generation.spv"))
+        }
+    };
+    CompiledShaderModules {
+        named_spv_modules: vec![(None, module)],
+    }
+}
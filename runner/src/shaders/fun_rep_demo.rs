@@ -1,20 +1,26 @@
-use crate::controller::{BindGroupBufferType, BufferData, SSBO};
+use crate::{
+    controller::{BindGroupBufferType, BufferData, FrameContext, SSBO},
+    egui_components::palette::{PaletteChoice, PaletteEditor},
+    params::Param,
+    probe::Probe,
+    window::UserEvent,
+};
 use bytemuck::Zeroable;
+use egui::Context;
 use egui_winit::winit::{
     dpi::{PhysicalPosition, PhysicalSize},
     event::{ElementState, MouseButton, MouseScrollDelta},
+    event_loop::EventLoopProxy,
 };
 use glam::{vec2, Vec2};
 use shared::{
-    interpreter::{OpCode0, OpCodeStruct},
+    from_pixels,
+    interpreter::{Interpreter, OpCode0, OpCodeStruct},
     push_constants::fun_rep_demo::ShaderConstants,
 };
-use std::time::{Duration, Instant};
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
-    elapsed: Duration,
     cursor: Vec2,
     mouse_button_pressed: bool,
     can_drag: Option<usize>,
@@ -22,6 +28,13 @@ pub struct Controller {
     shader_constants: ShaderConstants,
     zoom: f32,
     buffer: Vec<OpCodeStruct>,
+    palette: PaletteEditor,
+    /// Time of the animation, which stops while another shader is active.
+    time: f32,
+    /// Clock time at which the animation time was zero.
+    time_offset: f32,
+    /// Set when the controller becomes active, so the animation resumes where it stopped.
+    resumed: bool,
 }
 
 impl crate::controller::Controller for Controller {
@@ -30,8 +43,6 @@ impl crate::controller::Controller for Controller {
 
         Self {
             size,
-            start: Instant::now(),
-            elapsed: Duration::ZERO,
             cursor: Vec2::ZERO,
             mouse_button_pressed: false,
             can_drag: None,
@@ -39,6 +50,10 @@ impl crate::controller::Controller for Controller {
             shader_constants: ShaderConstants::zeroed(),
             zoom: 1.0,
             buffer: ops,
+            palette: PaletteEditor::new(PaletteChoice::BlueOrange),
+            time: 0.0,
+            time_offset: 0.0,
+            resumed: false,
         }
     }
 
@@ -86,16 +101,24 @@ impl crate::controller::Controller for Controller {
         self.size = size
     }
 
-    fn update(&mut self) {
-        self.elapsed = self.start.elapsed();
+    fn on_activate(&mut self) {
+        self.resumed = true;
+    }
+
+    fn update(&mut self, ctx: &FrameContext) {
+        if std::mem::take(&mut self.resumed) {
+            self.time_offset = ctx.time - self.time;
+        }
+        self.time = ctx.time - self.time_offset;
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
-            time: self.elapsed.as_secs_f32(),
+            time: self.time,
             cursor: self.cursor.into(),
             mouse_button_pressed: !(1
                 << (self.mouse_button_pressed && self.drag_point.is_none()) as u32),
             zoom: self.zoom,
         };
+        self.palette.update();
     }
 
     fn push_constants(&self) -> &[u8] {
@@ -103,18 +126,39 @@ impl crate::controller::Controller for Controller {
     }
 
     fn has_ui(&self) -> bool {
-        false
+        true
+    }
+
+    fn ui(&mut self, _ctx: &Context, ui: &mut egui::Ui, _: &EventLoopProxy<UserEvent>) {
+        self.palette.ui(ui);
     }
 
     fn buffers(&self) -> BufferData<'_> {
         BufferData {
-            bind_group_buffers: vec![BindGroupBufferType::SSBO(SSBO {
-                data: bytemuck::cast_slice(&self.buffer),
-                read_only: true,
-            })],
+            bind_group_buffers: vec![
+                BindGroupBufferType::SSBO(SSBO {
+                    data: bytemuck::cast_slice(&self.buffer),
+                    read_only: true,
+                    per_frame: false,
+                }),
+                self.palette.buffer(),
+            ],
             ..Default::default()
         }
     }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            Param::unbounded("zoom", &mut self.zoom),
+            Param::choice("palette", &mut self.palette.choice),
+        ]
+    }
+
+    fn probe(&self) -> Option<Probe> {
+        let position = self.zoom * from_pixels(self.cursor, self.size.into());
+        let distance = Interpreter::<8>::new(position).interpret(&self.buffer, self.buffer.len());
+        Some(Probe::new(position).with("distance", distance))
+    }
 }
 
 fn length() -> Vec<OpCode0> {
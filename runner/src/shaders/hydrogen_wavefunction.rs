@@ -1,4 +1,4 @@
-use crate::window::UserEvent;
+use crate::{controller::FrameContext, params::Param, window::UserEvent};
 use bytemuck::Zeroable;
 use egui::Context;
 use egui_winit::winit::{
@@ -7,13 +7,14 @@ use egui_winit::winit::{
     event_loop::EventLoopProxy,
 };
 use glam::{vec2, Vec2};
-use shared::{push_constants::hydrogen_wavefunction::ShaderConstants, spherical_harmonics};
-use std::time::Instant;
+use shared::{
+    push_constants::{hydrogen_wavefunction::ShaderConstants, Eye},
+    spherical_harmonics,
+};
+use std::borrow::Cow;
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
-    paused: Instant,
     cursor: Vec2,
     camera: Vec2,
     camera_distance: f32,
@@ -23,14 +24,18 @@ pub struct Controller {
     l: i32,
     m: i32,
     time_dependent: bool,
+    /// Time of the wavefunction, which stops while it doesn't evolve.
+    phase: f32,
+    /// Clock time at which the phase was zero.
+    time_offset: f32,
+    /// Set when the controller becomes active, so the phase resumes where it stopped.
+    resumed: bool,
 }
 
 impl crate::controller::Controller for Controller {
     fn new(size: PhysicalSize<u32>) -> Self {
         Self {
             size,
-            start: Instant::now(),
-            paused: Instant::now(),
             cursor: Vec2::ZERO,
             camera: Vec2::ZERO,
             camera_distance: 30.0,
@@ -40,6 +45,9 @@ impl crate::controller::Controller for Controller {
             l: 1,
             m: 1,
             time_dependent: false,
+            phase: 0.0,
+            time_offset: 0.0,
+            resumed: false,
         }
     }
 
@@ -84,17 +92,21 @@ impl crate::controller::Controller for Controller {
         self.size = size;
     }
 
-    fn update(&mut self) {
+    fn on_activate(&mut self) {
+        self.resumed = true;
+    }
+
+    fn update(&mut self, ctx: &FrameContext) {
         self.l = self.l.clamp(0, self.n - 1);
         self.m = self.m.clamp(-self.l, self.l);
+        if self.time_dependent && !std::mem::take(&mut self.resumed) {
+            self.phase = ctx.time - self.time_offset;
+        } else {
+            self.time_offset = ctx.time - self.phase;
+        }
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
-            time: (if self.time_dependent {
-                self.start.elapsed()
-            } else {
-                self.paused - self.start
-            })
-            .as_secs_f32(),
+            time: self.phase,
             cursor: self.cursor.into(),
             camera_distance: self.camera_distance,
             translate: (self.camera / self.size.height as f32).into(),
@@ -104,6 +116,7 @@ impl crate::controller::Controller for Controller {
             m: self.m,
             normalization_constant: radial_nc(self.n as u32, self.l as u32)
                 * angular_nc(self.m, self.l as u32),
+            eye: Eye::mono(self.size.width),
         };
     }
 
@@ -111,25 +124,43 @@ impl crate::controller::Controller for Controller {
         bytemuck::bytes_of(&self.shader_constants)
     }
 
+    fn has_stereo(&self) -> bool {
+        true
+    }
+
+    fn eye_push_constants(&self, eye: Eye) -> Cow<'_, [u8]> {
+        // The eye separation is relative to the distance to the nucleus
+        let eye = Eye {
+            offset: eye.offset * self.camera_distance,
+            ..eye
+        };
+        Cow::Owned(bytemuck::bytes_of(&ShaderConstants { eye, ..self.shader_constants }).to_vec())
+    }
+
     fn has_ui(&self) -> bool {
         true
     }
 
     fn ui(&mut self, _ctx: &Context, ui: &mut egui::Ui, _: &EventLoopProxy<UserEvent>) {
-        if ui
-            .checkbox(&mut self.time_dependent, "Evolve over time")
-            .clicked()
-        {
-            if self.time_dependent {
-                self.start += self.paused.elapsed();
-            } else {
-                self.paused = Instant::now();
-            }
-        }
+        ui.checkbox(&mut self.time_dependent, "Evolve over time");
         ui.add(egui::Slider::new(&mut self.n, 1..=5).text("n"));
         ui.add(egui::Slider::new(&mut self.l, 0..=self.n - 1).text("l"));
         ui.add(egui::Slider::new(&mut self.m, -self.l..=self.l).text("m"));
     }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        let n = self.n;
+        let l = self.l;
+        vec![
+            Param::i32("n", &mut self.n, 1..=5),
+            Param::i32("l", &mut self.l, 0..=n - 1),
+            Param::i32("m", &mut self.m, -l..=l),
+            Param::bool("time_dependent", &mut self.time_dependent),
+            Param::unbounded("camera_x", &mut self.camera.x),
+            Param::unbounded("camera_y", &mut self.camera.y),
+            Param::unbounded("camera_distance", &mut self.camera_distance),
+        ]
+    }
 }
 
 fn radial_nc(n: u32, l: u32) -> f32 {
@@ -1,4 +1,4 @@
-use crate::window::UserEvent;
+use crate::{controller::FrameContext, params::Param, window::UserEvent};
 use bytemuck::Zeroable;
 use egui::Context;
 use glam::{vec2, Vec2};
@@ -33,7 +33,7 @@ impl crate::controller::Controller for Controller {
         self.size = size;
     }
 
-    fn update(&mut self) {
+    fn update(&mut self, _ctx: &FrameContext) {
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
             cursor: from_pixels(self.cursor, self.size.into()).into(),
@@ -53,4 +53,8 @@ impl crate::controller::Controller for Controller {
         ui.radio_value(&mut self.use_antisnowflake, false, "Snowflake");
         ui.radio_value(&mut self.use_antisnowflake, true, "AntiSnowflake");
     }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![Param::bool("use_antisnowflake", &mut self.use_antisnowflake)]
+    }
 }
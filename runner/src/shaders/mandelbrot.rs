@@ -1,4 +1,10 @@
-use crate::window::UserEvent;
+use crate::{
+    controller::{BufferData, FrameContext},
+    egui_components::palette::{PaletteChoice, PaletteEditor},
+    params::Param,
+    probe::Probe,
+    window::UserEvent,
+};
 use bytemuck::Zeroable;
 use egui::Context;
 use egui_winit::winit::{
@@ -7,7 +13,7 @@ use egui_winit::winit::{
     event_loop::EventLoopProxy,
 };
 use glam::{vec2, Vec2};
-use shared::push_constants::mandelbrot::ShaderConstants;
+use shared::{from_pixels, mandelbrot, push_constants::mandelbrot::ShaderConstants};
 
 pub struct Controller {
     size: PhysicalSize<u32>,
@@ -18,6 +24,7 @@ pub struct Controller {
     mouse_button_pressed: bool,
     exponent: f32,
     num_iterations: u32,
+    palette: PaletteEditor,
     shader_constants: ShaderConstants,
 }
 
@@ -32,6 +39,7 @@ impl crate::controller::Controller for Controller {
             mouse_button_pressed: false,
             exponent: 2.0,
             num_iterations: 35,
+            palette: PaletteEditor::new(PaletteChoice::Grayscale),
             shader_constants: ShaderConstants::zeroed(),
         }
     }
@@ -80,7 +88,7 @@ impl crate::controller::Controller for Controller {
         self.size = size;
     }
 
-    fn update(&mut self) {
+    fn update(&mut self, _ctx: &FrameContext) {
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
             zoom: self.zoom,
@@ -88,6 +96,7 @@ impl crate::controller::Controller for Controller {
             exponent: self.exponent,
             num_iterations: self.num_iterations,
         };
+        self.palette.update();
     }
 
     fn push_constants(&self) -> &[u8] {
@@ -115,5 +124,30 @@ impl crate::controller::Controller for Controller {
                     .speed(1),
             );
         });
+        self.palette.ui(ui);
+    }
+
+    fn buffers(&self) -> BufferData<'_> {
+        BufferData {
+            bind_group_buffers: vec![self.palette.buffer()],
+            ..Default::default()
+        }
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            Param::f32("exponent", &mut self.exponent, 1.0..=6.0),
+            Param::u32("num_iterations", &mut self.num_iterations, 2..=200),
+            Param::unbounded("camera_x", &mut self.camera.x),
+            Param::unbounded("camera_y", &mut self.camera.y),
+            Param::unbounded("zoom", &mut self.zoom),
+            Param::choice("palette", &mut self.palette.choice),
+        ]
+    }
+
+    fn probe(&self) -> Option<Probe> {
+        let position = self.zoom * from_pixels(self.cursor + self.camera, self.size.into());
+        let iterations = mandelbrot::iterations(position.into(), self.exponent, self.num_iterations);
+        Some(Probe::new(position).with("iterations", iterations))
     }
 }
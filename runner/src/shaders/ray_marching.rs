@@ -1,20 +1,29 @@
-use crate::camera::FirstPersonCamera;
+use crate::{
+    camera::FirstPersonCamera,
+    controller::{BindGroupBufferType, BufferData, FrameContext, KeyInput, SSBO},
+    params::Param,
+};
 use bytemuck::Zeroable;
 use glam::{vec2, vec3};
-use shared::push_constants::ray_marching::ShaderConstants;
-use std::time::{Duration, Instant};
+use shared::push_constants::{
+    ray_marching::{DebugValues, Projection, ShaderConstants},
+    Eye, UVec2,
+};
+use std::{borrow::Cow, time::Duration};
+use strum::IntoEnumIterator;
 use egui_winit::winit::{
-    dpi::PhysicalSize,
-    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta},
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, MouseButton, MouseScrollDelta},
+    keyboard::{KeyCode, PhysicalKey},
 };
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
-    elapsed: Duration,
-    last_elapsed: Duration,
     camera: FirstPersonCamera,
     mouse_button_pressed: bool,
+    projection: Projection,
+    inspect: Option<PhysicalPosition<u32>>,
+    debug_values: DebugValues,
     shader_constants: ShaderConstants,
 }
 
@@ -22,20 +31,29 @@ impl crate::controller::Controller for Controller {
     fn new(size: PhysicalSize<u32>) -> Self {
         Self {
             size,
-            start: Instant::now(),
-            elapsed: Duration::ZERO,
-            last_elapsed: Duration::ZERO,
             camera: FirstPersonCamera::new(
                 size.width as f32 / size.height as f32,
                 vec3(0.0, 1.0, 1.0),
                 1.0,
             ),
             mouse_button_pressed: false,
+            projection: Projection::Pinhole,
+            inspect: None,
+            debug_values: DebugValues::zeroed(),
             shader_constants: ShaderConstants::zeroed(),
         }
     }
 
-    fn keyboard_input(&mut self, event: KeyEvent) {
+    fn keyboard_input(&mut self, event: KeyInput) {
+        if event.state == ElementState::Pressed
+            && event.physical_key == PhysicalKey::Code(KeyCode::KeyP)
+        {
+            self.projection = Projection::iter()
+                .cycle()
+                .skip_while(|&projection| projection != self.projection)
+                .nth(1)
+                .unwrap();
+        }
         self.camera.keyboard_input(event);
     }
 
@@ -80,17 +98,27 @@ impl crate::controller::Controller for Controller {
         self.camera.resize(size);
     }
 
-    fn update(&mut self) {
-        self.elapsed = self.start.elapsed();
-        self.camera.update(self.elapsed - self.last_elapsed);
+    fn update(&mut self, ctx: &FrameContext) {
+        self.camera.update(Duration::from_secs_f32(ctx.dt));
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
-            time: self.elapsed.as_secs_f32(),
+            time: ctx.time,
             pos: self.camera.position.into(),
             yaw: self.camera.yaw,
             pitch: self.camera.pitch,
+            projection: self.projection as u32,
+            inspect: self.inspect.map_or(
+                UVec2 {
+                    x: u32::MAX,
+                    y: u32::MAX,
+                },
+                |pixel| UVec2 {
+                    x: pixel.x,
+                    y: pixel.y,
+                },
+            ),
+            eye: Eye::mono(self.size.width),
         };
-        self.last_elapsed = self.elapsed;
     }
 
     fn cursor_visible(&self) -> bool {
@@ -100,4 +128,69 @@ impl crate::controller::Controller for Controller {
     fn push_constants(&self) -> &[u8] {
         bytemuck::bytes_of(&self.shader_constants)
     }
+
+    fn has_stereo(&self) -> bool {
+        true
+    }
+
+    fn eye_push_constants(&self, eye: Eye) -> Cow<'_, [u8]> {
+        Cow::Owned(bytemuck::bytes_of(&ShaderConstants { eye, ..self.shader_constants }).to_vec())
+    }
+
+    fn panorama_push_constants(&self, width: u32) -> Option<Vec<u8>> {
+        let constants = ShaderConstants {
+            size: PhysicalSize::new(width, width / 2).into(),
+            projection: Projection::Equirectangular as u32,
+            eye: Eye::mono(width),
+            ..self.shader_constants
+        };
+        Some(bytemuck::bytes_of(&constants).to_vec())
+    }
+
+    fn buffers(&self) -> BufferData<'_> {
+        BufferData {
+            bind_group_buffers: vec![BindGroupBufferType::SSBO(SSBO {
+                data: bytemuck::bytes_of(&self.debug_values),
+                read_only: false,
+                per_frame: false,
+            })],
+            ..Default::default()
+        }
+    }
+
+    fn inspect(&mut self, pixel: Option<PhysicalPosition<u32>>) {
+        self.inspect = pixel;
+    }
+
+    fn debug_buffer(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn debug_values(&self, buffer: &[u8]) -> Vec<(&'static str, String)> {
+        let values: DebugValues = bytemuck::pod_read_unaligned(buffer);
+        let normal = values.normal;
+        let material = match values.material {
+            0 => "matte".to_string(),
+            1 => "mirror".to_string(),
+            id => id.to_string(),
+        };
+        vec![
+            ("steps", values.steps.to_string()),
+            ("distance", values.distance.to_string()),
+            ("normal", format!("{:.3} {:.3} {:.3}", normal.x, normal.y, normal.z)),
+            ("material", material),
+            ("reflections", values.reflections.to_string()),
+        ]
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            Param::unbounded("camera_x", &mut self.camera.position.x),
+            Param::unbounded("camera_y", &mut self.camera.position.y),
+            Param::unbounded("camera_z", &mut self.camera.position.z),
+            Param::unbounded("yaw", &mut self.camera.yaw),
+            Param::unbounded("pitch", &mut self.camera.pitch),
+            Param::choice("projection", &mut self.projection),
+        ]
+    }
 }
@@ -1,7 +1,10 @@
+use crate::{
+    controller::{BindGroupBufferType, BufferData, FrameContext, SSBO},
+    params::Param,
+};
 use bytemuck::Zeroable;
 use egui::{vec2, Vec2};
-use shared::push_constants::ray_marching_2d::ShaderConstants;
-use std::time::{Duration, Instant};
+use shared::{push_constants::ray_marching_2d::ShaderConstants, spectrum::Spectrum};
 use egui_winit::winit::{
     dpi::{PhysicalPosition, PhysicalSize},
     event::{ElementState, MouseButton, MouseScrollDelta},
@@ -9,8 +12,6 @@ use egui_winit::winit::{
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
-    elapsed: Duration,
     cursor: Vec2,
     drag_start: Vec2,
     drag_end: Vec2,
@@ -22,14 +23,13 @@ pub struct Controller {
     drag: Vec2,
     prev_cursor: Vec2,
     shader_constants: ShaderConstants,
+    spectrum: Spectrum,
 }
 
 impl crate::controller::Controller for Controller {
     fn new(size: PhysicalSize<u32>) -> Self {
         Self {
             size,
-            start: Instant::now(),
-            elapsed: Duration::ZERO,
             cursor: Vec2::ZERO,
             drag_start: Vec2::ZERO,
             drag_end: Vec2::ZERO,
@@ -41,6 +41,7 @@ impl crate::controller::Controller for Controller {
             drag: Vec2::ZERO,
             prev_cursor: Vec2::ZERO,
             shader_constants: ShaderConstants::zeroed(),
+            spectrum: Spectrum::zeroed(),
         }
     }
 
@@ -91,15 +92,14 @@ impl crate::controller::Controller for Controller {
         self.size = size;
     }
 
-    fn update(&mut self) {
-        self.elapsed = self.start.elapsed();
+    fn update(&mut self, ctx: &FrameContext) {
         self.zoom *= self.scroll;
         self.camera *= 1.0 / self.scroll;
         self.camera += self.drag;
         self.shader_constants = ShaderConstants {
             width: self.size.width,
             height: self.size.height,
-            time: self.elapsed.as_secs_f32(),
+            time: ctx.time,
             cursor_x: self.cursor.x,
             cursor_y: self.cursor.y,
             drag_start_x: self.drag_start.x,
@@ -111,12 +111,32 @@ impl crate::controller::Controller for Controller {
             translate_y: self.camera.y,
             mouse_button_pressed: !(1 << self.mouse_button_pressed as u32),
         };
+        self.spectrum = *ctx.spectrum;
         self.finish_update();
     }
 
     fn push_constants(&self) -> &[u8] {
         bytemuck::bytes_of(&self.shader_constants)
     }
+
+    fn buffers(&self) -> BufferData<'_> {
+        BufferData {
+            bind_group_buffers: vec![BindGroupBufferType::SSBO(SSBO {
+                data: bytemuck::bytes_of(&self.spectrum),
+                read_only: true,
+                per_frame: true,
+            })],
+            ..Default::default()
+        }
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            Param::unbounded("camera_x", &mut self.camera.x),
+            Param::unbounded("camera_y", &mut self.camera.y),
+            Param::unbounded("zoom", &mut self.zoom),
+        ]
+    }
 }
 
 impl Controller {
@@ -1,4 +1,9 @@
-use dfutils::{grid::Grid, primitives::Disk, primitives_enum::Shape};
+use super::modifier::Modifier;
+use dfutils::{
+    grid::Grid,
+    primitives::{Disk, Rectangle},
+    primitives_enum::Shape,
+};
 use egui::{Color32, ColorImage, TextureHandle};
 use glam::{vec2, vec3, Vec3};
 use shared::sdf_interpreter::{Instruction, Operator, SdfInstructions, Transform};
@@ -7,11 +12,13 @@ use strum::IntoEnumIterator;
 pub struct IconImages {
     pub shapes: Vec<ColorImage>,
     pub operators: Vec<ColorImage>,
+    pub modifiers: Vec<ColorImage>,
 }
 
 pub struct TextureHandles {
     pub shapes: Vec<TextureHandle>,
     pub operators: Vec<TextureHandle>,
+    pub modifiers: Vec<TextureHandle>,
 }
 
 impl TextureHandles {
@@ -19,6 +26,7 @@ impl TextureHandles {
         TextureHandles {
             shapes: vec![],
             operators: vec![],
+            modifiers: vec![],
         }
     }
 
@@ -40,7 +48,7 @@ pub fn generate_icons() -> IconImages {
                     .collect(),
             })
             .collect(),
-        operators: Operator::iter()
+        operators: Operator::all()
             .map(|op| {
                 let instructions = get_instructions(op);
                 let sdf = SdfInstructions::new(&instructions);
@@ -54,28 +62,47 @@ pub fn generate_icons() -> IconImages {
                 }
             })
             .collect(),
+        modifiers: Modifier::all()
+            .into_iter()
+            .map(|modifier| {
+                let instructions = get_modifier_instructions(modifier);
+                let sdf = SdfInstructions::new(&instructions);
+                ColorImage {
+                    size: [N, N],
+                    pixels: Grid::from_sdf(N, N, &sdf)
+                        .buffer
+                        .into_iter()
+                        .map(color_from_distance)
+                        .collect(),
+                }
+            })
+            .collect(),
     }
 }
 
 fn get_instructions(op: Operator) -> [Instruction<Shape>; 3] {
     let disk = Shape::Disk(Disk::new(0.25));
     [
-        Instruction::Sdf(
-            disk,
-            Transform {
-                position: vec2(0.1, 0.0),
-            },
-        ),
-        Instruction::Sdf(
-            disk,
-            Transform {
-                position: vec2(-0.1, 0.0),
-            },
-        ),
+        Instruction::Sdf(disk, Transform::from_position(vec2(0.1, 0.0))),
+        Instruction::Sdf(disk, Transform::from_position(vec2(-0.1, 0.0))),
         Instruction::Operator(op),
     ]
 }
 
+fn get_modifier_instructions(modifier: Modifier) -> Vec<Instruction<Shape>> {
+    let rectangle = Instruction::Sdf(
+        Shape::Rectangle(Rectangle::new(0.15, 0.1)),
+        Transform::from_position(vec2(0.1, 0.05)),
+    );
+    match (modifier.point_modifier(), modifier.distance_modifier()) {
+        (Some(point_modifier), _) => vec![Instruction::PointModifier(point_modifier, 1), rectangle],
+        (None, Some(distance_modifier)) => {
+            vec![rectangle, Instruction::DistanceModifier(distance_modifier)]
+        }
+        (None, None) => vec![rectangle],
+    }
+}
+
 fn color_from_distance(d: f32) -> Color32 {
     let col = 255.0
         * ((1.0 - (-6.0 * d.abs()).exp())
@@ -1,9 +1,12 @@
 use crate::{
-    controller::{BindGroupBufferType, BufferData, SSBO},
+    controller::{sample_pixels, BindGroupBufferType, BufferData, FrameContext, SSBO},
+    egui_components::palette::{PaletteChoice, PaletteEditor},
+    params::Param,
+    probe::Probe,
     window::UserEvent,
 };
 use bytemuck::Zeroable;
-use dfutils::{grid::*, primitives_enum::Shape, sdf::Sdf};
+use dfutils::{grid::*, primitives::Triangle, primitives_enum::Shape, sdf::Sdf};
 use egui::{Context, CursorIcon};
 use egui_winit::winit::{
     dpi::{PhysicalPosition, PhysicalSize},
@@ -20,10 +23,11 @@ use shared::{
     sdf_interpreter::{Instruction, SdfInstructions, Transform},
     sdf_wrapper::{SdfWrapper, WrappedDistance},
 };
-use std::time::{Duration, Instant};
 
 mod icons;
+mod modifier;
 mod resize;
+mod scene;
 mod sdf_builder_tree;
 pub mod shape_ui;
 
@@ -57,7 +61,6 @@ impl GrabType {
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
     shader_constants: ShaderConstants,
     grid: Grid<WrappedDistance<ItemId>>,
     sdf_builder_tree: SdfBuilderTree,
@@ -68,15 +71,17 @@ pub struct Controller {
     original_selected_item: Option<Item>,
     texture_handles: TextureHandles,
     instructions: Vec<Instruction<SdfWrapper<Shape, ItemId>>>,
-    last_mouse_press: (Vec2, std::time::Instant),
+    /// Seconds the controller has been active, for timing clicks.
+    active_time: f32,
+    scale_factor: f64,
+    last_mouse_press: (Vec2, f32),
+    palette: PaletteEditor,
 }
 
 impl crate::controller::Controller for Controller {
     fn new(size: PhysicalSize<u32>) -> Self {
-        let now = Instant::now();
         Self {
             size,
-            start: now,
             shader_constants: ShaderConstants::zeroed(),
             grid: Grid::new(size.width as usize, size.height as usize),
             sdf_builder_tree: SdfBuilderTree::default(),
@@ -87,17 +92,34 @@ impl crate::controller::Controller for Controller {
             original_selected_item: None,
             texture_handles: TextureHandles::empty(),
             instructions: vec![],
-            last_mouse_press: (Vec2::ZERO, now),
+            active_time: 0.0,
+            scale_factor: 1.0,
+            last_mouse_press: (Vec2::ZERO, 0.0),
+            palette: PaletteEditor::new(PaletteChoice::BlueOrange),
         }
     }
 
     fn resize(&mut self, size: PhysicalSize<u32>) {
+        if size == self.size {
+            return;
+        }
         self.size = size;
         self.grid
             .resize(self.size.width as usize, self.size.height as usize);
         self.sdf_builder_tree.grid_needs_updating = true;
     }
 
+    fn on_activate(&mut self) {
+        // The scene can have been replaced from a preset or a screenshot meanwhile
+        self.sdf_builder_tree.grid_needs_updating = true;
+    }
+
+    fn on_deactivate(&mut self) {
+        // The cursor will have moved on by the time the controller is back
+        self.grab_type = GrabType::None;
+        self.grabbing = None;
+    }
+
     fn mouse_move(&mut self, position: PhysicalPosition<f64>) {
         self.cursor = vec2(position.x as f32, position.y as f32);
         let cursor = self.cursor_from_pixels();
@@ -113,27 +135,26 @@ impl crate::controller::Controller for Controller {
             &self.original_selected_item,
             self.sdf_builder_tree.selected_item.id,
         ) {
-            let item: Item = match self.grab_type {
-                GrabType::Move => match item {
-                    Item::Shape(shape, transform) => Item::Shape(
-                        *shape,
-                        Transform {
-                            position: transform.position - (position - cursor),
-                        },
-                    ),
-                    Item::Operator(_, _) => todo!(),
-                },
-                GrabType::Resize => match item {
-                    Item::Shape(shape, transform) => Item::Shape(
-                        shape.resize(
-                            position - transform.position,
-                            cursor - transform.position,
-                            derivative,
-                        ),
-                        *transform,
+            // Only shapes can be grabbed
+            let Item::Shape(shape, transform) = item else {
+                return;
+            };
+            let item = match self.grab_type {
+                GrabType::Move => Item::Shape(
+                    *shape,
+                    Transform {
+                        position: transform.position - (position - cursor),
+                        ..*transform
+                    },
+                ),
+                GrabType::Resize => Item::Shape(
+                    shape.resize(
+                        transform.inverse_transform_point(position),
+                        transform.inverse_transform_point(cursor),
+                        derivative,
                     ),
-                    _ => todo!(),
-                },
+                    *transform,
+                ),
                 GrabType::None => unimplemented!(),
             };
             self.sdf_builder_tree
@@ -153,15 +174,16 @@ impl crate::controller::Controller for Controller {
                         self.original_selected_item =
                             self.sdf_builder_tree.get_selected_item().cloned();
                     }
-                    self.last_mouse_press = (self.cursor, Instant::now());
+                    self.last_mouse_press = (self.cursor, self.active_time);
                     true
                 }
                 ElementState::Released => {
                     self.grab_type = GrabType::None;
                     self.grabbing = None;
-                    let (press_position, instant) = self.last_mouse_press;
-                    if press_position.distance_squared(self.cursor) < 4.0
-                        && instant.elapsed() < Duration::from_millis(300)
+                    let (press_position, press_time) = self.last_mouse_press;
+                    let max_distance = 2.0 * self.scale_factor as f32;
+                    if press_position.distance_squared(self.cursor) < max_distance * max_distance
+                        && self.active_time - press_time < 0.3
                     {
                         self.sdf_builder_tree
                             .send_command(Command::SetSelectedItem(self.get_item_for_selection()));
@@ -172,10 +194,12 @@ impl crate::controller::Controller for Controller {
         }
     }
 
-    fn update(&mut self) {
+    fn update(&mut self, ctx: &FrameContext) {
+        self.active_time += ctx.dt;
+        self.scale_factor = ctx.scale_factor;
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
-            time: self.start.elapsed().as_secs_f32(),
+            time: ctx.time,
             mouse_button_pressed: (self.mouse_button_pressed && self.grabbing.is_none()).into(),
             cursor: self.cursor_from_pixels().into(),
             selected_id: self
@@ -184,7 +208,8 @@ impl crate::controller::Controller for Controller {
                 .id
                 .map(|id| id.0)
                 .unwrap_or(0),
-        }
+        };
+        self.palette.update();
     }
 
     fn push_constants(&self) -> &[u8] {
@@ -207,10 +232,11 @@ impl crate::controller::Controller for Controller {
                 }
                 GrabType::None => {}
             }
-        } else if let Some(item) = &self.sdf_builder_tree.get_selected_item() {
-            if let Item::Shape(shape, transform) = item {
-                self.set_grab_type(ctx, *shape, self.cursor_from_pixels() - transform.position);
-            }
+        } else if let Some(Item::Shape(shape, transform)) =
+            self.sdf_builder_tree.get_selected_item()
+        {
+            let position = transform.inverse_transform_point(self.cursor_from_pixels());
+            self.set_grab_type(ctx, *shape, position);
         } else {
             self.grab_type = GrabType::None;
             ctx.set_cursor_icon(CursorIcon::Default);
@@ -218,25 +244,64 @@ impl crate::controller::Controller for Controller {
 
         self.sdf_builder_tree
             .ui(ui, &self.texture_handles, self.size);
-        if self.sdf_builder_tree.grid_needs_updating {
-            self.instructions = self.sdf_builder_tree.generate_instructions();
-            self.grid.update(&SdfInstructions::new(&self.instructions));
-            if event_proxy.send_event(UserEvent::NewBuffersReady).is_err() {
-                panic!("Event loop dead");
-            }
-            self.sdf_builder_tree.grid_needs_updating = false;
+        if self.prepare_buffers() && event_proxy.send_event(UserEvent::NewBuffersReady).is_err() {
+            panic!("Event loop dead");
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Export SVG").clicked() {
+            self.export_svg();
+        }
+        ui.separator();
+        self.palette.ui(ui);
+    }
+
+    fn prepare_buffers(&mut self) -> bool {
+        if !self.sdf_builder_tree.grid_needs_updating {
+            return false;
         }
+        self.instructions = self.sdf_builder_tree.generate_instructions();
+        self.grid.update(&SdfInstructions::new(&self.instructions));
+        self.sdf_builder_tree.grid_needs_updating = false;
+        true
     }
 
     fn buffers(&self) -> BufferData<'_> {
         BufferData {
-            bind_group_buffers: vec![BindGroupBufferType::SSBO(SSBO {
-                data: bytemuck::cast_slice(&self.grid.buffer[..]),
-                read_only: true,
-            })],
+            bind_group_buffers: vec![
+                BindGroupBufferType::SSBO(SSBO {
+                    data: bytemuck::cast_slice(&self.grid.buffer[..]),
+                    read_only: true,
+                    per_frame: false,
+                }),
+                self.palette.buffer(),
+            ],
             ..Default::default()
         }
     }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        vec![
+            Param::text("scene", &mut self.sdf_builder_tree),
+            Param::choice("palette", &mut self.palette.choice),
+        ]
+    }
+
+    fn distance_field(&self) -> Option<Vec<f32>> {
+        let sdf = SdfInstructions::new(&self.instructions);
+        Some(sample_pixels(self.size, |p| sdf.signed_distance(p).d))
+    }
+
+    fn probe(&self) -> Option<Probe> {
+        let position = self.cursor_from_pixels();
+        let WrappedDistance { d, data } =
+            SdfInstructions::new(&self.instructions).signed_distance(position);
+        let probe = Probe::new(position).with("distance", d);
+        Some(if d == f32::INFINITY {
+            probe
+        } else {
+            probe.with("item", data.0)
+        })
+    }
 }
 
 impl Controller {
@@ -247,9 +312,10 @@ impl Controller {
     fn derivative_at_cursor(&self) -> Vec2 {
         if let Some(item) = &self.sdf_builder_tree.get_selected_item() {
             match item {
-                Item::Shape(shape, transform) => {
-                    shape.derivative(self.cursor_from_pixels() - transform.position, 0.01)
-                }
+                Item::Shape(shape, transform) => shape.derivative(
+                    transform.inverse_transform_point(self.cursor_from_pixels()),
+                    0.01,
+                ),
                 _ => Vec2::ZERO,
             }
         } else {
@@ -263,6 +329,11 @@ impl Controller {
             self.derivative_at_cursor(),
             |Grabbing { derivative, .. }| derivative,
         );
+        // The derivative is in the space of the shape
+        let d = match self.sdf_builder_tree.get_selected_item() {
+            Some(Item::Shape(_, transform)) => Vec2::from_angle(transform.rotation).rotate(d),
+            _ => d,
+        };
         let slope = d.y / d.x;
         if slope > 1.0 / H && slope < H {
             CursorIcon::ResizeNeSw
@@ -289,6 +360,11 @@ impl Controller {
                     .into_iter()
                     .map(|icon| ctx.load_texture("logo", icon, Default::default()))
                     .collect(),
+                modifiers: icon_images
+                    .modifiers
+                    .into_iter()
+                    .map(|icon| ctx.load_texture("logo", icon, Default::default()))
+                    .collect(),
             }
         }
     }
@@ -311,6 +387,10 @@ impl Controller {
                     GrabType::None
                 }
             }
+            Shape::Triangle(Triangle { a, b, c }) => {
+                self.vertex_grab_type(ctx, d, position, &[a, b, c])
+            }
+            Shape::Polygon(polygon) => self.vertex_grab_type(ctx, d, position, &polygon.vertices),
             Shape::Plane(_) => {
                 if d < 0.001 {
                     ctx.set_cursor_icon(CursorIcon::Grab);
@@ -344,6 +424,25 @@ impl Controller {
         }
     }
 
+    /// Vertices of a polygon resize it and the inside moves it.
+    fn vertex_grab_type(
+        &self,
+        ctx: &Context,
+        d: f32,
+        position: Vec2,
+        vertices: &[Vec2],
+    ) -> GrabType {
+        if vertices.iter().any(|v| v.distance(position) < 0.01) {
+            ctx.set_cursor_icon(self.choose_resize_cursor());
+            GrabType::Resize
+        } else if d < 0.0 {
+            ctx.set_cursor_icon(CursorIcon::Grab);
+            GrabType::Move
+        } else {
+            GrabType::None
+        }
+    }
+
     fn get_item_for_selection(&self) -> SelectedItem {
         let wrapped_distance = self.grid.signed_distance(self.cursor_from_pixels());
         if wrapped_distance.d == f32::INFINITY {
@@ -352,4 +451,40 @@ impl Controller {
             wrapped_distance.data.into()
         }
     }
+
+    /// Exports the shapes as exact SVG elements if the tree only has unions
+    /// of shapes with a closed form, or else the contours of the scene.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_svg(&self) {
+        use crate::svg::{self, Element, Svg};
+        let aspect_ratio = self.size.width as f32 / self.size.height as f32;
+        let mut svg = Svg::new(aspect_ratio);
+        let exact = self.sdf_builder_tree.union_of_shapes().and_then(|shapes| {
+            shapes
+                .into_iter()
+                .map(|(id, shape, transform)| Some((id, Element::from_shape(&shape)?, transform)))
+                .collect::<Option<Vec<_>>>()
+        });
+        if let Some(elements) = exact {
+            for (id, element, transform) in &elements {
+                svg.add(Some(&id.element_id()), element, transform);
+            }
+        } else {
+            // Each contour belongs to the item closest to its start
+            let sdf = SdfInstructions::new(&self.instructions);
+            let mut items: Vec<(ItemId, Vec<_>)> = vec![];
+            for contour in svg::contours(aspect_ratio, |p| sdf.signed_distance(p).d) {
+                let id = sdf.signed_distance(contour.points[0]).data;
+                match items.iter_mut().find(|(item_id, _)| *item_id == id) {
+                    Some((_, contours)) => contours.push(contour),
+                    None => items.push((id, vec![contour])),
+                }
+            }
+            for (id, contours) in items {
+                let element = Element::Contours(contours);
+                svg.add(Some(&id.element_id()), &element, &Transform::IDENTITY);
+            }
+        }
+        svg.save("SdfBuilder");
+    }
 }
@@ -0,0 +1,126 @@
+use crate::egui_components::repetition::{Repetition, RepetitionValue};
+use glam::*;
+use shared::sdf_interpreter::{self, DistanceModifier, PointModifier};
+
+/// A node of [SdfBuilderTree](super::sdf_builder_tree::SdfBuilderTree)
+/// changing the domain or the distance of the union of its children.
+#[derive(Clone, Copy, Debug, PartialEq, strum::IntoStaticStr)]
+pub enum Modifier {
+    Repetition(Repetition),
+    Mirror(BVec2),
+    Elongation(Vec2),
+    Onion(f32),
+    Rounding(f32),
+    Displacement { amplitude: f32, frequency: f32 },
+}
+
+impl Modifier {
+    /// Every modifier, with default parameters.
+    pub fn all() -> [Self; 6] {
+        [
+            Self::Repetition(Repetition {
+                current: RepetitionValue::Unlimited,
+                ..Default::default()
+            }),
+            Self::Mirror(BVec2::new(true, false)),
+            Self::Elongation(vec2(0.1, 0.0)),
+            Self::Onion(0.02),
+            Self::Rounding(0.05),
+            Self::Displacement {
+                amplitude: 0.03,
+                frequency: 10.0,
+            },
+        ]
+    }
+
+    pub fn point_modifier(&self) -> Option<PointModifier> {
+        use sdf_interpreter::Repetition as R;
+        use Modifier::*;
+        match *self {
+            Repetition(repetition) => {
+                let repetition = match repetition.current {
+                    RepetitionValue::None => return None,
+                    RepetitionValue::Unlimited => R::Unlimited(repetition.unlimited),
+                    RepetitionValue::Limited => {
+                        let (dim, n1, n2) = repetition.limited;
+                        R::Limited(dim, n1, n2)
+                    }
+                    RepetitionValue::Rectangular => {
+                        let (dim, n) = repetition.rectangular;
+                        R::Rectangular(dim, n)
+                    }
+                    RepetitionValue::Angular => {
+                        let (radius, n) = repetition.angular;
+                        R::Angular(radius, n)
+                    }
+                    RepetitionValue::Mirrored => R::Mirrored(repetition.mirrored),
+                };
+                Some(PointModifier::Repeat(repetition))
+            }
+            Mirror(axes) => Some(PointModifier::Mirror(axes)),
+            Elongation(h) => Some(PointModifier::Elongate(h)),
+            Onion(_) | Rounding(_) | Displacement { .. } => None,
+        }
+    }
+
+    pub fn distance_modifier(&self) -> Option<DistanceModifier> {
+        use Modifier::*;
+        match *self {
+            Onion(r) => Some(DistanceModifier::Onion(r)),
+            Rounding(r) => Some(DistanceModifier::Round(r)),
+            Displacement {
+                amplitude,
+                frequency,
+            } => Some(DistanceModifier::Displace {
+                amplitude,
+                frequency,
+            }),
+            Repetition(_) | Mirror(_) | Elongation(_) => None,
+        }
+    }
+
+    /// The ui for the parameters of the modifier.
+    pub fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        use Modifier::*;
+        let distance = |ui: &mut egui::Ui, label: &str, x: &mut f32| {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add(
+                    egui::DragValue::new(x)
+                        .clamp_range(0.0..=f64::INFINITY)
+                        .speed(0.005),
+                );
+            });
+        };
+        match &mut self {
+            Repetition(repetition) => repetition.ui(ui),
+            Mirror(axes) => {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut axes.x, "x");
+                    ui.checkbox(&mut axes.y, "y");
+                });
+            }
+            Elongation(h) => {
+                distance(ui, "x", &mut h.x);
+                distance(ui, "y", &mut h.y);
+            }
+            Onion(r) => distance(ui, "Thickness", r),
+            Rounding(r) => distance(ui, "Radius", r),
+            Displacement {
+                amplitude,
+                frequency,
+            } => {
+                distance(ui, "Amplitude", amplitude);
+                ui.horizontal(|ui| {
+                    ui.label("Frequency");
+                    ui.add(
+                        egui::DragValue::new(frequency)
+                            .clamp_range(0.1..=100.0)
+                            .speed(0.1),
+                    );
+                });
+            }
+        }
+        self
+    }
+}
@@ -84,6 +84,93 @@ impl Resize for Ray {
     }
 }
 
+impl Resize for Capsule {
+    fn resize(mut self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative;
+        self.radius = (self.radius + s.x + s.y).max(0.0);
+        self
+    }
+}
+
+impl Resize for IsoscelesTriangle {
+    fn resize(self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative.signum();
+        IsoscelesTriangle::new((self.width + 2.0 * s.x).max(0.0), self.height + s.y)
+    }
+}
+
+impl Resize for Triangle {
+    fn resize(mut self, initial: Vec2, current: Vec2, _derivative: Vec2) -> Self {
+        let mut vertices = [self.a, self.b, self.c];
+        vertices[closest_vertex(initial, &vertices)] += current - initial;
+        [self.a, self.b, self.c] = vertices;
+        self
+    }
+}
+
+impl<const N: usize> Resize for Polygon<N> {
+    fn resize(mut self, initial: Vec2, current: Vec2, _derivative: Vec2) -> Self {
+        self.vertices[closest_vertex(initial, &self.vertices)] += current - initial;
+        self
+    }
+}
+
+impl Resize for EquilateralTriangle {
+    fn resize(mut self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative;
+        self.radius = (self.radius + s.x + s.y).max(0.0);
+        self
+    }
+}
+
+impl Resize for Hexagon {
+    fn resize(mut self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative;
+        self.radius = (self.radius + s.x + s.y).max(0.0);
+        self
+    }
+}
+
+impl Resize for Pentagon {
+    fn resize(mut self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative;
+        self.radius = (self.radius + s.x + s.y).max(0.0);
+        self
+    }
+}
+
+impl Resize for RegularPolygon {
+    fn resize(mut self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative;
+        self.radius = (self.radius + s.x + s.y).max(0.0);
+        self
+    }
+}
+
+impl Resize for RegularStar {
+    fn resize(mut self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative;
+        self.radius = (self.radius + s.x + s.y).max(0.0);
+        self
+    }
+}
+
+impl Resize for SierpinskiTriangle {
+    fn resize(mut self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative;
+        self.radius = (self.radius + s.x + s.y).max(0.0);
+        self
+    }
+}
+
+impl Resize for KochSnowflake {
+    fn resize(mut self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
+        let s = (current - initial) * derivative;
+        self.radius = (self.radius + s.x + s.y).max(0.0);
+        self
+    }
+}
+
 impl Resize for Shape {
     fn resize(self, initial: Vec2, current: Vec2, derivative: Vec2) -> Self {
         match self {
@@ -94,6 +181,24 @@ impl Resize for Shape {
             Shape::LineSegment(shape) => shape.resize(initial, current, derivative).into(),
             Shape::Plane(shape) => shape.resize(initial, current, derivative).into(),
             Shape::Ray(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::Capsule(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::EquilateralTriangle(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::IsoscelesTriangle(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::Triangle(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::Polygon(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::Hexagon(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::Pentagon(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::RegularPolygon(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::RegularStar(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::SierpinskiTriangle(shape) => shape.resize(initial, current, derivative).into(),
+            Shape::KochSnowflake(shape) => shape.resize(initial, current, derivative).into(),
         }
     }
 }
+
+/// The index of the vertex closest to `p`, the one being dragged.
+fn closest_vertex(p: Vec2, vertices: &[Vec2]) -> usize {
+    (0..vertices.len())
+        .min_by(|&i, &j| vertices[i].distance(p).total_cmp(&vertices[j].distance(p)))
+        .unwrap()
+}
@@ -0,0 +1,282 @@
+//! The text form of the items of an [SdfBuilderTree](super::sdf_builder_tree::SdfBuilderTree),
+//! stored in screenshots and presets. Every item is written as its name
+//! followed by its values, with the children of operators and modifiers in
+//! parentheses and the values of shapes followed by those of their transform,
+//! e.g. `Disk 0.2 0 0 0 1 1 1 BlendUnion Smooth 0.05 ( Rectangle 0.4 0.3 0.1 0 0 1 1 1 )`.
+
+use super::{
+    modifier::Modifier,
+    sdf_builder_tree::{Item, ItemId},
+};
+use crate::params::Param;
+use dfutils::primitives_enum::Shape;
+use glam::Vec2;
+use shared::sdf_interpreter::{Operator, Transform};
+use std::{collections::HashMap, iter::Peekable, str::SplitWhitespace};
+use strum::IntoEnumIterator;
+
+type Tokens<'a> = Peekable<SplitWhitespace<'a>>;
+
+/// Writes the items with the given ids and their descendants.
+pub fn write(items: &HashMap<ItemId, Item>, ids: &[ItemId]) -> String {
+    let mut tokens = vec![];
+    for id in ids {
+        write_item(items, *id, &mut tokens);
+    }
+    tokens.join(" ")
+}
+
+/// Reads items written by [`write`], returning them with the ids of the
+/// outermost ones.
+pub fn read(s: &str) -> Result<(HashMap<ItemId, Item>, Vec<ItemId>), String> {
+    let s = s.replace('(', " ( ").replace(')', " ) ");
+    let mut tokens = s.split_whitespace().peekable();
+    let mut items = HashMap::new();
+    let mut ids = vec![];
+    while tokens.peek().is_some() {
+        ids.push(read_item(&mut items, &mut tokens)?);
+    }
+    Ok((items, ids))
+}
+
+fn write_item(items: &HashMap<ItemId, Item>, id: ItemId, tokens: &mut Vec<String>) {
+    let Some(item) = items.get(&id) else {
+        return;
+    };
+    let children = match item.clone() {
+        Item::Shape(mut shape, mut transform) => {
+            tokens.push(<&str>::from(&shape).to_string());
+            write_values(tokens, shape_params(&mut shape));
+            write_values(tokens, transform_params(&mut transform));
+            return;
+        }
+        Item::Operator(mut op, children) => {
+            tokens.push(<&str>::from(&op).to_string());
+            write_values(tokens, operator_params(&mut op));
+            children
+        }
+        Item::Modifier(mut modifier, children) => {
+            tokens.push(<&str>::from(&modifier).to_string());
+            write_values(tokens, modifier_params(&mut modifier));
+            children
+        }
+    };
+    tokens.push("(".to_string());
+    for child in children {
+        write_item(items, child, tokens);
+    }
+    tokens.push(")".to_string());
+}
+
+fn read_item(items: &mut HashMap<ItemId, Item>, tokens: &mut Tokens) -> Result<ItemId, String> {
+    let name = next(tokens)?;
+    let item = if let Some(mut shape) = Shape::iter().find(|shape| <&str>::from(shape) == name) {
+        let mut transform = Transform::default();
+        read_values(&mut shape, shape_params, tokens)?;
+        read_values(&mut transform, transform_params, tokens)?;
+        Item::Shape(shape, transform)
+    } else if let Some(mut op) = Operator::all().find(|op| <&str>::from(op) == name) {
+        read_values(&mut op, operator_params, tokens)?;
+        Item::Operator(op, read_children(items, tokens)?)
+    } else if let Some(mut modifier) = Modifier::all()
+        .into_iter()
+        .find(|modifier| <&str>::from(modifier) == name)
+    {
+        read_values(&mut modifier, modifier_params, tokens)?;
+        Item::Modifier(modifier, read_children(items, tokens)?)
+    } else {
+        return Err(format!("unknown item `{name}`"));
+    };
+    let id = ItemId::new();
+    items.insert(id, item);
+    Ok(id)
+}
+
+fn read_children(
+    items: &mut HashMap<ItemId, Item>,
+    tokens: &mut Tokens,
+) -> Result<Vec<ItemId>, String> {
+    let token = next(tokens)?;
+    if token != "(" {
+        return Err(format!("expected `(`, got `{token}`"));
+    }
+    let mut children = vec![];
+    while tokens.next_if_eq(&")").is_none() {
+        children.push(read_item(items, tokens)?);
+    }
+    Ok(children)
+}
+
+fn next<'a>(tokens: &mut Tokens<'a>) -> Result<&'a str, String> {
+    tokens
+        .next()
+        .ok_or_else(|| "unexpected end of the scene".to_string())
+}
+
+fn write_values(tokens: &mut Vec<String>, params: Vec<Param>) {
+    tokens.extend(params.iter().map(Param::value_string));
+}
+
+/// Sets the parameters of `x` from the next tokens. The parameters are listed
+/// again after every value, as they can depend on the earlier ones.
+fn read_values<T>(
+    x: &mut T,
+    params: fn(&mut T) -> Vec<Param<'_>>,
+    tokens: &mut Tokens,
+) -> Result<(), String> {
+    let mut i = 0;
+    while let Some(mut param) = params(x).into_iter().nth(i) {
+        param.set(next(tokens)?)?;
+        i += 1;
+    }
+    Ok(())
+}
+
+fn length<'a>(name: &'static str, x: &'a mut f32) -> Param<'a> {
+    Param::f32(name, x, 0.0..=f32::INFINITY)
+}
+
+fn point<'a>(name: &str, p: &'a mut Vec2) -> [Param<'a>; 2] {
+    [
+        Param::unbounded(format!("{name}_x"), &mut p.x),
+        Param::unbounded(format!("{name}_y"), &mut p.y),
+    ]
+}
+
+fn shape_params(shape: &mut Shape) -> Vec<Param<'_>> {
+    match shape {
+        Shape::Disk(s) => vec![length("radius", &mut s.radius)],
+        Shape::Torus(s) => vec![
+            length("major_radius", &mut s.major_radius),
+            length("minor_radius", &mut s.minor_radius),
+        ],
+        Shape::Rectangle(s) => vec![
+            length("width", &mut s.width),
+            length("height", &mut s.height),
+        ],
+        Shape::Cross(s) => vec![
+            length("length", &mut s.length),
+            length("thickness", &mut s.thickness),
+        ],
+        Shape::Plane(s) => point("normal", &mut s.normal).into(),
+        Shape::Ray(s) => point("direction", &mut s.direction).into(),
+        Shape::LineSegment(s) => [point("a", &mut s.a), point("b", &mut s.b)]
+            .into_iter()
+            .flatten()
+            .collect(),
+        Shape::Capsule(s) => [point("a", &mut s.a), point("b", &mut s.b)]
+            .into_iter()
+            .flatten()
+            .chain([length("radius", &mut s.radius)])
+            .collect(),
+        Shape::EquilateralTriangle(s) => vec![length("radius", &mut s.radius)],
+        Shape::IsoscelesTriangle(s) => vec![
+            length("width", &mut s.width),
+            Param::unbounded("height", &mut s.height),
+        ],
+        Shape::Triangle(s) => [
+            point("a", &mut s.a),
+            point("b", &mut s.b),
+            point("c", &mut s.c),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        Shape::Polygon(s) => s
+            .vertices
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, vertex)| point(&format!("v{i}"), vertex))
+            .collect(),
+        Shape::Hexagon(s) => vec![length("radius", &mut s.radius)],
+        Shape::Pentagon(s) => vec![length("radius", &mut s.radius)],
+        Shape::RegularPolygon(s) => vec![
+            length("radius", &mut s.radius),
+            Param::u32("n", &mut s.n, 3..=12),
+        ],
+        Shape::RegularStar(s) => vec![
+            length("radius", &mut s.radius),
+            Param::u32("n", &mut s.n, 3..=12),
+            Param::f32("sharpness", &mut s.sharpness, 0.0..=1.0),
+        ],
+        Shape::SierpinskiTriangle(s) => vec![
+            length("radius", &mut s.radius),
+            Param::u32("iterations", &mut s.iterations, 0..=8),
+        ],
+        Shape::KochSnowflake(s) => vec![
+            length("radius", &mut s.radius),
+            Param::u32("iterations", &mut s.iterations, 0..=5),
+        ],
+    }
+}
+
+fn transform_params(transform: &mut Transform) -> Vec<Param<'_>> {
+    let Transform {
+        position,
+        rotation,
+        scale,
+        stretch,
+    } = transform;
+    let mut params = Vec::from(point("position", position));
+    params.extend([
+        Param::unbounded("rotation", rotation),
+        length("scale", scale),
+    ]);
+    params.extend(point("stretch", stretch));
+    params
+}
+
+fn operator_params(op: &mut Operator) -> Vec<Param<'_>> {
+    use Operator::*;
+    match op {
+        Union | Intersect | Subtract | Xor => vec![],
+        BlendUnion(blend, r) | BlendIntersect(blend, r) | BlendSubtract(blend, r) => {
+            vec![Param::choice("blend", blend), length("radius", r)]
+        }
+    }
+}
+
+fn modifier_params(modifier: &mut Modifier) -> Vec<Param<'_>> {
+    use Modifier::*;
+    match modifier {
+        Repetition(repetition) => repetition.params(),
+        Mirror(axes) => vec![Param::bool("x", &mut axes.x), Param::bool("y", &mut axes.y)],
+        Elongation(h) => point("elongation", h).into(),
+        Onion(r) => vec![length("thickness", r)],
+        Rounding(r) => vec![length("radius", r)],
+        Displacement {
+            amplitude,
+            frequency,
+        } => vec![
+            length("amplitude", amplitude),
+            Param::f32("frequency", frequency, 0.1..=100.0),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let scene = "Disk 0.2 0.1 -0.3 0.5 1 1 2 \
+            BlendSubtract Chamfer 0.05 ( Rectangle 0.4 0.3 0 0 0 1 1 1 \
+            Repetition Angular 5 0.3 ( Triangle -0.1 -0.2 0.3 0.2 0.2 -0.3 0 0 0 2 1 1 ) \
+            Mirror true false ( ) )";
+        let (items, ids) = read(scene).unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(items.len(), 6);
+        assert!(matches!(
+            items[&ids[1]],
+            Item::Operator(Operator::BlendSubtract(dfutils::sdf::Blend::Chamfer, _), _)
+        ));
+        assert_eq!(write(&items, &ids), scene);
+
+        assert!(read("Disk").is_err());
+        assert!(read("Disk -1 0 0 0 1 1 1").is_err());
+        assert!(read("Sphere 1").is_err());
+        assert!(read("Union ( Disk 0.2 0 0 0 1 1 1").is_err());
+        assert!(read("Union Disk 0.2 0 0 0 1 1 1").is_err());
+    }
+}
@@ -1,5 +1,6 @@
-use super::{icons::TextureHandles, shape_ui::ShapeUi};
-use dfutils::primitives_enum::Shape;
+use super::{icons::TextureHandles, modifier::Modifier, scene, shape_ui::ShapeUi};
+use crate::params::TextParam;
+use dfutils::{primitives_enum::Shape, sdf::Blend};
 use egui::{load::SizedTexture, NumExt as _, TextureHandle};
 use egui_winit::winit::dpi::PhysicalSize;
 use glam::*;
@@ -17,9 +18,14 @@ use strum::IntoEnumIterator;
 pub struct ItemId(pub u32);
 
 impl ItemId {
-    fn new() -> Self {
+    pub(super) fn new() -> Self {
         Self(rand::random())
     }
+
+    /// The id of the item in exported SVG.
+    pub fn element_id(&self) -> String {
+        format!("item-{:08x}", self.0)
+    }
 }
 
 impl std::fmt::Debug for ItemId {
@@ -37,9 +43,27 @@ impl From<ItemId> for egui::Id {
 #[derive(Clone, Debug)]
 pub enum Item {
     Operator(Operator, Vec<ItemId>),
+    /// Modifies the union of its children.
+    Modifier(Modifier, Vec<ItemId>),
     Shape(Shape, Transform),
 }
 
+impl Item {
+    fn children(&self) -> Option<&Vec<ItemId>> {
+        match self {
+            Item::Operator(_, children) | Item::Modifier(_, children) => Some(children),
+            Item::Shape(_, _) => None,
+        }
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<ItemId>> {
+        match self {
+            Item::Operator(_, children) | Item::Modifier(_, children) => Some(children),
+            Item::Shape(_, _) => None,
+        }
+    }
+}
+
 impl From<Shape> for Item {
     fn from(shape: Shape) -> Self {
         Item::Shape(shape, Default::default())
@@ -52,6 +76,12 @@ impl From<Operator> for Item {
     }
 }
 
+impl From<Modifier> for Item {
+    fn from(modifier: Modifier) -> Self {
+        Item::Modifier(modifier, Default::default())
+    }
+}
+
 #[derive(Debug)]
 pub struct SelectedItem {
     pub id: Option<ItemId>,
@@ -186,15 +216,36 @@ impl SdfBuilderTree {
         children
     }
 
+    /// The shapes of the tree, if it only has unions.
+    pub fn union_of_shapes(&self) -> Option<Vec<(ItemId, Shape, Transform)>> {
+        let mut shapes = Vec::new();
+        self.union_of_shapes_impl(self.root_id, &mut shapes)
+            .then_some(shapes)
+    }
+
+    fn union_of_shapes_impl(
+        &self,
+        id: ItemId,
+        shapes: &mut Vec<(ItemId, Shape, Transform)>,
+    ) -> bool {
+        match self.items.get(&id) {
+            Some(Item::Operator(Operator::Union, children)) => children
+                .iter()
+                .all(|child_id| self.union_of_shapes_impl(*child_id, shapes)),
+            Some(Item::Shape(shape, transform)) => {
+                shapes.push((id, *shape, *transform));
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn get_selected_item(&self) -> Option<&Item> {
         self.selected_item.id.and_then(|id| self.items.get(&id))
     }
 
     fn container(&self, id: ItemId) -> Option<&Vec<ItemId>> {
-        match self.items.get(&id) {
-            Some(Item::Operator(_, children)) => Some(children),
-            _ => None,
-        }
+        self.items.get(&id).and_then(Item::children)
     }
 
     /// Does some container contain the given item?
@@ -229,7 +280,11 @@ impl SdfBuilderTree {
         // Remove the item from its current location. Note: we must adjust the target position if the item is
         // moved within the same container, as the removal might shift the positions by one.
         if let Some((source_parent_id, source_pos)) = self.parent_and_pos(item_id) {
-            if let Some(Item::Operator(_, children)) = self.items.get_mut(&source_parent_id) {
+            if let Some(children) = self
+                .items
+                .get_mut(&source_parent_id)
+                .and_then(Item::children_mut)
+            {
                 children.remove(source_pos);
             }
 
@@ -238,7 +293,11 @@ impl SdfBuilderTree {
             }
         }
 
-        if let Some(Item::Operator(_, children)) = self.items.get_mut(&container_id) {
+        if let Some(children) = self
+            .items
+            .get_mut(&container_id)
+            .and_then(Item::children_mut)
+        {
             children.insert(pos.at_most(children.len()), item_id);
         }
     }
@@ -249,7 +308,11 @@ impl SdfBuilderTree {
 
         self.items.insert(item_id, item);
 
-        if let Some(Item::Operator(_, children)) = self.items.get_mut(&container_id) {
+        if let Some(children) = self
+            .items
+            .get_mut(&container_id)
+            .and_then(Item::children_mut)
+        {
             children.insert(pos.at_most(children.len()), item_id);
         }
     }
@@ -267,7 +330,7 @@ impl SdfBuilderTree {
 
         let item = self.items.get(&item_id).unwrap();
         match item {
-            Item::Operator(_, items) => {
+            Item::Operator(_, items) | Item::Modifier(_, items) => {
                 for id in items {
                     self.send_command(Command::RemoveItem { item_id: *id })
                 }
@@ -276,7 +339,7 @@ impl SdfBuilderTree {
         }
         if let Some((id, pos)) = self.parent_and_pos(item_id) {
             match self.items.get_mut(&id).unwrap() {
-                Item::Operator(_, items) => {
+                Item::Operator(_, items) | Item::Modifier(_, items) => {
                     items.remove(pos);
                 }
                 Item::Shape(_, _) => {}
@@ -317,7 +380,7 @@ impl SdfBuilderTree {
 
         self.items.insert(id, shape.into());
 
-        if let Some(Item::Operator(_, children)) = self.items.get_mut(&parent_id) {
+        if let Some(children) = self.items.get_mut(&parent_id).and_then(Item::children_mut) {
             children.push(id);
         }
     }
@@ -328,6 +391,29 @@ impl SdfBuilderTree {
     }
 }
 
+//
+// Scene stuff
+//
+impl TextParam for SdfBuilderTree {
+    fn to_text(&self) -> String {
+        scene::write(&self.items, self.get_root_children())
+    }
+
+    fn set_text(&mut self, s: &str) -> Result<(), String> {
+        let (mut items, children) = scene::read(s)?;
+        let root_id = ItemId::new();
+        items.insert(root_id, Item::Operator(Operator::Union, children));
+        self.items = items;
+        self.root_id = root_id;
+        self.selected_item = SelectedItem::NONE;
+        self.extra_item = None;
+        // Commands sent by the ui refer to the old items
+        while self.command_receiver.try_recv().is_ok() {}
+        self.grid_needs_updating = true;
+        Ok(())
+    }
+}
+
 //
 // UI stuff
 //
@@ -337,6 +423,8 @@ impl SdfBuilderTree {
         ui.separator();
         self.operators_ui(ui, &icons.operators);
         ui.separator();
+        self.modifiers_ui(ui, &icons.modifiers);
+        ui.separator();
 
         let top_level_items = self.get_root_children();
         if top_level_items.is_empty() {
@@ -443,7 +531,7 @@ impl SdfBuilderTree {
     fn operators_ui(&mut self, ui: &mut egui::Ui, icons: &[TextureHandle]) {
         egui::Grid::new("operator_icons_grid").show(ui, |ui| {
             for (operator, icon, end_row) in
-                izip!(Operator::iter(), icons, [false, true].into_iter().cycle())
+                izip!(Operator::all(), icons, [false, true].into_iter().cycle())
             {
                 let label: &str = operator.into();
                 let mut frame = egui::Frame::none()
@@ -478,11 +566,42 @@ impl SdfBuilderTree {
         });
     }
 
+    fn modifiers_ui(&self, ui: &mut egui::Ui, icons: &[TextureHandle]) {
+        egui::Grid::new("modifier_icons_grid").show(ui, |ui| {
+            for (modifier, icon, end_row) in
+                izip!(Modifier::all(), icons, [false, true].into_iter().cycle())
+            {
+                let label: &str = modifier.into();
+                let mut frame = egui::Frame::none()
+                    .inner_margin(egui::Margin::same(3.0))
+                    .begin(ui);
+                let response = frame
+                    .content_ui
+                    .vertical_centered(|ui| {
+                        let rect = ui
+                            .label(label)
+                            .rect
+                            .union(ui.image(SizedTexture::from_handle(icon)).rect);
+                        ui.interact(rect, egui::Id::new(label), egui::Sense::click_and_drag())
+                    })
+                    .inner;
+                if response.hovered() {
+                    frame.frame.stroke = egui::Stroke::new(1.0, egui::Color32::DARK_GRAY);
+                }
+                frame.end(ui);
+                self.handle_new_item_drag(ui, &response, modifier.into());
+                if end_row {
+                    ui.end_row();
+                }
+            }
+        });
+    }
+
     fn container_ui(
         &self,
         ui: &mut egui::Ui,
         item_id: ItemId,
-        operator: &Operator,
+        item: &Item,
         children: &Vec<ItemId>,
     ) {
         let (response, head_response, body_resp) =
@@ -492,8 +611,13 @@ impl SdfBuilderTree {
                 true,
             )
             .show_header(ui, |ui| {
+                let label: &str = match item {
+                    Item::Operator(operator, _) => operator.into(),
+                    Item::Modifier(modifier, _) => modifier.into(),
+                    Item::Shape(shape, _) => shape.into(),
+                };
                 let resp = ui.add(
-                    egui::Label::new(format!("{operator:?}"))
+                    egui::Label::new(label)
                         .selectable(false)
                         .sense(egui::Sense::click_and_drag()),
                 );
@@ -508,6 +632,40 @@ impl SdfBuilderTree {
                 .union(resp)
             })
             .body(|ui| {
+                if let Item::Modifier(modifier, _) = item {
+                    let new_modifier = ui.push_id(item_id, |ui| modifier.ui(ui)).inner;
+                    if new_modifier != *modifier {
+                        self.send_command(Command::EditItem {
+                            item: Item::Modifier(new_modifier, children.clone()),
+                            item_id,
+                        });
+                    }
+                }
+                if let Item::Operator(operator, _) = item
+                    && let Some((mut blend, mut r)) = operator.blend()
+                {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source(("blend", item_id.0))
+                            .selected_text(Into::<&str>::into(blend))
+                            .show_ui(ui, |ui| {
+                                for b in Blend::iter() {
+                                    ui.selectable_value(&mut blend, b, Into::<&str>::into(b));
+                                }
+                            });
+                        ui.add(
+                            egui::DragValue::new(&mut r)
+                                .clamp_range(0.0..=f64::INFINITY)
+                                .speed(0.005),
+                        );
+                    });
+                    let new_operator = operator.with_blend(blend, r);
+                    if new_operator != *operator {
+                        self.send_command(Command::EditItem {
+                            item: Item::Operator(new_operator, children.clone()),
+                            item_id,
+                        });
+                    }
+                }
                 self.container_children_ui(ui, children);
             });
 
@@ -549,8 +707,8 @@ impl SdfBuilderTree {
             };
 
             match self.items.get(child_id) {
-                Some(Item::Operator(operator, children)) => {
-                    self.container_ui(ui, *child_id, operator, children);
+                Some(item @ (Item::Operator(_, children) | Item::Modifier(_, children))) => {
+                    self.container_ui(ui, *child_id, item, children);
                 }
                 Some(Item::Shape(shape, transform)) => {
                     self.leaf_ui(ui, *child_id, *shape, *transform);
@@ -592,6 +750,25 @@ impl SdfBuilderTree {
                     ui.label("pos");
                     ui.add(egui::DragValue::new(&mut new_transform.position.x).speed(0.01));
                     ui.add(egui::DragValue::new(&mut new_transform.position.y).speed(0.01));
+                    ui.end_row();
+                    ui.label("rotation");
+                    ui.drag_angle(&mut new_transform.rotation);
+                    ui.end_row();
+                    ui.label("scale");
+                    ui.add(
+                        egui::DragValue::new(&mut new_transform.scale)
+                            .clamp_range(0.01..=f64::INFINITY)
+                            .speed(0.01),
+                    );
+                    ui.end_row();
+                    ui.label("stretch");
+                    for x in [&mut new_transform.stretch.x, &mut new_transform.stretch.y] {
+                        ui.add(
+                            egui::DragValue::new(x)
+                                .clamp_range(0.01..=f64::INFINITY)
+                                .speed(0.01),
+                        );
+                    }
                     if shape != new_shape || transform != new_transform {
                         self.send_command(Command::EditItem {
                             item: Item::Shape(new_shape, new_transform),
@@ -828,10 +1005,10 @@ impl SdfBuilderTree {
                             return true;
                         }
                     }
-                    let op_to_add = if *op == Operator::Subtract {
-                        Operator::Union
-                    } else {
-                        *op
+                    // Everything after the first child is subtracted from it at once
+                    let op_to_add = match op {
+                        Operator::Subtract | Operator::BlendSubtract(..) => Operator::Union,
+                        _ => *op,
                     };
                     instructions.push(Instruction::Operator(op_to_add));
                     for next_id in items {
@@ -839,13 +1016,42 @@ impl SdfBuilderTree {
                             instructions.push(Instruction::Operator(op_to_add));
                         }
                     }
-                    if *op == Operator::Subtract {
-                        if let Some(Instruction::Operator(op)) = instructions.last_mut() {
-                            *op = Operator::Subtract;
+                    if op_to_add != *op {
+                        if let Some(Instruction::Operator(last)) = instructions.last_mut() {
+                            *last = *op;
                         }
                     }
                     true
                 }
+                Item::Modifier(modifier, ids) => {
+                    let start = instructions.len();
+                    let point_modifier = modifier.point_modifier();
+                    if let Some(point_modifier) = point_modifier {
+                        instructions.push(Instruction::PointModifier(point_modifier, 0));
+                    }
+                    // The children are modified as a union
+                    let mut any = false;
+                    for next_id in ids {
+                        if self.generate_instructions_for_id(next_id, instructions) {
+                            if any {
+                                instructions.push(Instruction::Operator(Operator::Union));
+                            }
+                            any = true;
+                        }
+                    }
+                    if !any {
+                        instructions.truncate(start);
+                        return false;
+                    }
+                    if let Some(point_modifier) = point_modifier {
+                        let len = instructions.len() - start - 1;
+                        instructions[start] = Instruction::PointModifier(point_modifier, len);
+                    }
+                    if let Some(distance_modifier) = modifier.distance_modifier() {
+                        instructions.push(Instruction::DistanceModifier(distance_modifier));
+                    }
+                    true
+                }
                 Item::Shape(shape, transform) => {
                     instructions.push(Instruction::Sdf(SdfWrapper::new(*shape, *id), *transform));
                     true
@@ -861,9 +1067,8 @@ impl SdfBuilderTree {
             if !ui.ui_contains_pointer() && egui::DragAndDrop::has_any_payload(ui.ctx()) {
                 if let Some(Item::Shape(shape, _)) = self.selected_item.new_item {
                     ui.input(|i| i.pointer.latest_pos()).map(|pos| {
-                        let transform = Transform {
-                            position: from_pixels(vec2(pos.x, pos.y), size.into()),
-                        };
+                        let transform =
+                            Transform::from_position(from_pixels(vec2(pos.x, pos.y), size.into()));
                         (shape, transform)
                     })
                 } else {
@@ -93,6 +93,164 @@ impl ShapeUi for Ray {
     }
 }
 
+impl ShapeUi for Capsule {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Radius");
+        ui.add(
+            egui::DragValue::new(&mut self.radius)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        self
+    }
+}
+
+impl ShapeUi for EquilateralTriangle {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Radius");
+        ui.add(
+            egui::DragValue::new(&mut self.radius)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        self
+    }
+}
+
+impl ShapeUi for IsoscelesTriangle {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Width");
+        ui.add(
+            egui::DragValue::new(&mut self.width)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        ui.end_row();
+        ui.label("Height");
+        ui.add(egui::DragValue::new(&mut self.height).speed(0.01));
+        self
+    }
+}
+
+impl ShapeUi for Triangle {
+    fn ui(self, _ui: &mut egui::Ui) -> Self {
+        self
+    }
+}
+
+impl<const N: usize> ShapeUi for Polygon<N> {
+    fn ui(self, _ui: &mut egui::Ui) -> Self {
+        self
+    }
+}
+
+impl ShapeUi for Hexagon {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Radius");
+        ui.add(
+            egui::DragValue::new(&mut self.radius)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        self
+    }
+}
+
+impl ShapeUi for Pentagon {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Radius");
+        ui.add(
+            egui::DragValue::new(&mut self.radius)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        self
+    }
+}
+
+impl ShapeUi for RegularPolygon {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Radius");
+        ui.add(
+            egui::DragValue::new(&mut self.radius)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        ui.end_row();
+        ui.label("N");
+        ui.add(
+            egui::DragValue::new(&mut self.n)
+                .clamp_range(3..=12)
+                .speed(0.1),
+        );
+        self
+    }
+}
+
+impl ShapeUi for RegularStar {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Radius");
+        ui.add(
+            egui::DragValue::new(&mut self.radius)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        ui.end_row();
+        ui.label("N");
+        ui.add(
+            egui::DragValue::new(&mut self.n)
+                .clamp_range(3..=12)
+                .speed(0.1),
+        );
+        ui.end_row();
+        ui.label("Sharpness");
+        ui.add(
+            egui::DragValue::new(&mut self.sharpness)
+                .clamp_range(0.0..=1.0)
+                .speed(0.01),
+        );
+        self
+    }
+}
+
+impl ShapeUi for SierpinskiTriangle {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Radius");
+        ui.add(
+            egui::DragValue::new(&mut self.radius)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        ui.end_row();
+        ui.label("Iterations");
+        ui.add(
+            egui::DragValue::new(&mut self.iterations)
+                .clamp_range(0..=8)
+                .speed(0.1),
+        );
+        self
+    }
+}
+
+impl ShapeUi for KochSnowflake {
+    fn ui(mut self, ui: &mut egui::Ui) -> Self {
+        ui.label("Radius");
+        ui.add(
+            egui::DragValue::new(&mut self.radius)
+                .clamp_range(0.0..=f64::INFINITY)
+                .speed(0.01),
+        );
+        ui.end_row();
+        ui.label("Iterations");
+        ui.add(
+            egui::DragValue::new(&mut self.iterations)
+                .clamp_range(0..=5)
+                .speed(0.1),
+        );
+        self
+    }
+}
+
 impl ShapeUi for Shape {
     fn ui(self, ui: &mut egui::Ui) -> Self {
         match self {
@@ -103,6 +261,17 @@ impl ShapeUi for Shape {
             Shape::LineSegment(shape) => shape.ui(ui).into(),
             Shape::Plane(shape) => shape.ui(ui).into(),
             Shape::Ray(shape) => shape.ui(ui).into(),
+            Shape::Capsule(shape) => shape.ui(ui).into(),
+            Shape::EquilateralTriangle(shape) => shape.ui(ui).into(),
+            Shape::IsoscelesTriangle(shape) => shape.ui(ui).into(),
+            Shape::Triangle(shape) => shape.ui(ui).into(),
+            Shape::Polygon(shape) => shape.ui(ui).into(),
+            Shape::Hexagon(shape) => shape.ui(ui).into(),
+            Shape::Pentagon(shape) => shape.ui(ui).into(),
+            Shape::RegularPolygon(shape) => shape.ui(ui).into(),
+            Shape::RegularStar(shape) => shape.ui(ui).into(),
+            Shape::SierpinskiTriangle(shape) => shape.ui(ui).into(),
+            Shape::KochSnowflake(shape) => shape.ui(ui).into(),
         }
     }
 }
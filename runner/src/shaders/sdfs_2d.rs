@@ -1,12 +1,15 @@
 use crate::{
-    controller::{BindGroupBufferType, BufferData, SSBO},
+    controller::{sample_pixels, BindGroupBufferType, BufferData, FrameContext, SSBO},
     egui_components::{
         enabled_number::EnabledNumber,
         repetition::{Repetition, RepetitionValue},
     },
+    params::Param,
+    probe::Probe,
     window::UserEvent,
 };
 use bytemuck::Zeroable;
+use convert_case::{Case, Casing};
 use egui::{Context, CursorIcon};
 use egui_winit::winit::{
     dpi::{PhysicalPosition, PhysicalSize},
@@ -18,10 +21,7 @@ use sdf::grid::SdfGrid;
 use shared::push_constants::sdfs_2d::{ShaderConstants, MAX_NUM_POINTS};
 use shared::sdf_2d as sdf;
 use shared::{fast_optional::Optional_f32, from_pixels};
-use std::{
-    f32::consts::PI,
-    time::{Duration, Instant},
-};
+use std::f32::consts::PI;
 use strum::IntoEnumIterator;
 
 #[derive(strum::EnumIter, strum::Display, PartialEq, Copy, Clone)]
@@ -200,8 +200,6 @@ struct Params {
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
-    elapsed: Duration,
     cursor: Vec2,
     mouse_button_pressed: bool,
     can_drag: Option<usize>,
@@ -213,7 +211,8 @@ pub struct Controller {
     repeat: Repetition,
     params: Vec<Params>,
     prev_params: Params,
-    grid: SdfGrid,
+    /// Boxed without going through the stack, as it is about 780 KB.
+    grid: Box<SdfGrid>,
     grid_needs_updating: bool,
     smooth: bool,
 }
@@ -222,8 +221,6 @@ impl crate::controller::Controller for Controller {
     fn new(size: PhysicalSize<u32>) -> Self {
         Self {
             size,
-            start: Instant::now(),
-            elapsed: Duration::ZERO,
             cursor: Vec2::ZERO,
             mouse_button_pressed: false,
             can_drag: None,
@@ -235,7 +232,7 @@ impl crate::controller::Controller for Controller {
             repeat: Repetition::default(),
             params: Shape::iter().map(|shape| shape.default_params()).collect(),
             prev_params: Shape::Disk.default_params(),
-            grid: SdfGrid::new(),
+            grid: bytemuck::zeroed_box(),
             grid_needs_updating: true,
             smooth: true,
         }
@@ -291,16 +288,20 @@ impl crate::controller::Controller for Controller {
         self.size = size;
     }
 
-    fn update(&mut self) {
-        self.params[self.shape as usize] = self.params();
+    fn on_deactivate(&mut self) {
+        // The cursor will have moved on by the time the controller is back
+        self.can_drag = None;
+    }
+
+    fn update(&mut self, ctx: &FrameContext) {
+        self.params[self.shape as usize] = self.shape_params();
         if self.params[self.shape as usize] != self.prev_params {
             self.grid_needs_updating = true;
         }
         let cursor = self.grid.clamp(from_pixels(self.cursor, self.size.into()));
-        self.elapsed = self.start.elapsed();
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
-            time: self.elapsed.as_secs_f32(),
+            time: ctx.time,
             cursor: cursor.into(),
             mouse_button_pressed: !(1
                 << (self.mouse_button_pressed && self.drag_point.is_none()) as u32),
@@ -363,11 +364,14 @@ impl crate::controller::Controller for Controller {
         self.onion.ui(ui, "Onion", 0.0..=0.2, 0.01);
         ui.heading("Repetition");
         self.repeat.ui(ui);
-        if self.grid_needs_updating {
-            self.grid_needs_updating = false;
-            let shape = self.shape;
-            let params = self.params();
-            self.grid.update(|p| sdf(p, shape, params));
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.separator();
+            if ui.button("Export SVG").clicked() {
+                self.export_svg();
+            }
+        }
+        if self.prepare_buffers() {
             self.signal_new_buffers(event_proxy);
         }
     }
@@ -377,14 +381,65 @@ impl crate::controller::Controller for Controller {
             bind_group_buffers: vec![BindGroupBufferType::SSBO(SSBO {
                 data: bytemuck::cast_slice(&self.grid.grid),
                 read_only: true,
+                per_frame: false,
             })],
             ..Default::default()
         }
     }
+
+    fn prepare_buffers(&mut self) -> bool {
+        if !self.grid_needs_updating {
+            return false;
+        }
+        self.grid_needs_updating = false;
+        let shape = self.shape;
+        let params = self.shape_params();
+        self.grid.update(|p| sdf(p, shape, params));
+        true
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        let shape = self.shape;
+        let Params { dims, ps, rot, .. } = &mut self.params[shape as usize];
+        let mut params = vec![Param::choice("shape", &mut self.shape)];
+        params.extend(
+            dims.iter_mut()
+                .zip(shape.labels().iter().zip(shape.dim_range()))
+                .map(|(dim, (label, range))| {
+                    Param::f32(label.to_case(Case::Snake), dim, range.clone())
+                }),
+        );
+        // The control points, before rotation
+        for (i, [x, y]) in ps.iter_mut().take(shape.default_points().len()).enumerate() {
+            params.extend([
+                Param::unbounded(format!("p{i}_x"), x),
+                Param::unbounded(format!("p{i}_y"), y),
+            ]);
+        }
+        params.extend([
+            Param::unbounded("rotation", rot),
+            Param::bool("smooth", &mut self.smooth),
+            Param::enabled("pad", &mut self.pad, 0.0..=0.2),
+            Param::enabled("onion", &mut self.onion, 0.0..=0.2),
+        ]);
+        params.extend(self.repeat.params());
+        params
+    }
+
+    fn distance_field(&self) -> Option<Vec<f32>> {
+        let params = self.shape_params();
+        Some(sample_pixels(self.size, |p| sdf(p, self.shape, params)))
+    }
+
+    fn probe(&self) -> Option<Probe> {
+        let position = from_pixels(self.cursor, self.size.into());
+        let distance = sdf(position, self.shape, self.shape_params());
+        Some(Probe::new(position).with("distance", distance))
+    }
 }
 
 impl Controller {
-    fn params(&self) -> Params {
+    fn shape_params(&self) -> Params {
         Params {
             pad: self.pad.into(),
             onion: self.onion.into(),
@@ -398,6 +453,57 @@ impl Controller {
             panic!("Event loop dead");
         }
     }
+
+    /// Exports the shape as an exact SVG element if it has one and isn't
+    /// repeated, padded or hollowed out, or else as its contours.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_svg(&self) {
+        use crate::svg::{self, Element, Svg};
+        use dfutils::{primitives::*, primitives_enum::Shape as Primitive};
+        use shared::sdf_interpreter::Transform;
+        let aspect_ratio = self.size.width as f32 / self.size.height as f32;
+        let (shape, params) = (self.shape, self.shape_params());
+        let [dim0, dim1, _] = params.dims;
+        let p = params.ps.map(Vec2::from);
+        let primitive = match shape {
+            Shape::Disk => Some(Primitive::Disk(Disk::new(dim0))),
+            Shape::Rectangle => Some(Primitive::Rectangle(Rectangle::new(dim0, dim1))),
+            Shape::Torus => Some(Primitive::Torus(Torus::new(dim0, dim1))),
+            Shape::LineSegment => Some(Primitive::LineSegment(LineSegment::new(p[0], p[1]))),
+            Shape::Triangle => Some(Primitive::Triangle(Triangle::new(p[0], p[1], p[2]))),
+            Shape::Polygon => Some(Primitive::Polygon(Polygon::new([
+                p[0], p[1], p[2], p[3], p[4],
+            ]))),
+            _ => None,
+        };
+        let plain = params.repeat.current == RepetitionValue::None
+            && !params.pad.has_value()
+            && !params.onion.has_value();
+        let mut svg = Svg::new(aspect_ratio);
+        let id = shape.to_string();
+        match primitive
+            .filter(|_| plain)
+            .and_then(|p| Element::from_shape(&p))
+        {
+            Some(element) => {
+                // The shader rotates the points rather than the shape
+                let transform = Transform {
+                    rotation: -params.rot,
+                    ..Transform::IDENTITY
+                };
+                svg.add(Some(&id), &element, &transform);
+            }
+            None => {
+                let contours = svg::contours(aspect_ratio, |p| sdf(p, shape, params));
+                svg.add(
+                    Some(&id),
+                    &Element::Contours(contours),
+                    &Transform::IDENTITY,
+                );
+            }
+        }
+        svg.save("SDFs2D");
+    }
 }
 
 fn sdf(mut p: Vec2, shape: Shape, params: Params) -> f32 {
@@ -1,14 +1,20 @@
+use crate::controller::FrameContext;
 use crate::egui_components::enabled_number::EnabledNumber;
+use crate::params::Param;
 use crate::window::UserEvent;
 use bytemuck::Zeroable;
+use convert_case::{Case, Casing};
 use egui::{Context, CursorIcon};
 use glam::{vec2, Mat3, Vec2, Vec3};
 use shared::{
     from_pixels,
-    push_constants::sdfs_3d::{Params, ShaderConstants, Shape},
+    push_constants::{
+        sdfs_3d::{Params, ShaderConstants, Shape},
+        Eye,
+    },
     ray_intersection::ray_intersect_sphere,
 };
-use std::{f32::consts::PI, time::Instant};
+use std::{borrow::Cow, f32::consts::PI};
 use strum::IntoEnumIterator;
 use egui_winit::winit::{
     dpi::{PhysicalPosition, PhysicalSize},
@@ -18,7 +24,6 @@ use egui_winit::winit::{
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
     mouse_button_pressed: u32,
     cursor: Vec2,
     prev_cursor: Vec2,
@@ -40,7 +45,6 @@ impl crate::controller::Controller for Controller {
     fn new(size: PhysicalSize<u32>) -> Self {
         Self {
             size,
-            start: Instant::now(),
             cursor: Vec2::ZERO,
             prev_cursor: Vec2::ZERO,
             mouse_button_pressed: 0,
@@ -122,7 +126,7 @@ impl crate::controller::Controller for Controller {
         self.size = size;
     }
 
-    fn update(&mut self) {
+    fn update(&mut self, ctx: &FrameContext) {
         let cursor_3d_pos = if self.mouse_button_pressed & 1 == 1 {
             self.get_cursor_slice_pos()
         } else {
@@ -130,7 +134,7 @@ impl crate::controller::Controller for Controller {
         };
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
-            time: self.start.elapsed().as_secs_f32(),
+            time: ctx.time,
             cursor: cursor_3d_pos.into(),
             mouse_button_pressed: if self.drag_point.is_some() || cursor_3d_pos == Vec3::MAX {
                 self.mouse_button_pressed & !1
@@ -140,7 +144,8 @@ impl crate::controller::Controller for Controller {
             slice_z: self.slice_z,
             translate: self.camera.into(),
             shape: self.shape as u32,
-            params: self.params(),
+            params: self.shape_params(),
+            eye: Eye::mono(self.size.width),
         };
     }
 
@@ -148,6 +153,14 @@ impl crate::controller::Controller for Controller {
         bytemuck::bytes_of(&self.shader_constants)
     }
 
+    fn has_stereo(&self) -> bool {
+        true
+    }
+
+    fn eye_push_constants(&self, eye: Eye) -> Cow<'_, [u8]> {
+        Cow::Owned(bytemuck::bytes_of(&ShaderConstants { eye, ..self.shader_constants }).to_vec())
+    }
+
     fn has_ui(&self) -> bool {
         true
     }
@@ -188,10 +201,34 @@ impl crate::controller::Controller for Controller {
             });
         }
     }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        let shape = self.shape;
+        let dims = &mut self.params[shape as usize].dims;
+        let mut params = vec![Param::choice("shape", &mut self.shape)];
+        params.extend(
+            dims.iter_mut()
+                .zip(shape.labels().iter().zip(shape.dim_range()))
+                .map(|(dim, (label, range))| {
+                    Param::f32(label.to_case(Case::Snake), dim, range.clone())
+                }),
+        );
+        params.extend([
+            Param::enabled("pad", &mut self.pad, 0.0..=0.1),
+            Param::enabled("onion", &mut self.onion, 0.0..=0.1),
+            Param::enabled("repeat_x", &mut self.repeat_x, 0.01..=1.0),
+            Param::enabled("repeat_y", &mut self.repeat_y, 0.01..=1.0),
+            Param::enabled("repeat_z", &mut self.repeat_z, 0.01..=1.0),
+            Param::unbounded("slice_z", &mut self.slice_z),
+            Param::unbounded("camera_x", &mut self.camera.x),
+            Param::unbounded("camera_y", &mut self.camera.y),
+        ]);
+        params
+    }
 }
 
 impl Controller {
-    fn params(&self) -> Params {
+    fn shape_params(&self) -> Params {
         Params {
             onion: self.onion.into(),
             pad: self.pad.into(),
@@ -1,3 +1,4 @@
+use crate::controller::FrameContext;
 use bytemuck::Zeroable;
 use glam::vec2;
 use shared::push_constants::sierpinski_triangle::ShaderConstants;
@@ -29,7 +30,7 @@ impl crate::controller::Controller for Controller {
         self.size = size;
     }
 
-    fn update(&mut self) {
+    fn update(&mut self, _ctx: &FrameContext) {
         let c = 59.87868500430847;
         let v = 34.102688577484;
         let scroll = if self.scroll > c {
@@ -1,4 +1,4 @@
-use crate::window::UserEvent;
+use crate::{controller::FrameContext, params::Param, window::UserEvent};
 use bytemuck::Zeroable;
 use egui::{Color32, Context, Rect, RichText, Sense, Stroke, Ui};
 use egui_winit::winit::{
@@ -8,11 +8,9 @@ use egui_winit::winit::{
 };
 use glam::{vec2, Quat, Vec2, Vec2Swizzles};
 use shared::push_constants::spherical_harmonics::{ShaderConstants, Variant};
-use std::time::Instant;
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
     cursor: Vec2,
     prev_cursor: Vec2,
     rot: Quat,
@@ -30,7 +28,6 @@ impl crate::controller::Controller for Controller {
     fn new(size: PhysicalSize<u32>) -> Self {
         Self {
             size,
-            start: Instant::now(),
             cursor: Vec2::ZERO,
             prev_cursor: Vec2::ZERO,
             rot: Quat::IDENTITY,
@@ -90,11 +87,11 @@ impl crate::controller::Controller for Controller {
         self.size = size;
     }
 
-    fn update(&mut self) {
+    fn update(&mut self, ctx: &FrameContext) {
         self.shader_constants = ShaderConstants {
             size: self.size.into(),
             time: if self.include_time_factor {
-                self.start.elapsed().as_secs_f32()
+                ctx.time
             } else {
                 0.0
             },
@@ -117,13 +114,7 @@ impl crate::controller::Controller for Controller {
     fn ui(&mut self, ctx: &Context, ui: &mut Ui, _: &EventLoopProxy<UserEvent>) {
         ui.radio_value(&mut self.variant, Variant::Real, "Real");
         ui.radio_value(&mut self.variant, Variant::Complex, "Complex");
-        if ui
-            .checkbox(&mut self.include_time_factor, "Include time factor")
-            .clicked()
-            && self.include_time_factor
-        {
-            self.start = Instant::now();
-        }
+        ui.checkbox(&mut self.include_time_factor, "Include time factor");
 
         let (rect, response) = ui.allocate_at_least([220.0; 2].into(), Sense::drag());
         let l_max = 9;
@@ -189,4 +180,20 @@ impl crate::controller::Controller for Controller {
         );
         ui.advance_cursor_after_rect(rect);
     }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        let l = self.l as i32;
+        let rot = &mut *self.rot;
+        vec![
+            Param::choice("variant", &mut self.variant),
+            Param::u32("l", &mut self.l, 0..=9),
+            Param::i32("m", &mut self.m, -l..=l),
+            Param::bool("include_time_factor", &mut self.include_time_factor),
+            Param::unbounded("zoom", &mut self.zoom),
+            Param::f32("rot_x", &mut rot.x, -1.0..=1.0),
+            Param::f32("rot_y", &mut rot.y, -1.0..=1.0),
+            Param::f32("rot_z", &mut rot.z, -1.0..=1.0),
+            Param::f32("rot_w", &mut rot.w, -1.0..=1.0),
+        ]
+    }
 }
@@ -1,4 +1,10 @@
-use crate::{camera::RotationCamera, controller::BufferData, model::Vertex, window::UserEvent};
+use crate::{
+    camera::RotationCamera,
+    controller::{BufferData, FrameContext},
+    model::Vertex,
+    params::Param,
+    window::UserEvent,
+};
 use bytemuck::Zeroable;
 use egui::{Color32, Context, Rect, RichText, Sense, Stroke, Ui};
 use egui_winit::winit::{
@@ -8,13 +14,16 @@ use egui_winit::winit::{
 };
 use glam::{vec2, vec3, Vec2};
 use shared::{
-    push_constants::spherical_harmonics_shape::{ShaderConstants, Variant},
+    push_constants::{
+        spherical_harmonics_shape::{ShaderConstants, Variant},
+        Eye,
+    },
     spherical_harmonics::*,
 };
 use std::{
+    borrow::Cow,
     f32::consts::{FRAC_1_SQRT_2, PI, TAU},
     ops::Rem,
-    time::Instant,
 };
 use strum::IntoEnumIterator;
 
@@ -23,7 +32,7 @@ const J_MAX: usize = 400;
 
 pub struct Controller {
     size: PhysicalSize<u32>,
-    start: Instant,
+    time: f32,
     cursor: Vec2,
     prev_cursor: Vec2,
     mouse_button_pressed: bool,
@@ -46,7 +55,7 @@ impl crate::controller::Controller for Controller {
 
         Self {
             size,
-            start: Instant::now(),
+            time: 0.0,
             cursor: Vec2::ZERO,
             prev_cursor: Vec2::ZERO,
             mouse_button_pressed: false,
@@ -112,7 +121,8 @@ impl crate::controller::Controller for Controller {
         self.camera.resize(size);
     }
 
-    fn update(&mut self) {
+    fn update(&mut self, ctx: &FrameContext) {
+        self.time = ctx.time;
         self.shader_constants = ShaderConstants {
             view_proj: self.camera.build_view_projection_matrix().into(),
         };
@@ -122,6 +132,17 @@ impl crate::controller::Controller for Controller {
         bytemuck::bytes_of(&self.shader_constants)
     }
 
+    fn has_stereo(&self) -> bool {
+        true
+    }
+
+    fn eye_push_constants(&self, eye: Eye) -> Cow<'_, [u8]> {
+        let constants = ShaderConstants {
+            view_proj: self.camera.build_eye_view_projection_matrix(eye.offset).into(),
+        };
+        Cow::Owned(bytemuck::bytes_of(&constants).to_vec())
+    }
+
     fn has_ui(&self) -> bool {
         true
     }
@@ -140,9 +161,7 @@ impl crate::controller::Controller for Controller {
         if ui
             .checkbox(&mut self.include_time_factor, "Include time factor")
             .clicked()
-        // && self.include_time_factor
         {
-            self.start = Instant::now();
             self.new_vertices = true;
         }
 
@@ -215,9 +234,8 @@ impl crate::controller::Controller for Controller {
         );
         ui.advance_cursor_after_rect(rect);
 
-        if self.new_vertices || self.include_time_factor {
-            self.update_vertices(event_proxy);
-            self.new_vertices = false;
+        if self.prepare_buffers() {
+            signal_new_vertices(event_proxy);
         }
     }
 
@@ -229,14 +247,41 @@ impl crate::controller::Controller for Controller {
             ..Default::default()
         }
     }
+
+    fn prepare_buffers(&mut self) -> bool {
+        if !self.new_vertices && !self.include_time_factor {
+            return false;
+        }
+        self.update_vertices();
+        self.new_vertices = false;
+        true
+    }
+
+    fn params(&mut self) -> Vec<Param<'_>> {
+        // The vertices are regenerated as any of these may change
+        self.new_vertices = true;
+        let l = self.l as i32;
+        let rot = &mut *self.camera.rot;
+        vec![
+            Param::choice("variant", &mut self.variant),
+            Param::u32("l", &mut self.l, 0..=9),
+            Param::i32("m", &mut self.m, -l..=l),
+            Param::bool("include_time_factor", &mut self.include_time_factor),
+            Param::unbounded("zoom", &mut self.camera.zoom),
+            Param::f32("rot_x", &mut rot.x, -1.0..=1.0),
+            Param::f32("rot_y", &mut rot.y, -1.0..=1.0),
+            Param::f32("rot_z", &mut rot.z, -1.0..=1.0),
+            Param::f32("rot_w", &mut rot.w, -1.0..=1.0),
+        ]
+    }
 }
 
 impl Controller {
-    fn update_vertices(&mut self, event_proxy: &EventLoopProxy<UserEvent>) {
+    fn update_vertices(&mut self) {
         let m = self.m;
         let l = self.l;
         let time = if self.include_time_factor {
-            self.start.elapsed().as_secs_f32()
+            self.time
         } else {
             0.0
         };
@@ -268,7 +313,6 @@ impl Controller {
                 });
             }
         }
-        signal_new_vertices(event_proxy);
     }
 
     fn update_vertices_impl<F>(&mut self, f: F)
@@ -1,6 +1,7 @@
 use crate::{
     context::GraphicsContext,
-    controller::{new_controller, Controller},
+    controller::{new_controller, Controller, FrameContext, InputEvent, InputState, KeyInput},
+    params,
     render_pass::RenderPass,
     shader::{self, CompiledShaderModules},
     texture::Texture,
@@ -10,10 +11,19 @@ use crate::{
 };
 use egui_winit::winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+    event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::EventLoopProxy,
+    keyboard::ModifiersState,
     window::Window,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    audio::Audio,
+    recording::{Event, Frame, Recorder, Recording},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{collections::VecDeque, path::Path};
+use std::time::Instant;
 use strum::IntoEnumIterator;
 
 pub struct State<'a> {
@@ -24,6 +34,18 @@ pub struct State<'a> {
     ui_state: UiState,
     depth_texture: Texture,
     options: Options,
+    input_state: InputState,
+    modifiers: ModifiersState,
+    last_frame: Instant,
+    /// The pixel to read back after the next frame, chosen with alt+click.
+    #[cfg(not(target_arch = "wasm32"))]
+    pending_inspection: Option<PhysicalPosition<u32>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    recorder: Option<Recorder>,
+    #[cfg(not(target_arch = "wasm32"))]
+    replay: Option<VecDeque<Frame>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    audio: Option<Audio>,
 }
 
 impl<'a> State<'a> {
@@ -39,16 +61,24 @@ impl<'a> State<'a> {
 
         let ui_state = UiState::new(options.shader);
 
-        let controllers = RustGPUShader::iter()
+        let mut controllers = RustGPUShader::iter()
             .map(|s| new_controller(s, window.inner_size()))
             .collect::<Vec<Box<dyn Controller>>>();
 
-        let controller = &controllers[ui_state.active_shader as usize];
+        let controller = &mut controllers[ui_state.active_shader as usize];
+        controller.on_activate();
+
+        if let Err(e) = compiled_shader_modules
+            .validate(controller.push_constants().len(), &controller.buffers())
+        {
+            eprintln!("{}: {e}", options.shader);
+            std::process::exit(1);
+        }
 
         let rpass = RenderPass::new(
             &ctx,
             compiled_shader_modules,
-            options,
+            options.clone(),
             &controller.buffers(),
         );
 
@@ -63,6 +93,17 @@ impl<'a> State<'a> {
             ui_state,
             depth_texture,
             options,
+            input_state: InputState::default(),
+            modifiers: ModifiersState::empty(),
+            last_frame: Instant::now(),
+            #[cfg(not(target_arch = "wasm32"))]
+            pending_inspection: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            recorder: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            replay: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            audio: None,
         }
     }
 
@@ -77,34 +118,132 @@ impl<'a> State<'a> {
             self.ctx
                 .surface
                 .configure(&self.ctx.device, &self.ctx.config);
-            self.controller().resize(size);
             self.depth_texture =
                 Texture::create_depth_texture(&self.ctx.device, &self.ctx.config, "depth_texture");
+            self.input(InputEvent::Resize(size));
         }
     }
 
-    pub fn keyboard_input(&mut self, event: KeyEvent) {
-        self.controller().keyboard_input(event);
+    pub fn keyboard_input(&mut self, input: KeyInput) {
+        self.input(InputEvent::Keyboard(input));
+    }
+
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
     }
 
     pub fn mouse_input(&mut self, state: ElementState, button: MouseButton) {
-        self.controller().mouse_input(state, button);
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.modifiers.alt_key() && button == MouseButton::Left {
+            if state == ElementState::Pressed {
+                self.start_inspection();
+            }
+            return;
+        }
+        self.input(InputEvent::MouseInput(state, button));
+    }
+
+    /// Asks the active controller to write debug values for the pixel under
+    /// the cursor, which is read back after the next frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_inspection(&mut self) {
+        let cursor = self.input_state.cursor;
+        let pixel = PhysicalPosition::new(cursor.x.max(0.0) as u32, cursor.y.max(0.0) as u32);
+        self.controller().inspect(Some(pixel));
+        self.pending_inspection = Some(pixel);
     }
 
     pub fn mouse_move(&mut self, position: PhysicalPosition<f64>) {
-        self.controller().mouse_move(position);
+        self.input(InputEvent::MouseMove(position));
     }
 
     pub fn mouse_delta(&mut self, position: (f64, f64)) {
-        self.controller().mouse_delta(position);
+        self.input(InputEvent::MouseDelta(position));
     }
 
     pub fn mouse_scroll(&mut self, delta: MouseScrollDelta) {
-        self.controller().mouse_scroll(delta);
+        self.input(InputEvent::MouseScroll(delta));
     }
 
-    pub fn update(&mut self) {
-        self.controller().update();
+    /// Passes an event to the active controller, unless a recording is being replayed.
+    fn input(&mut self, event: InputEvent) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if self.replay.is_some() {
+                return;
+            }
+            if let Some(recorder) = &mut self.recorder {
+                recorder.event(Event::Input(event));
+            }
+        }
+        self.dispatch(event);
+    }
+
+    fn dispatch(&mut self, event: InputEvent) {
+        self.input_state.apply(event);
+        event.apply(self.controller());
+    }
+
+    /// Advances the clock and returns the seconds since the previous frame.
+    #[cfg(target_arch = "wasm32")]
+    fn tick(&mut self) -> f32 {
+        let now = Instant::now();
+        let dt = (now - self.last_frame).as_secs_f32();
+        self.last_frame = now;
+        self.ui_state.clock.tick();
+        dt
+    }
+
+    /// Advances the clock, or to the next frame of the replayed recording,
+    /// and returns the seconds since the previous frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn tick(&mut self) -> f32 {
+        let now = Instant::now();
+        let dt = (now - self.last_frame).as_secs_f32();
+        self.last_frame = now;
+        if let Some(replay) = &mut self.replay {
+            if let Some(frame) = replay.pop_front() {
+                self.ui_state.clock.seek(frame.time);
+                for event in frame.events {
+                    match event {
+                        Event::Input(event) => self.dispatch(event),
+                        Event::SwitchShader(shader) => self.load_shader(shader),
+                    }
+                }
+                return frame.dt;
+            }
+            println!("Replay finished");
+            self.replay = None;
+        }
+        self.ui_state.clock.tick();
+        if let Some(recorder) = &mut self.recorder
+            && let Err(e) = recorder.frame(self.ui_state.clock.time(), dt)
+        {
+            eprintln!("Failed to record input: {e}");
+            self.recorder = None;
+        }
+        dt
+    }
+
+    pub fn update(&mut self, window: &Window) {
+        let dt = self.tick();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(audio) = &self.audio {
+            self.ui_state.spectrum = audio.spectrum(self.ui_state.clock.time());
+        }
+        let frame = FrameContext {
+            dt,
+            time: self.ui_state.clock.time(),
+            size: PhysicalSize::new(self.ctx.config.width, self.ctx.config.height),
+            scale_factor: window.scale_factor(),
+            input: &self.input_state,
+            spectrum: &self.ui_state.spectrum,
+        };
+        let controller = &mut *self.controllers[self.ui_state.active_shader as usize];
+        self.ui_state
+            .modulation
+            .apply(controller, frame.time, frame.spectrum);
+        controller.update(&frame);
     }
 
     pub fn render(&mut self, window: &Window) -> Result<(), wgpu::SurfaceError> {
@@ -114,6 +253,23 @@ impl<'a> State<'a> {
             .use_depth_buffer
             .then_some(&self.depth_texture);
 
+        // Taken before rendering, so that parameters changed by the ui last
+        // frame have been through `update`
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.ui_state.presets.pending_thumbnail.is_some() {
+            let stereo = &self.ui_state.stereo;
+            match self.rpass.screenshot(&self.ctx, controller, depth_texture, stereo) {
+                Ok(rgba) => {
+                    let (width, height) = (self.ctx.config.width, self.ctx.config.height);
+                    self.ui_state.presets.set_thumbnail(width, height, &rgba);
+                }
+                Err(e) => {
+                    self.ui_state.presets.pending_thumbnail = None;
+                    eprintln!("Failed to take preset thumbnail: {e}");
+                }
+            }
+        }
+
         self.rpass.render(
             &self.ctx,
             window,
@@ -121,11 +277,25 @@ impl<'a> State<'a> {
             &mut self.ui_state,
             controller,
             depth_texture,
-        )
+        )?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(pixel) = self.pending_inspection.take() {
+            let stereo = &self.ui_state.stereo;
+            match self
+                .rpass
+                .inspect(&self.ctx, controller, depth_texture, stereo, pixel)
+            {
+                Ok(inspection) => self.ui_state.inspection = Some(inspection),
+                Err(e) => eprintln!("Failed to inspect pixel: {e}"),
+            }
+        }
+
+        Ok(())
     }
 
     pub fn update_and_render(&mut self, window: &Window) -> Result<(), wgpu::SurfaceError> {
-        self.update();
+        self.update(window);
         self.render(window)
     }
 
@@ -134,9 +304,28 @@ impl<'a> State<'a> {
     }
 
     pub fn new_module(&mut self, shader: RustGPUShader, new_module: CompiledShaderModules) {
+        let controller = &self.controllers[shader as usize];
+        if let Err(e) =
+            new_module.validate(controller.push_constants().len(), &controller.buffers())
+        {
+            return eprintln!("{shader}: {e}");
+        }
+        if shader != self.ui_state.active_shader {
+            let controller = &mut *self.controllers[self.ui_state.active_shader as usize];
+            self.input_state.release(controller);
+            self.ui_state.modulation.replace(controller, vec![]);
+            self.controller().on_deactivate();
+            self.ui_state.active_shader = shader;
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.ui_state.presets = crate::presets::Presets::load(shader);
+            }
+            let size = PhysicalSize::new(self.ctx.config.width, self.ctx.config.height);
+            self.controller().resize(size);
+            self.controller().on_activate();
+        }
         let controller = &self.controllers[shader as usize];
         let buffers = controller.buffers();
-        self.ui_state.active_shader = shader;
         self.rpass.new_module(&self.ctx, new_module, &buffers);
     }
 
@@ -146,16 +335,30 @@ impl<'a> State<'a> {
     }
 
     pub fn switch_shader(&mut self, shader: RustGPUShader) {
-        self.new_module(
-            shader,
-            shader::maybe_watch(
-                &Options {
-                    shader,
-                    ..self.options
-                },
-                None,
-            ),
-        )
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if self.replay.is_some() {
+                return;
+            }
+            if let Some(recorder) = &mut self.recorder {
+                recorder.event(Event::SwitchShader(shader));
+            }
+        }
+        self.load_shader(shader);
+    }
+
+    fn load_shader(&mut self, shader: RustGPUShader) {
+        #[cfg(target_arch = "wasm32")]
+        let module = shader::embedded(shader);
+        #[cfg(not(target_arch = "wasm32"))]
+        let module = shader::maybe_watch(
+            &Options {
+                shader,
+                ..self.options.clone()
+            },
+            None,
+        );
+        self.new_module(shader, module)
     }
 
     pub fn set_vsync(&mut self, enable: bool) {
@@ -166,4 +369,162 @@ impl<'a> State<'a> {
         let controller = &self.controllers[self.ui_state.active_shader as usize];
         controller.cursor_visible()
     }
+
+    /// Sets parameters of the active controller by name.
+    pub fn set_params(&mut self, assignments: &[(String, String)]) -> Result<(), String> {
+        let shader = self.ui_state.active_shader;
+        for (key, value) in assignments {
+            params::set(self.controller(), key, value).map_err(|e| format!("{shader}: {e}"))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn screenshot(&mut self) {
+        let shader = self.ui_state.active_shader;
+        let controller = &*self.controllers[shader as usize];
+        let depth_texture = controller
+            .buffers()
+            .use_depth_buffer
+            .then_some(&self.depth_texture);
+        let rgba = self.rpass.screenshot(
+            &self.ctx,
+            controller,
+            depth_texture,
+            &self.ui_state.stereo,
+        );
+        let (width, height) = (self.ctx.config.width, self.ctx.config.height);
+        self.save_image(&shader.to_string(), width, height, rgba);
+    }
+
+    /// Saves a 2:1 equirectangular panorama around the camera.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn panorama(&mut self) {
+        const WIDTH: u32 = 4096;
+        let shader = self.ui_state.active_shader;
+        let rgba = self
+            .rpass
+            .panorama(&self.ctx, &*self.controllers[shader as usize], WIDTH);
+        self.save_image(&format!("{shader}-panorama"), WIDTH, WIDTH / 2, rgba);
+    }
+
+    /// Saves the frame as linear float images, along with the distance field
+    /// if the shader has one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn hdr_screenshot(&mut self) {
+        let shader = self.ui_state.active_shader;
+        let controller = &*self.controllers[shader as usize];
+        let depth_texture = controller
+            .buffers()
+            .use_depth_buffer
+            .then_some(&self.depth_texture);
+        let rgba = self
+            .rpass
+            .hdr_screenshot(&self.ctx, controller, depth_texture);
+        let rgb = rgba
+            .chunks(4)
+            .flat_map(|pixel| &pixel[..3])
+            .copied()
+            .collect::<Vec<_>>();
+        let (width, height) = (self.ctx.config.width, self.ctx.config.height);
+        save_float_image(&shader.to_string(), width, height, 3, &rgb);
+        if let Some(distances) = controller.distance_field() {
+            save_float_image(&format!("{shader}-distance"), width, height, 1, &distances);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_image(&mut self, stem: &str, width: u32, height: u32, rgba: Result<Vec<u8>, String>) {
+        use crate::screenshot::{self, Metadata};
+        let rgba = match rgba {
+            Ok(rgba) => rgba,
+            Err(e) => return eprintln!("Failed to take screenshot: {e}"),
+        };
+        let shader = self.ui_state.active_shader;
+        let metadata = Metadata {
+            shader,
+            params: params::serialize(self.controller()),
+            time: self.ui_state.clock.time(),
+        };
+        let path = screenshot::file_name(stem, "png");
+        match screenshot::save(&path, width, height, &rgba, &metadata) {
+            Ok(()) => println!("Saved screenshot to {}", path.display()),
+            Err(e) => eprintln!("Failed to save screenshot: {e}"),
+        }
+    }
+
+    /// Writes all input from now on to `path`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn record(&mut self, path: &Path) -> std::io::Result<()> {
+        let size = PhysicalSize::new(self.ctx.config.width, self.ctx.config.height);
+        self.recorder = Some(Recorder::create(
+            path,
+            self.ui_state.active_shader,
+            size,
+        )?);
+        Ok(())
+    }
+
+    /// Feeds the recorded input to the controllers instead of the window's,
+    /// with the clock following the recorded frames.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn replay(&mut self, recording: Recording) {
+        if recording.shader != self.ui_state.active_shader {
+            self.load_shader(recording.shader);
+        }
+        self.controller().resize(recording.size);
+        self.replay = Some(recording.frames);
+    }
+
+    /// Analyses a WAV file at the clock's time from now on, driving the
+    /// spectrum of the controllers and the band modulators.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_audio(&mut self, path: &Path) {
+        match Audio::load(path) {
+            Ok(audio) => {
+                println!("Loaded {} ({:.1}s)", audio.name, audio.duration());
+                self.ui_state.audio = Some(audio.name.clone());
+                self.audio = Some(audio);
+            }
+            Err(e) => eprintln!("Failed to load audio: {e}"),
+        }
+    }
+
+    /// Restores the shader, parameters and time stored in a screenshot.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_image(&mut self, path: &Path) {
+        let metadata = match crate::screenshot::load(path) {
+            Ok(metadata) => metadata,
+            Err(e) => return eprintln!("Failed to load image: {e}"),
+        };
+        if metadata.shader != self.ui_state.active_shader {
+            self.switch_shader(metadata.shader);
+            if metadata.shader != self.ui_state.active_shader {
+                // The module was rejected, don't apply its parameters to the active shader
+                return;
+            }
+        }
+        self.ui_state.clock.pause_at(metadata.time);
+        if let Err(e) = params::deserialize(self.controller(), &metadata.params) {
+            eprintln!("Failed to restore parameters: {e}");
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_float_image(stem: &str, width: u32, height: u32, channels: usize, data: &[f32]) {
+    use crate::{hdr, screenshot};
+    let path = screenshot::file_name(stem, "pfm");
+    match hdr::save_pfm(&path, width, height, channels, data) {
+        Ok(()) => println!("Saved {}", path.display()),
+        Err(e) => eprintln!("Failed to save {}: {e}", path.display()),
+    }
+    #[cfg(feature = "exr")]
+    {
+        let path = path.with_extension("exr");
+        match hdr::save_exr(&path, width, height, channels, data) {
+            Ok(()) => println!("Saved {}", path.display()),
+            Err(e) => eprintln!("Failed to save {}: {e}", path.display()),
+        }
+    }
 }
@@ -0,0 +1,104 @@
+use egui::Ui;
+use shared::push_constants::Eye;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+#[derive(Clone, Copy, PartialEq, EnumIter, Display)]
+pub enum StereoMode {
+    Off,
+    Anaglyph,
+    #[strum(serialize = "Side by side")]
+    SideBySide,
+}
+
+pub struct Stereo {
+    pub mode: StereoMode,
+    /// Distance between the two cameras in world units.
+    pub eye_separation: f32,
+}
+
+/// One draw of the shader, per eye in stereo mode.
+pub struct View {
+    pub eye: Eye,
+    /// Color channels written by this view, used as the blend constant.
+    pub color_mask: wgpu::Color,
+}
+
+impl View {
+    pub fn mono(width: u32) -> Self {
+        Self {
+            eye: Eye::mono(width),
+            color_mask: wgpu::Color::WHITE,
+        }
+    }
+}
+
+impl Stereo {
+    pub fn new() -> Self {
+        Self {
+            mode: StereoMode::Off,
+            eye_separation: 0.06,
+        }
+    }
+
+    pub fn views(&self, width: u32) -> Vec<View> {
+        let offset = 0.5 * self.eye_separation;
+        let half_width = 0.5 * width as f32;
+        match self.mode {
+            StereoMode::Off => vec![View::mono(width)],
+            StereoMode::Anaglyph => vec![
+                View {
+                    eye: Eye {
+                        offset: -offset,
+                        ..Eye::mono(width)
+                    },
+                    color_mask: wgpu::Color::RED,
+                },
+                View {
+                    eye: Eye {
+                        offset,
+                        ..Eye::mono(width)
+                    },
+                    color_mask: wgpu::Color {
+                        r: 0.0,
+                        g: 1.0,
+                        b: 1.0,
+                        a: 1.0,
+                    },
+                },
+            ],
+            StereoMode::SideBySide => vec![
+                View {
+                    eye: Eye {
+                        offset: -offset,
+                        viewport_x: 0.0,
+                        viewport_width: half_width,
+                    },
+                    color_mask: wgpu::Color::WHITE,
+                },
+                View {
+                    eye: Eye {
+                        offset,
+                        viewport_x: half_width,
+                        viewport_width: half_width,
+                    },
+                    color_mask: wgpu::Color::WHITE,
+                },
+            ],
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        egui::ComboBox::from_label("Stereo")
+            .selected_text(self.mode.to_string())
+            .show_ui(ui, |ui| {
+                for mode in StereoMode::iter() {
+                    ui.selectable_value(&mut self.mode, mode, mode.to_string());
+                }
+            });
+        if self.mode != StereoMode::Off {
+            ui.add(
+                egui::Slider::new(&mut self.eye_separation, 0.0..=0.2).text("Eye separation"),
+            );
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Export of 2D distance fields as SVG, in the coordinates of the shaders:
+//! y goes up and the drawing is 1 high, which is written as 100mm.
+
+use dfutils::{contour::Contour, primitives_enum::Shape, sdf::Sdf};
+use glam::{uvec2, vec2, Vec2};
+use shared::sdf_interpreter::Transform;
+use std::{fmt::Write as _, fs};
+
+/// Cells per unit of height when falling back to contours.
+const RESOLUTION: f32 = 512.0;
+
+/// A shape with a closed form in SVG, before its [`Transform`].
+pub enum Element {
+    Circle {
+        radius: f32,
+    },
+    /// Two circles filled with the even-odd rule.
+    Ring {
+        outer: f32,
+        inner: f32,
+    },
+    Rect {
+        size: Vec2,
+    },
+    /// A stroke, as a segment has no inside.
+    Line {
+        a: Vec2,
+        b: Vec2,
+    },
+    Polygon(Vec<Vec2>),
+    /// Contours of a distance field, filled with the even-odd rule.
+    Contours(Vec<Contour>),
+}
+
+impl Element {
+    /// The exact element of the shape, if it has one.
+    pub fn from_shape(shape: &Shape) -> Option<Self> {
+        Some(match *shape {
+            Shape::Disk(disk) => Self::Circle {
+                radius: disk.radius,
+            },
+            Shape::Torus(torus) if torus.minor_radius < torus.major_radius => Self::Ring {
+                outer: torus.major_radius + torus.minor_radius,
+                inner: torus.major_radius - torus.minor_radius,
+            },
+            Shape::Torus(torus) => Self::Circle {
+                radius: torus.major_radius + torus.minor_radius,
+            },
+            Shape::Rectangle(rectangle) => Self::Rect {
+                size: vec2(rectangle.width, rectangle.height),
+            },
+            Shape::LineSegment(line_segment) => Self::Line {
+                a: line_segment.a,
+                b: line_segment.b,
+            },
+            Shape::Triangle(triangle) => Self::Polygon(vec![triangle.a, triangle.b, triangle.c]),
+            Shape::Polygon(polygon) => Self::Polygon(polygon.vertices.to_vec()),
+            _ => return None,
+        })
+    }
+
+    fn write(&self, svg: &mut String, attributes: &str) {
+        let circle = |r: f32| format!("M {r} 0 A {r} {r} 0 1 0 {} 0 A {r} {r} 0 1 0 {r} 0 Z", -r);
+        let points = |points: &[Vec2]| {
+            points
+                .iter()
+                .map(|p| format!("{} {}", p.x, p.y))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        // Writing to a `String` can't fail
+        let _ = match self {
+            Self::Circle { radius } => writeln!(svg, r#"<circle{attributes} r="{radius}"/>"#),
+            Self::Ring { outer, inner } => writeln!(
+                svg,
+                r#"<path{attributes} fill-rule="evenodd" d="{} {}"/>"#,
+                circle(*outer),
+                circle(*inner)
+            ),
+            Self::Rect { size } => writeln!(
+                svg,
+                r#"<rect{attributes} x="{}" y="{}" width="{}" height="{}"/>"#,
+                -0.5 * size.x,
+                -0.5 * size.y,
+                size.x,
+                size.y
+            ),
+            Self::Line { a, b } => writeln!(
+                svg,
+                r#"<line{attributes} x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="0.002"/>"#,
+                a.x, a.y, b.x, b.y
+            ),
+            Self::Polygon(vertices) => {
+                writeln!(
+                    svg,
+                    r#"<polygon{attributes} points="{}"/>"#,
+                    points(vertices)
+                )
+            }
+            Self::Contours(contours) => {
+                let d = contours
+                    .iter()
+                    .filter(|contour| !contour.points.is_empty())
+                    .map(|contour| {
+                        let close = if contour.closed { " Z" } else { "" };
+                        format!("M {}{close}", points(&contour.points))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(svg, r#"<path{attributes} fill-rule="evenodd" d="{d}"/>"#)
+            }
+        };
+    }
+}
+
+pub struct Svg {
+    /// Width over height of the drawing.
+    aspect_ratio: f32,
+    elements: String,
+}
+
+impl Svg {
+    pub fn new(aspect_ratio: f32) -> Self {
+        Self {
+            aspect_ratio,
+            elements: String::new(),
+        }
+    }
+
+    /// Adds the element, with `id` as its element id.
+    pub fn add(&mut self, id: Option<&str>, element: &Element, transform: &Transform) {
+        let mut attributes = String::new();
+        if let Some(id) = id {
+            let _ = write!(attributes, r#" id="{id}""#);
+        }
+        if *transform != Transform::IDENTITY {
+            let Transform {
+                position,
+                rotation,
+                scale,
+                stretch,
+            } = *transform;
+            let scale = scale * stretch;
+            let _ = write!(
+                attributes,
+                r#" transform="translate({} {}) rotate({}) scale({} {})""#,
+                position.x,
+                position.y,
+                rotation.to_degrees(),
+                scale.x,
+                scale.y
+            );
+        }
+        element.write(&mut self.elements, &attributes);
+    }
+
+    pub fn contents(&self) -> String {
+        let (w, h) = (self.aspect_ratio, 1.0);
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}mm" height="{}mm" viewBox="{} {} {} {}">"#,
+                "\n",
+                r#"<g transform="scale(1 -1)" fill="black">"#,
+                "\n{}</g>\n</svg>\n"
+            ),
+            100.0 * w,
+            100.0 * h,
+            -0.5 * w,
+            -0.5 * h,
+            w,
+            h,
+            self.elements
+        )
+    }
+
+    /// Saves the drawing to a new file named after `stem`.
+    pub fn save(&self, stem: &str) {
+        let path = crate::screenshot::file_name(stem, "svg");
+        match fs::write(&path, self.contents()) {
+            Ok(()) => println!("Saved {}", path.display()),
+            Err(e) => eprintln!("Failed to save {}: {e}", path.display()),
+        }
+    }
+}
+
+/// The contours of `sdf` at zero over the view of the given aspect ratio.
+pub fn contours(aspect_ratio: f32, sdf: impl Fn(Vec2) -> f32) -> Vec<Contour> {
+    struct FnSdf<F>(F);
+    impl<F: Fn(Vec2) -> f32> Sdf for FnSdf<F> {
+        type T = f32;
+        fn signed_distance(&self, p: Vec2) -> f32 {
+            (self.0)(p)
+        }
+    }
+
+    let half = vec2(0.5 * aspect_ratio, 0.5);
+    let cells = uvec2((aspect_ratio * RESOLUTION) as u32, RESOLUTION as u32);
+    dfutils::contour::contours(&FnSdf(sdf), -half, half, cells, 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfutils::primitives::{Disk, Rectangle};
+
+    #[test]
+    fn transformed_shapes() {
+        let transform = Transform {
+            position: vec2(0.25, -0.5),
+            rotation: 0.5,
+            scale: 2.0,
+            stretch: vec2(1.0, 0.5),
+        };
+        let mut svg = Svg::new(2.0);
+        let disk = Element::from_shape(&Shape::Disk(Disk::new(0.1))).unwrap();
+        svg.add(Some("disk"), &disk, &transform);
+        let rectangle = Element::from_shape(&Shape::Rectangle(Rectangle::new(0.4, 0.2))).unwrap();
+        svg.add(None, &rectangle, &Transform::IDENTITY);
+        let contents = svg.contents();
+
+        assert!(contents.contains(r#"width="200mm" height="100mm" viewBox="-1 -0.5 2 1""#));
+        let rotate = 0.5f32.to_degrees();
+        assert!(contents.contains(&format!(
+            r#"<circle id="disk" transform="translate(0.25 -0.5) rotate({rotate}) scale(2 1)" r="0.1"/>"#
+        )));
+        assert!(contents.contains(r#"<rect x="-0.2" y="-0.1" width="0.4" height="0.2"/>"#));
+    }
+
+    #[test]
+    fn contour_fallback() {
+        // A ring, whose hole is only left out with the even-odd rule
+        let contours = contours(16.0 / 9.0, |p| (p.length() - 0.3).abs() - 0.1);
+        assert_eq!(contours.len(), 2);
+        let mut svg = Svg::new(16.0 / 9.0);
+        svg.add(None, &Element::Contours(contours), &Transform::IDENTITY);
+        let contents = svg.contents();
+
+        let d = contents
+            .split_once(r#"<path fill-rule="evenodd" d=""#)
+            .and_then(|(_, rest)| rest.split_once(r#""/>"#))
+            .map(|(d, _)| d)
+            .unwrap();
+        let subpaths = d.split('M').skip(1).collect::<Vec<_>>();
+        assert_eq!(subpaths.len(), 2);
+        for subpath in subpaths {
+            assert!(subpath.trim_end().ends_with('Z'), "{subpath}");
+        }
+    }
+}
@@ -1,19 +1,38 @@
-use crate::{controller::Controller, fps_counter::FpsCounter, window::UserEvent, RustGPUShader};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{inspector::Inspection, presets::Presets};
+use crate::{
+    clock::Clock, controller::Controller, fps_counter::FpsCounter, modulation::Modulation,
+    stereo::Stereo, window::UserEvent, RustGPUShader,
+};
+use bytemuck::Zeroable;
 use egui::{
     epaint::{textures::TexturesDelta, ClippedPrimitive},
-    vec2, Align2, Context, Layout, Vec2,
+    pos2, vec2, Align2, Context, Layout, Rect, Sense, Vec2,
 };
 use egui_winit::{
     winit::{event::WindowEvent, event_loop::EventLoopProxy, window::Window},
     State,
 };
+use shared::spectrum::Spectrum;
 use strum::IntoEnumIterator;
 
 pub struct UiState {
     pub fps: usize,
     pub show_fps: bool,
+    pub show_probe: bool,
     pub vsync: bool,
     pub active_shader: RustGPUShader,
+    pub clock: Clock,
+    pub stereo: Stereo,
+    pub modulation: Modulation,
+    /// Band energies of the loaded audio at the clock's time.
+    pub spectrum: Spectrum,
+    /// Name of the loaded audio file.
+    pub audio: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub inspection: Option<Inspection>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub presets: Presets,
 }
 
 impl UiState {
@@ -21,8 +40,18 @@ impl UiState {
         Self {
             fps: 0,
             show_fps: true,
+            show_probe: false,
             vsync: true,
             active_shader,
+            clock: Clock::new(),
+            stereo: Stereo::new(),
+            modulation: Modulation::default(),
+            spectrum: Spectrum::zeroed(),
+            audio: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            inspection: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            presets: Presets::load(active_shader),
         }
     }
 }
@@ -102,18 +131,54 @@ impl Ui {
                 });
                 ui.separator();
                 ui.checkbox(&mut ui_state.show_fps, "fps counter");
+                ui.checkbox(&mut ui_state.show_probe, "Cursor probe");
                 if ui.checkbox(&mut ui_state.vsync, "V-Sync").clicked() {
                     self.send_event(UserEvent::SetVSync(ui_state.vsync));
                 }
+                ui.separator();
+                ui_state.clock.ui(ui);
+                if let Some(audio) = &ui_state.audio {
+                    ui.label(audio);
+                    spectrum_ui(ui, &ui_state.spectrum);
+                }
+                if controller.has_stereo() {
+                    ui.separator();
+                    ui_state.stereo.ui(ui);
+                }
             });
-        if controller.has_ui() {
-            egui::Window::new(ui_state.active_shader.to_string())
-                .resizable(false)
-                .anchor(Align2::RIGHT_TOP, window_margin * vec2(-1.0, 1.0))
-                .default_width(130.0)
-                .show(ctx, |ui| {
+        egui::Window::new(ui_state.active_shader.to_string())
+            .resizable(false)
+            .anchor(Align2::RIGHT_TOP, window_margin * vec2(-1.0, 1.0))
+            .default_width(130.0)
+            .show(ctx, |ui| {
+                if controller.has_ui() {
                     controller.ui(ctx, ui, &self.event_proxy);
+                    ui.separator();
+                }
+                ui.collapsing("Modulation", |ui| ui_state.modulation.ui(ui, controller));
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.collapsing("Presets", |ui| {
+                    ui_state.presets.ui(ui, controller, &mut ui_state.modulation)
                 });
+            });
+        if let Some(probe) = ui_state.show_probe.then(|| controller.probe()).flatten() {
+            egui::Window::new("Probe")
+                .resizable(false)
+                .anchor(Align2::LEFT_BOTTOM, window_margin * vec2(1.0, -1.0))
+                .show(ctx, |ui| probe.ui(ui));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(inspection) = &ui_state.inspection {
+            let mut open = true;
+            egui::Window::new("Inspector")
+                .open(&mut open)
+                .resizable(false)
+                .anchor(Align2::CENTER_BOTTOM, vec2(0.0, -window_margin))
+                .show(ctx, |ui| inspection.ui(ui));
+            if !open {
+                ui_state.inspection = None;
+                controller.inspect(None);
+            }
         }
         if ui_state.show_fps {
             egui::Window::new("fps")
@@ -127,3 +192,18 @@ impl Ui {
         }
     }
 }
+
+/// Bars of the band energies, lowest first.
+fn spectrum_ui(ui: &mut egui::Ui, spectrum: &Spectrum) {
+    let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 32.0), Sense::hover());
+    let width = rect.width() / spectrum.bands.len() as f32;
+    let color = ui.visuals().selection.bg_fill;
+    for (i, band) in spectrum.bands.iter().enumerate() {
+        let left = rect.left() + i as f32 * width;
+        let bar = Rect::from_min_max(
+            pos2(left + 1.0, rect.bottom() - band * rect.height()),
+            pos2(left + width - 1.0, rect.bottom()),
+        );
+        ui.painter().rect_filled(bar, 0.0, color);
+    }
+}
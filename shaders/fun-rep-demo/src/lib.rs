@@ -2,6 +2,7 @@
 
 use push_constants::fun_rep_demo::ShaderConstants;
 use shared::interpreter::{Interpreter, OpCodeStruct};
+use shared::palette::Palette;
 use shared::sdf_2d as sdf;
 use shared::*;
 use spirv_std::glam::{vec3, Vec2, Vec3, Vec4, Vec4Swizzles};
@@ -20,6 +21,7 @@ pub fn main_fs(
     #[spirv(frag_coord)] frag_coord: Vec4,
     #[spirv(push_constant)] constants: &ShaderConstants,
     #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] ops: &[OpCodeStruct],
+    #[spirv(storage_buffer, descriptor_set = 1, binding = 0)] palette: &Palette,
     output: &mut Vec4,
 ) {
     let uv = constants.zoom * from_pixels(frag_coord.xy(), constants.size);
@@ -29,9 +31,9 @@ pub fn main_fs(
         let d = sdf(uv, ops);
 
         let mut col = if d < 0.0 {
-            vec3(0.65, 0.85, 1.0)
+            palette.sample(0.0)
         } else {
-            vec3(0.9, 0.6, 0.3)
+            palette.sample(1.0)
         };
         col *= 1.0 - (-6.0 * d.abs()).exp();
         col *= 0.8 + 0.2 * (150.0 * d).cos();
@@ -56,11 +56,15 @@ pub fn main_fs(
     output: &mut Vec4,
 ) {
     let translate: Vec2 = constants.translate.into();
-    let uv = from_pixels(frag_coord.xy(), constants.size);
+    let eye = constants.eye;
+    let uv = from_pixels(eye.frag_coord(frag_coord.xy(), constants.size), constants.size);
 
+    // The frustum is skewed so that both eyes converge on the nucleus
     let rm = Mat3::from_rotation_y(translate.x).mul_mat3(&Mat3::from_rotation_x(translate.y));
-    let ro = rm * Vec2::ZERO.extend(-constants.camera_distance);
-    let rd = rm * uv.extend(1.0).normalize();
+    let ro = rm * vec3(eye.offset, 0.0, -constants.camera_distance);
+    let rd = rm * (uv - eye.offset / constants.camera_distance * Vec2::X)
+        .extend(1.0)
+        .normalize();
 
     let z = integrate_ray(
         constants.n,
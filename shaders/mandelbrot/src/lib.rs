@@ -1,30 +1,27 @@
 #![cfg_attr(target_arch = "spirv", no_std)]
 
 use complex::Complex;
+use palette::Palette;
 use push_constants::mandelbrot::ShaderConstants;
 use shared::*;
-use spirv_std::glam::{Vec2, Vec3, Vec4, Vec4Swizzles};
+use spirv_std::glam::{Vec2, Vec4, Vec4Swizzles};
 use spirv_std::spirv;
 
 #[spirv(fragment)]
 pub fn main_fs(
     #[spirv(frag_coord)] frag_coord: Vec4,
     #[spirv(push_constant)] constants: &ShaderConstants,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] palette: &Palette,
     output: &mut Vec4,
 ) {
     let translate: Vec2 = constants.translate.into();
     let uv: Complex =
         (constants.zoom * from_pixels(frag_coord.xy() + translate, constants.size)).into();
 
-    let mut z = Complex::ZERO;
-    let mut n = constants.num_iterations;
-    while z.norm_squared() < 4.0 && n > 0 {
-        z = z.powf(constants.exponent as f32) + uv;
-        n -= 1;
-    }
+    let n = mandelbrot::iterations(uv, constants.exponent, constants.num_iterations);
 
-    let c = n as f32 / constants.num_iterations as f32;
-    *output = Vec3::splat(c).extend(1.0);
+    let c = 1.0 - n as f32 / constants.num_iterations as f32;
+    *output = palette.sample(c).extend(1.0);
 }
 
 #[spirv(vertex)]
@@ -1,8 +1,10 @@
 #![cfg_attr(target_arch = "spirv", no_std)]
 
 use crate::functional::vec::*;
+use dfutils::sdf::Blend;
 use push_constants::procedural_generation::ShaderConstants;
 use sdf_3d as sdf;
+use shared::spectrum::Spectrum;
 use shared::*;
 use spirv_std::glam::*;
 #[cfg_attr(not(target_arch = "spirv"), allow(unused_imports))]
@@ -32,15 +34,6 @@ fn ray_march(ro: Vec3, rd: Vec3) -> f32 {
 
     d0
 }
-fn smin(d1: f32, d2: f32, k: f32) -> f32 {
-    let h = saturate(0.5 + 0.5 * (d2 - d1) / k);
-    mix(d2, d1, h) - k * h * (1.0 - h)
-}
-
-fn smax(d1: f32, d2: f32, k: f32) -> f32 {
-    let h = saturate(0.5 - 0.5 * (d2 - d1) / k);
-    mix(d2, d1, h) + k * h * (1.0 - h)
-}
 
 fn sph(i: Vec3, f: Vec3, c: Vec3) -> f32 {
     // random radius at grid vertex i+c
@@ -75,8 +68,8 @@ fn sd_fbm(mut p: Vec3, mut d: f32) -> f32 {
         let n = s * sd_base(p);
 
         // Add
-        let n = smax(n, d - 0.1 * s, 0.3 * s);
-        d = smin(n, d, 0.3 * s);
+        let n = Blend::Smooth.intersect(n, d - 0.1 * s, 0.3 * s);
+        d = Blend::Smooth.union(n, d, 0.3 * s);
 
         // Prepare next octave
         p = M * p;
@@ -89,6 +82,7 @@ fn sd_fbm(mut p: Vec3, mut d: f32) -> f32 {
 pub fn main_fs(
     #[spirv(frag_coord)] frag_coord: Vec4,
     #[spirv(push_constant)] constants: &ShaderConstants,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] spectrum: &Spectrum,
     output: &mut Vec4,
 ) {
     let uv = from_pixels(frag_coord.xy(), constants.size);
@@ -112,6 +106,9 @@ pub fn main_fs(
             vec3(x, x * x, x * x)
         }
     };
+    // The terrain glows with the bass and turns blue with the treble
+    let col = col * (1.0 + 2.0 * spectrum.bass())
+        + spectrum.treble() * vec3(0.0, 0.05, 0.2) / d.max(1.0);
 
     *output = col.powf(2.2).extend(1.0);
 }
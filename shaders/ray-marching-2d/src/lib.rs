@@ -2,6 +2,7 @@
 
 use push_constants::ray_marching_2d::ShaderConstants;
 use shared::sdf_2d as sdf;
+use shared::spectrum::Spectrum;
 use shared::*;
 use spirv_std::glam::{vec2, vec3, Mat2, Vec2, Vec4};
 use spirv_std::num_traits::Euclid;
@@ -18,9 +19,11 @@ macro_rules! min {
     ($x: expr, $($y: expr), *$(,)?) => (min!($($y),*).min($x))
 }
 
-fn sdf(p: Vec2, time: f32) -> f32 {
+/// `bass` between 0 and 1 thickens the torus.
+fn sdf(p: Vec2, time: f32, bass: f32) -> f32 {
     let c = 0.6;
     let r = 0.15;
+    let thickness = 0.03 + 0.03 * bass;
     let x = Euclid::rem_euclid(&(time / 2.0), &4.0);
     let angle = c / (2.0 * r)
         * if x > 3.0 {
@@ -31,7 +34,7 @@ fn sdf(p: Vec2, time: f32) -> f32 {
             x
         };
     min!(
-        sdf::torus(p - vec2(0.0, -0.2), vec2(r - 0.03 - 0.005, 0.03)),
+        sdf::torus(p - vec2(0.0, -0.2), vec2(r - thickness - 0.005, thickness)),
         sdf::capsule(
             Mat2::from_angle(angle).mul_vec2(p - vec2(0.0, -0.2)) - vec2(-r * angle, r),
             vec2(-c / 2.0, 0.0),
@@ -52,8 +55,10 @@ fn from_pixels(x: f32, y: f32, constants: &ShaderConstants) -> Vec2 {
 pub fn main_fs(
     #[spirv(frag_coord)] frag_coord: Vec4,
     #[spirv(push_constant)] constants: &ShaderConstants,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] spectrum: &Spectrum,
     output: &mut Vec4,
 ) {
+    let bass = spectrum.bass();
     let uv = from_pixels(frag_coord.x, frag_coord.y, constants);
     let cursor = from_pixels(constants.cursor_x, constants.cursor_y, constants);
     let ro = from_pixels(constants.drag_end_x, constants.drag_end_y, constants);
@@ -61,7 +66,7 @@ pub fn main_fs(
     let rd = (0.99999 * cursor - ro).normalize_or_zero();
 
     let mut col = {
-        let d = sdf(uv, constants.time);
+        let d = sdf(uv, constants.time, bass);
 
         if d < 0.0 {
             vec3(10.0 * -d, -d, 0.0)
@@ -73,7 +78,7 @@ pub fn main_fs(
     let mut d0 = 0.0;
     for _ in 0..MAX_STEPS {
         let p = ro + rd * d0;
-        let ds = sdf(p, constants.time).abs();
+        let ds = sdf(p, constants.time, bass).abs();
         col = col
             .lerp(
                 vec3(0.0, 0.6, 0.0),
@@ -101,6 +106,7 @@ pub fn main_fs(
         }
     }
 
+    let col = col + 0.2 * spectrum.treble() * vec3(0.2, 0.4, 1.0);
     *output = col.extend(1.0);
 }
 
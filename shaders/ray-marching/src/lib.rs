@@ -1,9 +1,10 @@
 #![cfg_attr(target_arch = "spirv", no_std)]
 
-use push_constants::ray_marching::ShaderConstants;
+use core::f32::consts::{PI, TAU};
+use push_constants::ray_marching::{DebugValues, Projection, ShaderConstants};
 use shared::sdf_3d as sdf;
 use shared::*;
-use spirv_std::glam::{vec2, vec3, Quat, Vec2Swizzles, Vec3, Vec4, Vec4Swizzles};
+use spirv_std::glam::{vec2, vec3, Quat, Vec2, Vec2Swizzles, Vec3, Vec4, Vec4Swizzles};
 #[cfg_attr(not(target_arch = "spirv"), allow(unused_imports))]
 use spirv_std::num_traits::Float;
 use spirv_std::spirv;
@@ -12,9 +13,10 @@ const MAX_STEPS: u32 = 200;
 const MAX_DIST: f32 = 100.0;
 const SURF_DIST: f32 = 0.0001;
 const NUM_REFLECTIONS: u32 = 8;
+const ORTHOGRAPHIC_HEIGHT: f32 = 4.0;
 
 #[repr(u32)]
-#[derive(PartialEq, Default)]
+#[derive(PartialEq, Default, Clone, Copy)]
 pub enum Material {
     #[default]
     Matte,
@@ -80,13 +82,16 @@ fn sdf(p: Vec3, _time: f32) -> SdfResult {
 struct RayMarchResult {
     distance: f32,
     material: Material,
+    steps: u32,
 }
 
 fn ray_march(ro: Vec3, rd: Vec3, time: f32) -> RayMarchResult {
     let mut d0 = 0.0;
     let mut material = Material::default();
+    let mut steps = 0;
 
-    for _ in 0..MAX_STEPS {
+    while steps < MAX_STEPS {
+        steps += 1;
         let p = ro + rd * d0;
         let result = sdf(p, time);
         let ds = result.distance;
@@ -103,6 +108,7 @@ fn ray_march(ro: Vec3, rd: Vec3, time: f32) -> RayMarchResult {
     RayMarchResult {
         distance: d0,
         material,
+        steps,
     }
 }
 
@@ -166,18 +172,71 @@ fn get_light(p: Vec3, time: f32) -> f32 {
     dif.max(0.02) * ao.max(0.02) * shadows.max(0.05)
 }
 
+/// The origin and direction of the ray through `uv`, relative to the camera.
+fn camera_ray(uv: Vec2, aspect_ratio: f32, projection: Projection) -> (Vec3, Vec3) {
+    match projection {
+        Projection::Pinhole => (Vec3::ZERO, uv.extend(-1.0).normalize()),
+        Projection::Equirectangular => {
+            let longitude = TAU * uv.x / aspect_ratio;
+            let latitude = PI * uv.y;
+            let rd = vec3(
+                longitude.sin() * latitude.cos(),
+                latitude.sin(),
+                -longitude.cos() * latitude.cos(),
+            );
+            (Vec3::ZERO, rd)
+        }
+        Projection::Fisheye => {
+            // Equidistant, with a field of view of 180° across the height
+            let r = uv.length();
+            let theta = PI * r;
+            let rd = (theta.sin() * uv / r.max(f32::EPSILON)).extend(-theta.cos());
+            (Vec3::ZERO, rd)
+        }
+        Projection::Orthographic => ((ORTHOGRAPHIC_HEIGHT * uv).extend(0.0), -Vec3::Z),
+    }
+}
+
 #[spirv(fragment)]
 pub fn main_fs(
     #[spirv(frag_coord)] frag_coord: Vec4,
     #[spirv(push_constant)] constants: &ShaderConstants,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] debug: &mut DebugValues,
     output: &mut Vec4,
 ) {
-    let uv = from_pixels(frag_coord.xy(), constants.size);
-    let rot = Quat::from_rotation_y(constants.yaw) * Quat::from_rotation_x(constants.pitch);
-    let mut rd = rot * vec3(uv.x, uv.y, -1.0).normalize();
+    let uv = from_pixels(
+        constants.eye.frag_coord(frag_coord.xy(), constants.size),
+        constants.size,
+    );
+    let projection = Projection::from_u32(constants.projection);
+    // Panoramas keep the horizon level
+    let rot = if projection == Projection::Equirectangular {
+        Quat::from_rotation_y(constants.yaw)
+    } else {
+        Quat::from_rotation_y(constants.yaw) * Quat::from_rotation_x(constants.pitch)
+    };
+    let (ray_origin, ray_direction) =
+        camera_ray(uv, constants.size.aspect_ratio(), projection);
+    let mut rd = rot * ray_direction;
     let mut ro: Vec3 = constants.pos.into();
+    ro += rot * (ray_origin + constants.eye.offset * Vec3::X);
     let mut result = ray_march(ro, rd, constants.time);
     let mut num_mirrored = 0;
+    let inspected =
+        frag_coord.x as u32 == constants.inspect.x && frag_coord.y as u32 == constants.inspect.y;
+    if inspected {
+        *debug = DebugValues {
+            steps: result.steps,
+            distance: result.distance,
+            normal: if result.distance < MAX_DIST {
+                get_normal(ro + rd * result.distance, constants.time).into()
+            } else {
+                Vec3::ZERO.into()
+            },
+            material: result.material as u32,
+            reflections: 0,
+        };
+    }
 
     for _ in 0..NUM_REFLECTIONS {
         match result.material {
@@ -193,6 +252,9 @@ pub fn main_fs(
             }
         }
     }
+    if inspected {
+        debug.reflections = num_mirrored;
+    }
     let col = if result.distance >= MAX_DIST {
         let p = ro + rd * result.distance;
         vec3(0.1 * result.distance / p.y, 0.2, 0.1)
@@ -1,6 +1,7 @@
 #![cfg_attr(target_arch = "spirv", no_std)]
 
 use dfutils::gridref::*;
+use palette::Palette;
 use push_constants::sdf_builder::ShaderConstants;
 use sdf_wrapper::WrappedDistance;
 use shared::*;
@@ -20,6 +21,7 @@ pub fn main_fs(
     #[spirv(frag_coord)] frag_coord: Vec4,
     #[spirv(push_constant)] constants: &ShaderConstants,
     #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] grid_buffer: &[T],
+    #[spirv(storage_buffer, descriptor_set = 1, binding = 0)] palette: &Palette,
     output: &mut Vec4,
 ) {
     let uv = from_pixels(frag_coord.xy(), constants.size);
@@ -31,9 +33,9 @@ pub fn main_fs(
     );
     let T { d, data: id } = sdf(uv, grid);
     let mut col = if d < 0.0 {
-        vec3(0.65, 0.85, 1.0)
+        palette.sample(0.0)
     } else {
-        vec3(0.9, 0.6, 0.3)
+        palette.sample(1.0)
     };
     col *= 1.0 - (-20.0 * d.abs()).exp();
     col *= 0.8 + 0.2 * (300.0 * d).cos();
@@ -110,11 +110,13 @@ pub fn main_fs(
     let translate: Vec2 = constants.translate.into();
     let cursor: Vec3 = constants.cursor.into();
 
-    let uv = from_pixels(frag_coord.xy(), constants.size);
+    let eye = constants.eye;
+    let uv = from_pixels(eye.frag_coord(frag_coord.xy(), constants.size), constants.size);
 
+    // The frustum is skewed so that both eyes converge on the origin
     let rm = Mat3::from_rotation_y(translate.x).mul_mat3(&Mat3::from_rotation_x(translate.y));
-    let ro = rm.mul_vec3(-Vec3::Z);
-    let rd = rm.mul_vec3(uv.extend(1.0)).normalize();
+    let ro = rm.mul_vec3(vec3(eye.offset, 0.0, -1.0));
+    let rd = rm.mul_vec3((uv - eye.offset * Vec2::X).extend(1.0)).normalize();
 
     let slice_z = constants.slice_z;
     let mouse_pressed = constants.mouse_button_pressed & 1 != 0;
@@ -6,7 +6,9 @@ pub mod complex;
 pub mod fast_optional;
 pub mod functional;
 pub mod interpreter;
+pub mod mandelbrot;
 pub mod numeric_integration;
+pub mod palette;
 pub mod push_constants;
 pub mod random;
 pub mod ray_intersection;
@@ -14,6 +16,7 @@ pub mod sdf_2d;
 pub mod sdf_3d;
 pub mod sdf_interpreter;
 pub mod sdf_wrapper;
+pub mod spectrum;
 pub mod spherical_harmonics;
 pub mod stack;
 pub mod voronoi;
@@ -0,0 +1,13 @@
+use crate::complex::Complex;
+
+/// Number of iterations of `z = z^exponent + c` before `z` leaves the
+/// disk of radius 2, at most `max_iterations`.
+pub fn iterations(c: Complex, exponent: f32, max_iterations: u32) -> u32 {
+    let mut z = Complex::ZERO;
+    let mut n = 0;
+    while z.norm_squared() < 4.0 && n < max_iterations {
+        z = z.powf(exponent) + c;
+        n += 1;
+    }
+    n
+}
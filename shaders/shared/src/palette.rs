@@ -0,0 +1,61 @@
+//! Color palettes shared by the shaders, bound as a storage buffer holding a
+//! [`Palette`]. Shaders opt in by declaring it as their last bind group.
+
+use crate::push_constants::Vec4;
+use bytemuck::{Pod, Zeroable};
+use spirv_std::glam::{vec3, Vec3};
+#[cfg_attr(not(target_arch = "spirv"), allow(unused_imports))]
+use spirv_std::num_traits::Float;
+
+/// Number of colors of a sampled gradient.
+pub const LUT_SIZE: usize = 256;
+
+/// `a + b * cos(2π * (c * t + d))` per channel, from
+/// <https://iquilezles.org/articles/palettes/>. The `w` components are unused.
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+pub struct CosinePalette {
+    pub a: Vec4,
+    pub b: Vec4,
+    pub c: Vec4,
+    pub d: Vec4,
+}
+
+impl CosinePalette {
+    pub fn sample(&self, t: f32) -> Vec3 {
+        let x = 2.0 * core::f32::consts::PI * (rgb(self.c) * t + rgb(self.d));
+        rgb(self.a) + rgb(self.b) * vec3(x.x.cos(), x.y.cos(), x.z.cos())
+    }
+}
+
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+pub struct Palette {
+    /// [`Palette::COSINE`] or [`Palette::LUT`].
+    pub kind: u32,
+    pub _padding: [u32; 3],
+    pub cosine: CosinePalette,
+    /// A gradient sampled at evenly spaced points from 0 to 1, the `w`
+    /// components are unused.
+    pub lut: [Vec4; LUT_SIZE],
+}
+
+impl Palette {
+    pub const COSINE: u32 = 0;
+    pub const LUT: u32 = 1;
+
+    /// The color at `t`, clamped to between 0 and 1.
+    pub fn sample(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0);
+        if self.kind == Self::COSINE {
+            return self.cosine.sample(t);
+        }
+        let x = t * (LUT_SIZE - 1) as f32;
+        let i = (x as usize).min(LUT_SIZE - 2);
+        rgb(self.lut[i]).lerp(rgb(self.lut[i + 1]), x - i as f32)
+    }
+}
+
+fn rgb(v: Vec4) -> Vec3 {
+    vec3(v.x, v.y, v.z)
+}
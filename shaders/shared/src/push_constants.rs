@@ -51,6 +51,36 @@ impl From<winit::dpi::PhysicalSize<u32>> for Size {
     }
 }
 
+/// The view of one eye of a stereo pair, drawn into a horizontal slice of the frame.
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+pub struct Eye {
+    /// Sideways offset of the camera, negative for the left eye.
+    pub offset: f32,
+    /// Horizontal start and width of the eye's viewport in pixels.
+    pub viewport_x: f32,
+    pub viewport_width: f32,
+}
+
+impl Eye {
+    /// A single centered view covering the whole frame.
+    pub fn mono(width: u32) -> Self {
+        Self {
+            offset: 0.0,
+            viewport_x: 0.0,
+            viewport_width: width as f32,
+        }
+    }
+
+    /// Maps a fragment coordinate in the eye's viewport to the full frame.
+    pub fn frag_coord(self, frag_coord: glam::Vec2, size: Size) -> glam::Vec2 {
+        glam::vec2(
+            (frag_coord.x - self.viewport_x) * size.width as f32 / self.viewport_width,
+            frag_coord.y,
+        )
+    }
+}
+
 #[derive(Copy, Clone, Pod, Zeroable, PartialEq)]
 #[repr(C)]
 pub struct Vec2 {
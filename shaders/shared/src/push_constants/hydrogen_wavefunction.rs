@@ -1,4 +1,4 @@
-use super::{Size, Vec2};
+use super::{Eye, Size, Vec2};
 use bytemuck::{Pod, Zeroable};
 
 #[derive(Copy, Clone, Pod, Zeroable)]
@@ -15,4 +15,5 @@ pub struct ShaderConstants {
     pub l: u32,
     pub m: i32,
     pub normalization_constant: f32,
+    pub eye: Eye,
 }
@@ -1,6 +1,27 @@
-use super::{Size, Vec3};
+use super::{Eye, Size, UVec2, Vec3};
 use bytemuck::{Pod, Zeroable};
 
+#[cfg_attr(not(target_arch = "spirv"), derive(strum::EnumIter, strum::Display))]
+#[derive(PartialEq, Copy, Clone)]
+#[repr(u32)]
+pub enum Projection {
+    Pinhole,
+    Equirectangular,
+    Fisheye,
+    Orthographic,
+}
+
+impl Projection {
+    pub fn from_u32(x: u32) -> Self {
+        match x {
+            1 => Projection::Equirectangular,
+            2 => Projection::Fisheye,
+            3 => Projection::Orthographic,
+            _ => Projection::Pinhole,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Pod, Zeroable)]
 #[repr(C)]
 pub struct ShaderConstants {
@@ -9,4 +30,19 @@ pub struct ShaderConstants {
     pub pos: Vec3,
     pub yaw: f32,
     pub pitch: f32,
+    pub projection: u32,
+    /// The pixel whose [`DebugValues`] are written, or `u32::MAX` for none.
+    pub inspect: UVec2,
+    pub eye: Eye,
+}
+
+/// Values of the primary ray through the inspected pixel, written to a storage buffer.
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+pub struct DebugValues {
+    pub steps: u32,
+    pub distance: f32,
+    pub normal: Vec3,
+    pub material: u32,
+    pub reflections: u32,
 }
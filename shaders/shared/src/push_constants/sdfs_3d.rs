@@ -1,4 +1,4 @@
-use super::{Size, Vec2, Vec3};
+use super::{Eye, Size, Vec2, Vec3};
 use crate::fast_optional::Optional_f32;
 use bytemuck::{Pod, Zeroable};
 
@@ -137,4 +137,5 @@ pub struct ShaderConstants {
     pub mouse_button_pressed: u32,
     pub shape: u32,
     pub params: Params,
+    pub eye: Eye,
 }
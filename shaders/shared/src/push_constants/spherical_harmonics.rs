@@ -1,6 +1,7 @@
 use crate::push_constants::{Quat, Size};
 use bytemuck::{Pod, Zeroable};
 
+#[cfg_attr(not(target_arch = "spirv"), derive(strum::EnumIter, strum::Display))]
 #[derive(PartialEq, Copy, Clone)]
 #[repr(u32)]
 pub enum Variant {
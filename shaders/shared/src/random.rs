@@ -32,3 +32,16 @@ pub fn random22(p: Vec2) -> Vec2 {
         .into();
     (v * 43758.5453).fract()
 }
+
+/// Value noise between 0 and 1, smoothly interpolating [`random21`] at the
+/// corners of the unit grid.
+pub fn value_noise21(p: Vec2) -> f32 {
+    let i = p.floor();
+    let f = p - i;
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = random21(i);
+    let b = random21(i + vec2(1.0, 0.0));
+    let c = random21(i + vec2(0.0, 1.0));
+    let d = random21(i + vec2(1.0, 1.0));
+    a + (b - a) * u.x + (c - a) * u.y + (a - b - c + d) * u.x * u.y
+}
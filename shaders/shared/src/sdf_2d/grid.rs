@@ -9,6 +9,7 @@ const NUM_Y: usize = BASE + SMOOTH_PADDING;
 const NUM_X: usize = BASE * AR + SMOOTH_PADDING;
 const HALF_CELL_SIZE: f32 = 0.5 / BASE as f32;
 
+#[derive(bytemuck::Zeroable)]
 pub struct SdfGrid {
     pub grid: [[f32; NUM_Y]; NUM_X],
 }
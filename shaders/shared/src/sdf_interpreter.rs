@@ -1,6 +1,11 @@
-use crate::stack::Stack;
+use crate::{
+    random::value_noise21,
+    sdf_2d::ops::{self, fast_repeat, repeat},
+    stack::Stack,
+};
+use core::cell::Cell;
 use dfutils::sdf::*;
-use spirv_std::glam::Vec2;
+use spirv_std::glam::{BVec2, UVec2, Vec2};
 
 #[cfg_attr(
     not(target_arch = "spirv"),
@@ -12,27 +17,217 @@ pub enum Operator {
     Intersect,
     Subtract,
     Xor,
+    /// Union with the edges blended within a radius.
+    BlendUnion(Blend, f32),
+    BlendIntersect(Blend, f32),
+    BlendSubtract(Blend, f32),
 }
 
 impl Operator {
+    /// Blend radius of new blending operators.
+    pub const DEFAULT_RADIUS: f32 = 0.05;
+
     fn operate<T>(&self, a: T, b: T) -> T
     where
         T: Copy + SignedDistance,
     {
         use Operator::*;
-        match self {
+        match *self {
             Union => a.union(&b),
             Intersect => a.intersect(&b),
             Subtract => a.subtract(&b),
             Xor => a.xor(&b),
+            BlendUnion(blend, r) => a.blend_union(&b, blend, r),
+            BlendIntersect(blend, r) => a.blend_intersect(&b, blend, r),
+            BlendSubtract(blend, r) => a.blend_subtract(&b, blend, r),
+        }
+    }
+
+    /// Every operator, with the blending ones at the default radius.
+    #[cfg(not(target_arch = "spirv"))]
+    pub fn all() -> impl Iterator<Item = Self> {
+        use strum::IntoEnumIterator;
+        Self::iter().map(|op| op.with_blend(Blend::default(), Self::DEFAULT_RADIUS))
+    }
+
+    /// The blend and radius of a blending operator.
+    pub fn blend(&self) -> Option<(Blend, f32)> {
+        use Operator::*;
+        match *self {
+            BlendUnion(blend, r) | BlendIntersect(blend, r) | BlendSubtract(blend, r) => {
+                Some((blend, r))
+            }
+            Union | Intersect | Subtract | Xor => None,
+        }
+    }
+
+    /// Sets the blend and radius of a blending operator, others are unchanged.
+    pub fn with_blend(self, blend: Blend, r: f32) -> Self {
+        use Operator::*;
+        match self {
+            BlendUnion(..) => BlendUnion(blend, r),
+            BlendIntersect(..) => BlendIntersect(blend, r),
+            BlendSubtract(..) => BlendSubtract(blend, r),
+            Union | Intersect | Subtract | Xor => self,
         }
     }
 }
 
+/// Places a shape by scaling it, then rotating it and then moving it.
 #[cfg_attr(not(target_arch = "spirv"), derive(Debug))]
-#[derive(Clone, Copy, Default, PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct Transform {
     pub position: Vec2,
+    /// Counterclockwise angle in radians.
+    pub rotation: f32,
+    /// Uniform scale, which keeps distances exact.
+    pub scale: f32,
+    /// Non-uniform scale along the axes of the shape, which turns distances
+    /// into bounds.
+    pub stretch: Vec2,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        position: Vec2::ZERO,
+        rotation: 0.0,
+        scale: 1.0,
+        stretch: Vec2::ONE,
+    };
+
+    pub fn from_position(position: Vec2) -> Self {
+        Self {
+            position,
+            ..Self::IDENTITY
+        }
+    }
+
+    fn axis_scale(&self) -> Vec2 {
+        self.scale * self.stretch
+    }
+
+    /// Maps a point of the shape to the scene.
+    pub fn transform_point(&self, p: Vec2) -> Vec2 {
+        self.position + Vec2::from_angle(self.rotation).rotate(p * self.axis_scale())
+    }
+
+    /// Maps a point of the scene to the shape, the inverse of [`Transform::transform_point`].
+    pub fn inverse_transform_point(&self, p: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.rotation).rotate(p - self.position) / self.axis_scale()
+    }
+
+    /// Scales a distance of the shape to the scene. With a stretch the
+    /// distance is scaled by the smallest scale, so that it stays a lower bound.
+    pub fn transform_distance(&self, d: f32) -> f32 {
+        d * self.axis_scale().abs().min_element()
+    }
+}
+
+/// Domain repetition, with the modes of the `sdfs-2d` shader.
+#[cfg_attr(not(target_arch = "spirv"), derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum Repetition {
+    /// Tile size.
+    Unlimited(Vec2),
+    /// Tile size and the number of tiles in the negative and positive directions.
+    Limited(Vec2, UVec2, UVec2),
+    /// Tile size and the number of tiles along the sides of the rectangle.
+    Rectangular(f32, UVec2),
+    /// Radius and number of copies around the circle.
+    Angular(f32, u32),
+    /// Tile size, every other tile being mirrored.
+    Mirrored(Vec2),
+}
+
+impl Repetition {
+    /// The closest of the copies of `sdf`, whose data is kept.
+    fn repeat<U, F>(&self, p: Vec2, sdf: F) -> U
+    where
+        U: SignedDistance,
+        F: Fn(Vec2) -> U,
+    {
+        use Repetition::*;
+        let nearest = Cell::new(U::divergent());
+        let f = |q| {
+            let d = sdf(q);
+            nearest.set(nearest.get().union(&d));
+            d.value()
+        };
+        // Only the neighbouring tiles are checked, as this runs on the CPU
+        match *self {
+            Unlimited(dim) => {
+                repeat::Repeat::<1>::repeat_xy(p, dim, f);
+            }
+            Limited(dim, n1, n2) => {
+                repeat::RepeatLimited::<1>::repeat_xy(p, dim, n1, n2, f);
+            }
+            Rectangular(dim, n) => return sdf(fast_repeat::repeat_rectangular(p, dim, n)),
+            Angular(r, n) => {
+                repeat::repeat_angular(p, r, n, f);
+            }
+            Mirrored(dim) => return sdf(fast_repeat::repeat_mirrored(p, dim)),
+        }
+        nearest.get()
+    }
+}
+
+/// Changes the point at which a subtree is evaluated.
+#[cfg_attr(not(target_arch = "spirv"), derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum PointModifier {
+    Repeat(Repetition),
+    /// Mirrors the positive side onto the negative side of the selected axes.
+    Mirror(BVec2),
+    /// Stretches the shape by cutting it at its origin and filling the gap
+    /// with a rectangle of the given half size.
+    Elongate(Vec2),
+}
+
+impl PointModifier {
+    fn modify<U, F>(&self, p: Vec2, sdf: F) -> U
+    where
+        U: SignedDistance,
+        F: Fn(Vec2) -> U,
+    {
+        use PointModifier::*;
+        match *self {
+            Repeat(repetition) => repetition.repeat(p, sdf),
+            Mirror(axes) => sdf(Vec2::select(axes, p.abs(), p)),
+            Elongate(h) => sdf(p - p.clamp(-h, h)),
+        }
+    }
+}
+
+/// Changes the distance of a subtree.
+#[cfg_attr(not(target_arch = "spirv"), derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum DistanceModifier {
+    /// Keeps a shell of the given thickness around the edge.
+    Onion(f32),
+    /// Grows the shape by a radius, rounding its corners.
+    Round(f32),
+    /// Moves the edge by value noise, which turns distances into estimates.
+    Displace { amplitude: f32, frequency: f32 },
+}
+
+impl DistanceModifier {
+    fn modify<U: SignedDistance>(&self, p: Vec2, d: U) -> U {
+        use DistanceModifier::*;
+        d.with_new_distance(match *self {
+            Onion(r) => ops::onion(d.value(), r),
+            Round(r) => ops::pad(d.value(), r),
+            Displace {
+                amplitude,
+                frequency,
+            } => d.value() + amplitude * (2.0 * value_noise21(p * frequency) - 1.0),
+        })
+    }
 }
 
 pub struct SdfInstructions<'a, U: SignedDistance, T: Copy + Sdf<T = U>> {
@@ -48,6 +243,11 @@ impl<'a, U: SignedDistance, T: Copy + Sdf<T = U>> SdfInstructions<'a, U, T> {
 pub enum Instruction<T: Copy> {
     Operator(Operator),
     Sdf(T, Transform),
+    /// Evaluates the subtree made of the next `usize` instructions at the
+    /// modified point.
+    PointModifier(PointModifier, usize),
+    /// Modifies the distance on top of the stack.
+    DistanceModifier(DistanceModifier),
 }
 
 impl<'a, U, T> Sdf for SdfInstructions<'a, U, T>
@@ -61,18 +261,182 @@ where
             return U::divergent();
         }
         let mut stack = Stack::<8, U>::new();
-        for instruction in self.instructions {
-            match instruction {
+        let mut i = 0;
+        while i < self.instructions.len() {
+            match &self.instructions[i] {
                 Instruction::Operator(op) => {
                     let b = stack.pop();
                     let a = stack.pop();
                     stack.push(op.operate(a, b));
                 }
-                Instruction::Sdf(sdf, Transform { position }) => {
-                    stack.push(sdf.signed_distance(p - *position));
+                Instruction::Sdf(sdf, transform) => {
+                    let d = sdf.signed_distance(transform.inverse_transform_point(p));
+                    stack.push(d.with_new_distance(transform.transform_distance(d.value())));
+                }
+                Instruction::PointModifier(modifier, len) => {
+                    let subtree = Self::new(&self.instructions[i + 1..i + 1 + len]);
+                    stack.push(modifier.modify(p, |q| subtree.signed_distance(q)));
+                    i += len;
+                }
+                Instruction::DistanceModifier(modifier) => {
+                    let d = stack.pop();
+                    stack.push(modifier.modify(p, d));
                 }
             }
+            i += 1;
         }
         stack.pop()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::PI;
+    use dfutils::primitives::{Disk, Rectangle};
+    use spirv_std::glam::vec2;
+
+    const POINTS: [Vec2; 5] = [
+        vec2(0.0, 0.0),
+        vec2(0.3, 0.1),
+        vec2(-0.2, 0.4),
+        vec2(0.05, -0.5),
+        vec2(-0.7, -0.2),
+    ];
+
+    fn transformed<T: Copy + Sdf<T = f32>>(shape: T, transform: Transform) -> [Instruction<T>; 1] {
+        [Instruction::Sdf(shape, transform)]
+    }
+
+    #[test]
+    fn inverse_transform_point() {
+        let transform = Transform {
+            position: vec2(0.2, -0.1),
+            rotation: 0.7,
+            scale: 1.5,
+            stretch: vec2(2.0, 0.5),
+        };
+        for p in POINTS {
+            let q = transform.inverse_transform_point(transform.transform_point(p));
+            assert!(q.distance(p) < 1e-5);
+        }
+    }
+
+    #[test]
+    fn rigid_motions_preserve_distances() {
+        let rectangle = Rectangle::new(0.4, 0.2);
+        for rotation in [0.0, 0.3, PI / 2.0, -2.0] {
+            let transform = Transform {
+                position: vec2(0.25, -0.4),
+                rotation,
+                ..Transform::IDENTITY
+            };
+            let instructions = transformed(rectangle, transform);
+            let sdf = SdfInstructions::new(&instructions);
+            for p in POINTS {
+                let d = sdf.signed_distance(transform.transform_point(p));
+                assert!((d - rectangle.signed_distance(p)).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_scale_scales_distances() {
+        let rectangle = Rectangle::new(0.4, 0.2);
+        let transform = Transform {
+            rotation: 1.0,
+            scale: 2.5,
+            ..Transform::IDENTITY
+        };
+        let instructions = transformed(rectangle, transform);
+        let sdf = SdfInstructions::new(&instructions);
+        for p in POINTS {
+            let d = sdf.signed_distance(transform.transform_point(p));
+            assert!((d - 2.5 * rectangle.signed_distance(p)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn stretch_is_a_distance_bound() {
+        let transform = Transform {
+            rotation: 0.4,
+            stretch: vec2(3.0, 0.5),
+            ..Transform::IDENTITY
+        };
+        let instructions = transformed(Disk::new(0.2), transform);
+        let sdf = SdfInstructions::new(&instructions);
+        // Points on the boundary of the ellipse
+        let boundary = (0..1000)
+            .map(|i| {
+                transform.transform_point(0.2 * Vec2::from_angle(i as f32 * 2.0 * PI / 1000.0))
+            })
+            .collect::<Vec<_>>();
+        for p in POINTS.map(|p| 2.0 * p) {
+            let d = sdf.signed_distance(p);
+            let distance = boundary
+                .iter()
+                .map(|b| b.distance(p))
+                .fold(f32::INFINITY, f32::min);
+            assert!(d.abs() <= distance + 1e-5);
+        }
+    }
+
+    #[test]
+    fn distance_modifiers() {
+        let disk = Disk::new(0.2);
+        for (modifier, expected) in [
+            (DistanceModifier::Onion(0.05), 0.25),
+            (DistanceModifier::Round(0.1), 0.3),
+        ] {
+            let instructions = [
+                Instruction::Sdf(disk, Transform::IDENTITY),
+                Instruction::DistanceModifier(modifier),
+            ];
+            let sdf = SdfInstructions::new(&instructions);
+            assert!(sdf.signed_distance(vec2(expected, 0.0)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn point_modifiers_only_apply_to_their_subtree() {
+        let disk = Disk::new(0.1);
+        let instructions = [
+            Instruction::Sdf(disk, Transform::from_position(vec2(0.0, 0.3))),
+            Instruction::PointModifier(PointModifier::Repeat(Repetition::Unlimited(Vec2::ONE)), 1),
+            Instruction::Sdf(disk, Transform::IDENTITY),
+            Instruction::Operator(Operator::Union),
+        ];
+        let sdf = SdfInstructions::new(&instructions);
+        for p in POINTS {
+            let repeated = disk.signed_distance(p - p.round());
+            let d = repeated.min(disk.signed_distance(p - vec2(0.0, 0.3)));
+            assert!((sdf.signed_distance(p) - d).abs() < 1e-5);
+            assert!((sdf.signed_distance(p + vec2(3.0, -2.0)) - repeated).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn mirror_and_elongate() {
+        let disk = Disk::new(0.1);
+        let at = |position| Transform::from_position(position);
+        let mirror = [
+            Instruction::PointModifier(PointModifier::Mirror(BVec2::new(true, false)), 1),
+            Instruction::Sdf(disk, at(vec2(0.3, 0.0))),
+        ];
+        let elongate = [
+            Instruction::PointModifier(PointModifier::Elongate(vec2(0.2, 0.0)), 1),
+            Instruction::Sdf(disk, Transform::IDENTITY),
+        ];
+        let mirror = SdfInstructions::new(&mirror);
+        let elongate = SdfInstructions::new(&elongate);
+        for p in POINTS {
+            assert_eq!(
+                mirror.signed_distance(p),
+                mirror.signed_distance(-p * vec2(1.0, -1.0))
+            );
+        }
+        assert!((mirror.signed_distance(vec2(-0.3, 0.0)) + 0.1).abs() < 1e-5);
+        assert!((elongate.signed_distance(vec2(0.3, 0.0))).abs() < 1e-5);
+        assert!((elongate.signed_distance(vec2(0.1, 0.1))).abs() < 1e-5);
+    }
+}
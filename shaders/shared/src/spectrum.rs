@@ -0,0 +1,36 @@
+use bytemuck::{Pod, Zeroable};
+
+pub const NUM_BANDS: usize = 16;
+
+/// Energies between 0 and 1 of logarithmically spaced frequency bands of an
+/// audio file at the current time, lowest first.
+#[derive(Copy, Clone, Pod, Zeroable)]
+#[repr(C)]
+pub struct Spectrum {
+    pub bands: [f32; NUM_BANDS],
+}
+
+impl Spectrum {
+    /// The mean energy of the bands from `start` up to `end`.
+    pub fn mean(&self, start: usize, end: usize) -> f32 {
+        let mut sum = 0.0;
+        let mut i = start;
+        while i < end {
+            sum += self.bands[i];
+            i += 1;
+        }
+        sum / (end - start) as f32
+    }
+
+    pub fn bass(&self) -> f32 {
+        self.mean(0, 4)
+    }
+
+    pub fn mids(&self) -> f32 {
+        self.mean(4, 10)
+    }
+
+    pub fn treble(&self) -> f32 {
+        self.mean(10, NUM_BANDS)
+    }
+}